@@ -0,0 +1,112 @@
+//! Peak memory of fetching huge library, with whole response read at once versus parsed
+//! as it arrives.
+//!
+//! Run with `cargo bench --bench library_items_memory`. Each mode is measured in separate
+//! process, since peak RSS never decreases.
+use std::env;
+use std::error::Error;
+use std::process::Command;
+use std::sync::Arc;
+
+use audiobookshelf_api::params::LibraryItemParams;
+use audiobookshelf_api::schema::Id;
+use audiobookshelf_api::transport::{Transport, TransportFuture};
+use audiobookshelf_api::{ClientConfig, Url, UserClient};
+use bytes::Bytes;
+use futures_util::{stream, StreamExt};
+
+const ITEMS: usize = 20_000;
+const MODES: [&str; 2] = ["buffered", "streaming"];
+
+/// Serves library of `ITEMS` copies of fixture item, generated while body is read.
+#[derive(Debug)]
+struct LargeLibrary {
+    item: Arc<String>,
+}
+
+impl Transport for LargeLibrary {
+    fn execute(&self, _request: reqwest::Request) -> TransportFuture<'_> {
+        let item = self.item.clone();
+        let chunks = stream::iter(0..=ITEMS + 1).map(move |i| {
+            let chunk = match i {
+                0 => r#"{"results":["#.to_string(),
+                i if i == ITEMS + 1 => format!(r#"],"total":{ITEMS},"limit":0,"page":0}}"#),
+                1 => item.to_string(),
+                _ => format!(",{item}"),
+            };
+            Ok::<_, std::io::Error>(Bytes::from(chunk))
+        });
+        let response = http::Response::builder()
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(reqwest::Body::wrap_stream(chunks))
+            .unwrap();
+        Box::pin(std::future::ready(Ok(reqwest::Response::from(response))))
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match env::args().find(|arg| MODES.contains(&arg.as_str())) {
+        Some(mode) => measure(&mode),
+        None => {
+            for mode in MODES {
+                let status = Command::new(env::current_exe()?).arg(mode).status()?;
+                if !status.success() {
+                    return Err(format!("{mode} run failed").into());
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[tokio::main]
+async fn measure(mode: &str) -> Result<(), Box<dyn Error>> {
+    let fixture: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/library_items.json"
+    ))?)?;
+    let item = serde_json::to_string(&fixture["results"][0])?;
+    let config = ClientConfig::new(Url::parse("http://localhost:13378")?)?.with_transport(
+        Arc::new(LargeLibrary {
+            item: Arc::new(item),
+        }),
+    );
+    let client = UserClient::from_token(config, "token".to_string());
+    let id = Id::new("lib_1");
+
+    let before = peak_rss_kb();
+    let count = match mode {
+        "buffered" => client
+            .library_items(&id, LibraryItemParams::default())
+            .await?
+            .len(),
+        _ => {
+            let mut items =
+                std::pin::pin!(client.library_items_stream(&id, LibraryItemParams::default()));
+            let mut count = 0;
+            while let Some(item) = items.next().await {
+                item?;
+                count += 1;
+            }
+            count
+        }
+    };
+    let after = peak_rss_kb();
+    assert_eq!(count, ITEMS);
+
+    match (before, after) {
+        (Some(before), Some(after)) => println!(
+            "{mode:>9}: {count} items, peak RSS {after} KiB (+{} KiB)",
+            after - before
+        ),
+        _ => println!("{mode:>9}: {count} items, peak RSS is unavailable on this platform"),
+    }
+    Ok(())
+}
+
+/// Peak resident set size of current process, as reported by Linux.
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
@@ -0,0 +1,21 @@
+use audiobookshelf_api::{ClientConfig, UserClient};
+use futures_util::StreamExt;
+use reqwest::Url;
+use std::env::var;
+use std::error::Error;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv::dotenv().ok();
+
+    let config = ClientConfig::new(Url::parse(&var("AUDIOBOOKSHELF_URL")?)?)?;
+    let username = var("AUDIOBOOKSHELF_USERNAME")?;
+    let password = var("AUDIOBOOKSHELF_PASSWORD")?;
+
+    let client = UserClient::auth(config, username, password).await?;
+    let mut events = client.events().await?;
+    while let Some(event) = events.next().await {
+        println!("{:#?}", event);
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! Record responses of live server into `tests/fixtures/server/<version>`, to be checked by
+//! `tests/server_fixtures.rs`. Tokens are redacted, other data is stored as is, so use
+//! server without private data.
+use audiobookshelf_api::endpoints::Endpoints;
+use audiobookshelf_api::params::LibraryItemParams;
+use audiobookshelf_api::schema::{MediaType, ServerStatus};
+use audiobookshelf_api::{ClientConfig, UserClient};
+use reqwest::Url;
+use serde_json::Value;
+use std::env::var;
+use std::error::Error;
+use std::path::PathBuf;
+
+const REDACTED: [&str; 3] = ["token", "accessToken", "refreshToken"];
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv::dotenv().ok();
+
+    let config = ClientConfig::new(Url::parse(&var("AUDIOBOOKSHELF_URL")?)?)?;
+    let endpoints = config.endpoints.clone();
+    let username = var("AUDIOBOOKSHELF_USERNAME")?;
+    let password = var("AUDIOBOOKSHELF_PASSWORD")?;
+    let client = UserClient::auth(config, username, password).await?;
+
+    let status: ServerStatus = client.server_status().await?;
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/server")
+        .join(&status.server_version);
+    std::fs::create_dir_all(&directory)?;
+
+    let library = client
+        .libraries()
+        .await?
+        .into_iter()
+        .find(|library| library.media_type == MediaType::Book)
+        .ok_or("Server has no book library")?;
+    let library_id = library.id.as_str();
+    let params = LibraryItemParams {
+        limit: 1,
+        ..Default::default()
+    };
+    let item = client
+        .library_items(&library.id, params)
+        .await?
+        .pop()
+        .ok_or("Library is empty")?;
+    let item_id = item.id.as_str();
+    let filters = client.library(&library.id).await?.filterdata;
+    let author = filters.authors.first().ok_or("Library has no authors")?;
+    let author_id = author.id.as_str();
+
+    let requests: [(&str, Url); 14] = [
+        ("status", endpoints.url(["status"])),
+        ("me", endpoints.api(["me"])),
+        ("libraries", endpoints.api(["libraries"])),
+        (
+            "library",
+            query(
+                endpoints.api(["libraries", library_id]),
+                "include=filterdata",
+            ),
+        ),
+        (
+            "library_items",
+            query(
+                endpoints.api(["libraries", library_id, "items"]),
+                "limit=5&minified=1",
+            ),
+        ),
+        (
+            "library_item",
+            query(endpoints.api(["items", item_id]), "include=authors"),
+        ),
+        (
+            "library_series",
+            query(
+                endpoints.api(["libraries", library_id, "series"]),
+                "limit=5",
+            ),
+        ),
+        (
+            "library_stats",
+            endpoints.api(["libraries", library_id, "stats"]),
+        ),
+        (
+            "library_narrators",
+            endpoints.api(["libraries", library_id, "narrators"]),
+        ),
+        (
+            "library_collections",
+            endpoints.api(["libraries", library_id, "collections"]),
+        ),
+        (
+            "library_playlists",
+            endpoints.api(["libraries", library_id, "playlists"]),
+        ),
+        (
+            "library_search",
+            search(&endpoints, library_id, &author.name),
+        ),
+        (
+            "author",
+            query(
+                endpoints.api(["authors", author_id]),
+                "include=items,series",
+            ),
+        ),
+        (
+            "listening_sessions",
+            query(
+                endpoints.api(["me", "listening-sessions"]),
+                "itemsPerPage=5",
+            ),
+        ),
+    ];
+
+    let http = client.client();
+    for (name, url) in requests {
+        let response = http
+            .get(url)
+            .bearer_auth(client.token())
+            .send()
+            .await?
+            .error_for_status()?;
+        let mut json: Value = serde_json::from_str(&response.text().await?)?;
+        redact(&mut json);
+        let path = directory.join(format!("{name}.json"));
+        std::fs::write(&path, serde_json::to_string_pretty(&json)? + "\n")?;
+        println!("Recorded {}", path.display());
+    }
+    Ok(())
+}
+
+fn query(mut url: Url, query: &str) -> Url {
+    url.set_query(Some(query));
+    url
+}
+
+fn search(endpoints: &Endpoints, library_id: &str, text: &str) -> Url {
+    let mut url = endpoints.api(["libraries", library_id, "search"]);
+    url.query_pairs_mut()
+        .append_pair("q", text)
+        .append_pair("limit", "5");
+    url
+}
+
+fn redact(json: &mut Value) {
+    match json {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if REDACTED.contains(&key.as_str()) && value.is_string() {
+                    *value = Value::String("redacted".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
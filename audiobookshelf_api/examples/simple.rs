@@ -1,4 +1,4 @@
-use audiobookshelf_api::params::{LibraryItemFilter, LibraryItemParams, PlayLibraryItemParams};
+use audiobookshelf_api::params::{Filter, LibraryItemParams, PlayLibraryItemParams};
 use audiobookshelf_api::{ClientConfig, UserClient};
 use reqwest::Url;
 use std::env::var;
@@ -8,9 +8,7 @@ use std::error::Error;
 async fn main() -> Result<(), Box<dyn Error>> {
     dotenv::dotenv().ok();
 
-    let config = ClientConfig {
-        root_url: Url::parse(&var("AUDIOBOOKSHELF_URL")?)?,
-    };
+    let config = ClientConfig::new(Url::parse(&var("AUDIOBOOKSHELF_URL")?)?)?;
     let username = var("AUDIOBOOKSHELF_USERNAME")?;
     let password = var("AUDIOBOOKSHELF_PASSWORD")?;
 
@@ -23,10 +21,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .library_items(
             &library.id,
             LibraryItemParams {
-                filter: LibraryItemFilter {
-                    series: vec![filters.series[0].id.clone()],
-                    ..Default::default()
-                },
+                filter: Some(Filter::Series(filters.series[0].id.clone())),
                 ..Default::default()
             },
         )
@@ -40,7 +35,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let user_data = client.me().await?;
     if let Some(library_item) = user_data.currently_listening() {
         let playback = client
-            .library_item_play(&library_item, &PlayLibraryItemParams::default())
+            .library_item_play(&library_item, &PlayLibraryItemParams::builder().build())
             .await?;
         println!("{:#?}", playback);
     } else {
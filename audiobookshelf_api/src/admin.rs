@@ -0,0 +1,692 @@
+//! Server administration, available to users of `admin` and `root` types.
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
+use reqwest::{Method, Url};
+
+use crate::errors::{FusedError, RequestError};
+use crate::params::{
+    CreateCustomMetadataProviderParams, CreateNotificationParams, CreateShareParams,
+    CreateUserParams, EmbedMetadataParams, EncodeM4bParams, RenameGenreParams, RenameTagParams,
+    UpdateNotificationParams, UpdateNotificationSettingsParams, UpdateServerSettingsParams,
+    UpdateUserParams, WatchedPathChange, WatcherUpdateParams,
+};
+use crate::schema::{
+    AuthorizeResponse, CustomMetadataProvider, CustomMetadataProviderResponse,
+    CustomMetadataProviders, FilesystemDirectories, FilesystemDirectory, Genres, Id, Library,
+    LibraryItem, ListeningSessions, LogEntry, LogLevel, LoggerData, MediaItemShare, Notification,
+    NotificationSettings, NotificationSettingsResponse, NotificationsResponse, ScanResponse,
+    ScanResult, ServerSettings, ServerSettingsResponse, ServerYearStats, Tags, TagsUpdate, Task,
+    Tasks, User, UserData, UserResponse, Users,
+};
+use crate::{ClientConfig, UserClient};
+
+/// Administrative endpoints, obtained with `UserClient::admin`.
+pub struct AdminClient<'a> {
+    client: &'a UserClient,
+}
+
+impl ClientConfig {
+    /// `kind` is either `tags` or `genres`
+    fn tags_url(&self, kind: &str, path: Option<&str>) -> Url {
+        self.endpoints.api([kind].into_iter().chain(path))
+    }
+
+    fn notifications_url(&self) -> Url {
+        self.endpoints.api(["notifications"])
+    }
+
+    /// `path` is either `test`, `<ID>/test` or `<ID>`
+    fn notification_url(&self, path: &[&str]) -> Url {
+        self.endpoints.api(["notifications"].iter().chain(path))
+    }
+
+    /// `action` is either `embed-metadata` or `encode-m4b`
+    fn tools_url(&self, id: &str, action: &str) -> Url {
+        self.endpoints.api(["tools", "item", id, action])
+    }
+
+    fn logger_data_url(&self) -> Url {
+        self.endpoints.api(["logger-data"])
+    }
+
+    fn share_url(&self, id: Option<&str>) -> Url {
+        self.endpoints
+            .api(["share", "mediaitem"].into_iter().chain(id))
+    }
+
+    fn year_stats_url(&self, year: i32) -> Url {
+        self.endpoints.api(["stats", "year", &year.to_string()])
+    }
+
+    fn custom_metadata_providers_url(&self, id: Option<&str>) -> Url {
+        self.endpoints
+            .api(["custom-metadata-providers"].into_iter().chain(id))
+    }
+
+    fn item_scan_url(&self, id: &str) -> Url {
+        self.endpoints.api(["items", id, "scan"])
+    }
+
+    fn watcher_url(&self) -> Url {
+        self.endpoints.api(["watcher", "update"])
+    }
+
+    fn authorize_url(&self) -> Url {
+        self.endpoints.api(["authorize"])
+    }
+
+    fn settings_url(&self) -> Url {
+        self.endpoints.api(["settings"])
+    }
+
+    fn filesystem_url(&self) -> Url {
+        self.endpoints.api(["filesystem"])
+    }
+
+    fn tasks_url(&self) -> Url {
+        self.endpoints.api(["tasks"])
+    }
+
+    fn users_url(&self) -> Url {
+        self.endpoints.api(["users"])
+    }
+
+    fn user_url(&self, id: &str) -> Url {
+        self.endpoints.api(["users", id])
+    }
+
+    fn user_listening_sessions_url(&self, id: &str) -> Url {
+        self.endpoints.api(["users", id, "listening-sessions"])
+    }
+}
+
+impl UserClient {
+    /// Administrative endpoints. Server rejects them unless user is admin.
+    pub fn admin(&self) -> AdminClient<'_> {
+        AdminClient { client: self }
+    }
+}
+
+impl AdminClient<'_> {
+    pub async fn users(&self) -> Result<Vec<User>, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::GET, self.client.config.users_url());
+
+        let result = self
+            .client
+            .send::<Users>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.users)
+    }
+
+    pub async fn user(&self, id: &Id<UserData>) -> Result<User, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::GET, self.client.config.user_url(id.as_str()));
+
+        self.client
+            .send::<User>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn create_user(
+        &self,
+        params: &CreateUserParams,
+    ) -> Result<User, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::POST, self.client.config.users_url())
+            .body(body);
+
+        let result = self
+            .client
+            .send::<UserResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.user)
+    }
+
+    pub async fn update_user(
+        &self,
+        id: &Id<UserData>,
+        params: &UpdateUserParams,
+    ) -> Result<User, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::PATCH, self.client.config.user_url(id.as_str()))
+            .body(body);
+
+        let result = self
+            .client
+            .send::<UserResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.user)
+    }
+
+    pub async fn delete_user(&self, id: &Id<UserData>) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::DELETE, self.client.config.user_url(id.as_str()));
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Page of listening sessions of user, newest first. `page` is counted from zero.
+    pub async fn user_listening_sessions(
+        &self,
+        id: &Id<UserData>,
+        items_per_page: usize,
+        page: usize,
+    ) -> Result<ListeningSessions, FusedError<RequestError>> {
+        let url = self.client.config.user_listening_sessions_url(id.as_str());
+        let request_builder = self
+            .client
+            .request(Method::GET, url)
+            .query(&[("itemsPerPage", items_per_page), ("page", page)]);
+
+        self.client
+            .send::<ListeningSessions>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Notification settings, along with events notifications can be sent on.
+    pub async fn notifications(&self) -> Result<NotificationsResponse, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::GET, self.client.config.notifications_url());
+
+        self.client
+            .send::<NotificationsResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn update_notification_settings(
+        &self,
+        params: &UpdateNotificationSettingsParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::PATCH, self.client.config.notifications_url())
+            .body(body);
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    pub async fn create_notification(
+        &self,
+        params: &CreateNotificationParams,
+    ) -> Result<NotificationSettings, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::POST, self.client.config.notifications_url())
+            .body(body);
+        self.send_notification_settings(request_builder).await
+    }
+
+    pub async fn update_notification(
+        &self,
+        id: &Id<Notification>,
+        params: &UpdateNotificationParams,
+    ) -> Result<NotificationSettings, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let url = self.client.config.notification_url(&[id.as_str()]);
+        let request_builder = self.client.request(Method::PATCH, url).body(body);
+        self.send_notification_settings(request_builder).await
+    }
+
+    pub async fn delete_notification(
+        &self,
+        id: &Id<Notification>,
+    ) -> Result<NotificationSettings, FusedError<RequestError>> {
+        let url = self.client.config.notification_url(&[id.as_str()]);
+        let request_builder = self.client.request(Method::DELETE, url);
+        self.send_notification_settings(request_builder).await
+    }
+
+    async fn send_notification_settings(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<NotificationSettings, FusedError<RequestError>> {
+        let result = self
+            .client
+            .send::<NotificationSettingsResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.settings)
+    }
+
+    /// Fire `onTest` event. If `fail` is set, server simulates failed delivery.
+    pub async fn test_notifications(&self, fail: bool) -> Result<(), FusedError<RequestError>> {
+        let mut request_builder = self
+            .client
+            .request(Method::GET, self.client.config.notification_url(&["test"]));
+        if fail {
+            request_builder = request_builder.query(&[("fail", "1")]);
+        }
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Send test message with given notification.
+    pub async fn test_notification(
+        &self,
+        id: &Id<Notification>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self.client.config.notification_url(&[id.as_str(), "test"]);
+        let request_builder = self.client.request(Method::GET, url);
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// All tags used in any library.
+    pub async fn tags(&self) -> Result<Vec<String>, FusedError<RequestError>> {
+        let url = self.client.config.tags_url("tags", None);
+        let request_builder = self.client.request(Method::GET, url);
+
+        let result = self
+            .client
+            .send::<Tags>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.tags)
+    }
+
+    /// Rename tag on all items. Renaming to existing tag merges them.
+    pub async fn rename_tag(
+        &self,
+        tag: &str,
+        new_tag: &str,
+    ) -> Result<TagsUpdate, FusedError<RequestError>> {
+        let body = serde_json::to_string(&RenameTagParams { tag, new_tag }).unwrap();
+        let url = self.client.config.tags_url("tags", Some("rename"));
+        let request_builder = self.client.request(Method::POST, url).body(body);
+
+        self.client
+            .send::<TagsUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Remove tag from all items.
+    pub async fn delete_tag(&self, tag: &str) -> Result<TagsUpdate, FusedError<RequestError>> {
+        let url = self
+            .client
+            .config
+            .tags_url("tags", Some(&URL_SAFE.encode(tag)));
+        let request_builder = self.client.request(Method::DELETE, url);
+
+        self.client
+            .send::<TagsUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// All genres used in any library.
+    pub async fn genres(&self) -> Result<Vec<String>, FusedError<RequestError>> {
+        let url = self.client.config.tags_url("genres", None);
+        let request_builder = self.client.request(Method::GET, url);
+
+        let result = self
+            .client
+            .send::<Genres>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.genres)
+    }
+
+    /// Rename genre on all items. Renaming to existing genre merges them.
+    pub async fn rename_genre(
+        &self,
+        genre: &str,
+        new_genre: &str,
+    ) -> Result<TagsUpdate, FusedError<RequestError>> {
+        let body = serde_json::to_string(&RenameGenreParams { genre, new_genre }).unwrap();
+        let url = self.client.config.tags_url("genres", Some("rename"));
+        let request_builder = self.client.request(Method::POST, url).body(body);
+
+        self.client
+            .send::<TagsUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Remove genre from all items.
+    pub async fn delete_genre(&self, genre: &str) -> Result<TagsUpdate, FusedError<RequestError>> {
+        let url = self
+            .client
+            .config
+            .tags_url("genres", Some(&URL_SAFE.encode(genre)));
+        let request_builder = self.client.request(Method::DELETE, url);
+
+        self.client
+            .send::<TagsUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Write item metadata and chapters into its audio files. Runs as background task,
+    /// reported by `tasks`.
+    pub async fn embed_metadata(
+        &self,
+        id: &Id<LibraryItem>,
+        params: EmbedMetadataParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self.client.config.tools_url(id.as_str(), "embed-metadata");
+        let request_builder = self.client.request(Method::POST, url).query(&params);
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Merge audio files of book into single M4B file. Runs as background task,
+    /// reported by `tasks`.
+    pub async fn encode_m4b(
+        &self,
+        id: &Id<LibraryItem>,
+        params: EncodeM4bParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self.client.config.tools_url(id.as_str(), "encode-m4b");
+        let request_builder = self.client.request(Method::POST, url).query(&params);
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Cancel running M4B encoding of item.
+    pub async fn cancel_encode_m4b(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self.client.config.tools_url(id.as_str(), "encode-m4b");
+        let request_builder = self.client.request(Method::DELETE, url);
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Rescan files of library item right away, e.g. after editing them on disk.
+    pub async fn scan_library_item(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<ScanResult, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::POST, self.client.config.item_scan_url(id.as_str()));
+
+        let result = self
+            .client
+            .send::<ScanResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.result)
+    }
+
+    /// Report change of path inside library folder to server file watcher, so it is
+    /// picked up without waiting for filesystem events or scheduled scan.
+    pub async fn notify_watcher(
+        &self,
+        library_id: &Id<Library>,
+        change: &WatchedPathChange,
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(&WatcherUpdateParams::new(library_id, change)).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::POST, self.client.config.watcher_url())
+            .body(body);
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Enable or disable server file watcher for all libraries, returning server
+    /// settings after update.
+    pub async fn set_file_watcher(
+        &self,
+        enabled: bool,
+    ) -> Result<ServerSettings, FusedError<RequestError>> {
+        self.update_server_settings(UpdateServerSettingsParams {
+            scanner_disable_watcher: Some(!enabled),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Create public link to book. Server has no listing of shares, share of book is
+    /// included into its library item as `LibraryItem::media_item_share`.
+    pub async fn create_share(
+        &self,
+        params: &CreateShareParams,
+    ) -> Result<MediaItemShare, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::POST, self.client.config.share_url(None))
+            .body(body);
+
+        self.client
+            .send::<MediaItemShare>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn delete_share(
+        &self,
+        id: &Id<MediaItemShare>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self.client.request(
+            Method::DELETE,
+            self.client.config.share_url(Some(id.as_str())),
+        );
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Public link of share, to be opened in browser.
+    pub fn share_link(&self, share: &MediaItemShare) -> Url {
+        self.client
+            .config
+            .endpoints
+            .url(["share", share.slug.as_str()])
+    }
+
+    /// Summary of server for `year`: books added and listening of all users.
+    pub async fn year_stats(&self, year: i32) -> Result<ServerYearStats, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::GET, self.client.config.year_stats_url(year));
+
+        self.client
+            .send::<ServerYearStats>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn custom_metadata_providers(
+        &self,
+    ) -> Result<Vec<CustomMetadataProvider>, FusedError<RequestError>> {
+        let request_builder = self.client.request(
+            Method::GET,
+            self.client.config.custom_metadata_providers_url(None),
+        );
+
+        let result = self
+            .client
+            .send::<CustomMetadataProviders>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.providers)
+    }
+
+    pub async fn create_custom_metadata_provider(
+        &self,
+        params: &CreateCustomMetadataProviderParams,
+    ) -> Result<CustomMetadataProvider, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .client
+            .request(
+                Method::POST,
+                self.client.config.custom_metadata_providers_url(None),
+            )
+            .body(body);
+
+        let result = self
+            .client
+            .send::<CustomMetadataProviderResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.provider)
+    }
+
+    /// Remove custom provider. Libraries using it as default provider are switched back to
+    /// server default.
+    pub async fn delete_custom_metadata_provider(
+        &self,
+        id: &Id<CustomMetadataProvider>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self.client.request(
+            Method::DELETE,
+            self.client
+                .config
+                .custom_metadata_providers_url(Some(id.as_str())),
+        );
+
+        self.client
+            .send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Background tasks, both running and recently finished.
+    pub async fn tasks(&self) -> Result<Vec<Task>, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::GET, self.client.config.tasks_url());
+
+        let result = self
+            .client
+            .send::<Tasks>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.tasks)
+    }
+
+    /// Directories on server filesystem, to choose library folders from. Top level
+    /// directories are listed if `parent` is `None`, otherwise subdirectories of `parent`.
+    pub async fn filesystem(
+        &self,
+        parent: Option<&FilesystemDirectory>,
+    ) -> Result<FilesystemDirectories, FusedError<RequestError>> {
+        let mut request_builder = self
+            .client
+            .request(Method::GET, self.client.config.filesystem_url());
+        if let Some(parent) = parent {
+            request_builder = request_builder.query(&[
+                ("path", parent.path.clone()),
+                ("level", (parent.level + 1).to_string()),
+            ]);
+        }
+
+        self.client
+            .send::<FilesystemDirectories>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Most recent entries of today's server log, oldest first, keeping ones of
+    /// `min_level` or above. Server keeps limited number of recent entries in memory.
+    pub async fn server_logs(
+        &self,
+        min_level: LogLevel,
+    ) -> Result<Vec<LogEntry>, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::GET, self.client.config.logger_data_url());
+
+        let result = self
+            .client
+            .send::<LoggerData>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        let mut logs = result.current_daily_logs;
+        logs.retain(|entry| entry.level >= min_level);
+        Ok(logs)
+    }
+
+    /// Server settings. Server has no dedicated endpoint for reading them, so they are
+    /// taken from `POST /api/authorize`.
+    pub async fn server_settings(&self) -> Result<ServerSettings, FusedError<RequestError>> {
+        let request_builder = self
+            .client
+            .request(Method::POST, self.client.config.authorize_url());
+
+        let result = self
+            .client
+            .send::<AuthorizeResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.server_settings)
+    }
+
+    /// Change server settings, returning them after update.
+    pub async fn update_server_settings(
+        &self,
+        params: UpdateServerSettingsParams,
+    ) -> Result<ServerSettings, FusedError<RequestError>> {
+        let body = serde_json::to_string(&params).unwrap();
+        let request_builder = self
+            .client
+            .request(Method::PATCH, self.client.config.settings_url())
+            .body(body);
+
+        let result = self
+            .client
+            .send::<ServerSettingsResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.server_settings)
+    }
+}
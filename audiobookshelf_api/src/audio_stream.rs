@@ -0,0 +1,357 @@
+//! Streaming of audio files, resumed after network failures.
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, BufReader};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use futures_util::Stream;
+use stream_download::http::HttpStream;
+use stream_download::source::SourceStream;
+use stream_download::storage::bounded::BoundedStorageProvider;
+use stream_download::storage::memory::MemoryStorageProvider;
+use stream_download::storage::temp::TempStorageProvider;
+use stream_download::storage::{StorageProvider, StorageReader, StorageWriter};
+
+use crate::rate_limit::{Pacer, Throttle};
+use crate::retry::RetryPolicy;
+use crate::Url;
+
+/// Options of `UserClient::audiofile_stream_with`.
+#[derive(Clone, Default)]
+pub struct StreamOptions {
+    /// Byte offset to start streaming from. Data before it is only requested if read.
+    pub start: u64,
+    /// Reconnection after stream is interrupted. Reconnected stream continues from the
+    /// last received byte.
+    pub retry: RetryPolicy,
+    pub on_progress: Option<Arc<dyn Fn(StreamProgress) + Send + Sync>>,
+    /// Where downloaded part of the file is kept.
+    pub storage: StreamStorage,
+    /// Bandwidth limit, also applied to reconnected streams
+    pub throttle: Option<Arc<Throttle>>,
+}
+
+impl StreamOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(StreamProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn storage(mut self, storage: StreamStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn throttle(mut self, throttle: Arc<Throttle>) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+}
+
+impl std::fmt::Debug for StreamOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamOptions")
+            .field("start", &self.start)
+            .field("retry", &self.retry)
+            .field("on_progress", &self.on_progress.is_some())
+            .field("storage", &self.storage)
+            .field("throttle", &self.throttle)
+            .finish()
+    }
+}
+
+/// Download state, reported after each received chunk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamProgress {
+    /// Position of the last downloaded byte
+    pub position: u64,
+    pub content_length: Option<u64>,
+    pub complete: bool,
+}
+
+type Inner = Box<HttpStream<reqwest::Client>>;
+type Resume = Pin<Box<dyn Future<Output = (Inner, io::Result<()>)> + Send + Sync>>;
+
+enum State {
+    Streaming(Inner),
+    Resuming(Resume),
+    Failed,
+}
+
+/// Http stream, which reconnects with range request then connection is lost.
+pub struct ResumableStream {
+    state: State,
+    content_length: Option<u64>,
+    /// Absolute position of the next byte to be received
+    position: u64,
+    retry: RetryPolicy,
+    /// Failed attempts in a row
+    attempt: u32,
+    pacer: Option<Pacer>,
+}
+
+impl ResumableStream {
+    pub async fn new(client: reqwest::Client, url: Url, retry: RetryPolicy) -> io::Result<Self> {
+        let stream = HttpStream::new(client, url).await?;
+        Ok(Self {
+            content_length: stream.content_length(),
+            state: State::Streaming(Box::new(stream)),
+            position: 0,
+            retry,
+            attempt: 0,
+            pacer: None,
+        })
+    }
+
+    /// Receive no faster than `throttle` allows.
+    pub fn throttled(mut self, throttle: Arc<Throttle>) -> Self {
+        self.pacer = Some(Pacer::new(throttle));
+        self
+    }
+
+    fn resume(&mut self, mut stream: Inner) {
+        self.attempt += 1;
+        let delay = self.retry.delay(self.attempt);
+        let position = self.position;
+        self.state = State::Resuming(Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            let result = stream.seek_range(position, None).await;
+            (stream, result)
+        }));
+    }
+
+    fn can_retry(&self) -> bool {
+        self.attempt + 1 < self.retry.max_attempts
+    }
+}
+
+impl Stream for ResumableStream {
+    type Item = Result<Bytes, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(pacer) = &mut self.pacer {
+            ready!(pacer.poll_ready(cx));
+        }
+        loop {
+            match std::mem::replace(&mut self.state, State::Failed) {
+                State::Streaming(mut stream) => match Pin::new(&mut stream).poll_next(cx) {
+                    Poll::Ready(Some(Ok(bytes))) => {
+                        self.position += bytes.len() as u64;
+                        self.attempt = 0;
+                        if let Some(pacer) = &mut self.pacer {
+                            pacer.received(bytes.len());
+                        }
+                        #[cfg(feature = "metrics")]
+                        crate::telemetry::bytes_received("stream", bytes.len());
+                        self.state = State::Streaming(stream);
+                        return Poll::Ready(Some(Ok(bytes)));
+                    }
+                    Poll::Ready(Some(Err(error))) if !self.can_retry() => {
+                        return Poll::Ready(Some(Err(io::Error::other(error))));
+                    }
+                    Poll::Ready(Some(Err(_))) => self.resume(stream),
+                    Poll::Ready(None) => {
+                        self.state = State::Streaming(stream);
+                        return Poll::Ready(None);
+                    }
+                    Poll::Pending => {
+                        self.state = State::Streaming(stream);
+                        return Poll::Pending;
+                    }
+                },
+                State::Resuming(mut resume) => match resume.as_mut().poll(cx) {
+                    Poll::Ready((stream, Ok(()))) => self.state = State::Streaming(stream),
+                    Poll::Ready((_, Err(error))) if !self.can_retry() => {
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                    Poll::Ready((stream, Err(_))) => self.resume(stream),
+                    Poll::Pending => {
+                        self.state = State::Resuming(resume);
+                        return Poll::Pending;
+                    }
+                },
+                State::Failed => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl SourceStream for ResumableStream {
+    type Url = Url;
+    type StreamError = io::Error;
+
+    async fn create(url: Url) -> io::Result<Self> {
+        Self::new(reqwest::Client::new(), url, RetryPolicy::default()).await
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    async fn seek_range(&mut self, start: u64, end: Option<u64>) -> io::Result<()> {
+        let mut stream = match std::mem::replace(&mut self.state, State::Failed) {
+            State::Streaming(stream) => stream,
+            // Seek replaces interrupted response anyway
+            State::Resuming(resume) => resume.await.0,
+            State::Failed => return Err(io::Error::other("Stream failed")),
+        };
+        let result = stream.seek_range(start, end).await;
+        self.state = State::Streaming(stream);
+        result?;
+        self.position = start;
+        self.attempt = 0;
+        Ok(())
+    }
+}
+
+/// Storage backend of audio stream.
+#[derive(Debug, Clone, Default)]
+pub enum StreamStorage {
+    /// Temporary file, removed once stream is dropped.
+    #[default]
+    Temp,
+    /// Whole file kept in memory.
+    Memory,
+    /// Ring buffer in memory of given size. Seeking further back than buffer size
+    /// requests data from server again.
+    BoundedMemory(NonZeroUsize),
+    /// Files in persistent directory, named after their url.
+    CacheDir(StreamCacheDir),
+}
+
+/// Directory with streamed files, which evicts least recently written files
+/// once total size exceeds `max_size`.
+#[derive(Debug, Clone)]
+pub struct StreamCacheDir {
+    pub path: PathBuf,
+    pub max_size: u64,
+}
+
+impl StreamCacheDir {
+    pub fn new(path: impl Into<PathBuf>, max_size: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_size,
+        }
+    }
+
+    /// Remove oldest files until `reserve` more bytes fit into `max_size`.
+    fn evict(&self, reserve: u64) -> io::Result<()> {
+        let mut files = vec![];
+        for entry in fs::read_dir(&self.path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((metadata.modified()?, metadata.len(), entry.path()));
+            }
+        }
+        files.sort();
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum::<u64>() + reserve;
+        for (_, len, path) in files {
+            if total <= self.max_size {
+                break;
+            }
+            fs::remove_file(path)?;
+            total -= len;
+        }
+        Ok(())
+    }
+
+    fn file_path(&self, key: &str) -> PathBuf {
+        self.path
+            .join(format!("{:016x}", crate::http_cache::fnv1a(key)))
+    }
+}
+
+/// [`StorageProvider`] built from [`StreamStorage`] for a single file.
+#[derive(Debug, Clone)]
+pub struct StreamStorageProvider {
+    storage: StreamStorage,
+    /// Identifies file in cache directory
+    key: String,
+}
+
+impl StreamStorageProvider {
+    pub fn new(storage: StreamStorage, key: impl Into<String>) -> Self {
+        Self {
+            storage,
+            key: key.into(),
+        }
+    }
+
+    /// Location of the file, if it is stored in the cache directory.
+    pub fn cache_path(&self) -> Option<PathBuf> {
+        match &self.storage {
+            StreamStorage::CacheDir(dir) => Some(dir.file_path(&self.key)),
+            _ => None,
+        }
+    }
+}
+
+impl StorageProvider for StreamStorageProvider {
+    type Reader = Box<dyn StorageReader + Sync>;
+    type Writer = Box<dyn StorageWriter>;
+
+    fn into_reader_writer(
+        self,
+        content_length: Option<u64>,
+    ) -> io::Result<(Self::Reader, Self::Writer)> {
+        fn boxed<R: StorageReader + Sync + 'static, W: StorageWriter + 'static>(
+            (reader, writer): (R, W),
+        ) -> (Box<dyn StorageReader + Sync>, Box<dyn StorageWriter>) {
+            (Box::new(reader), Box::new(writer))
+        }
+
+        match self.storage {
+            StreamStorage::Temp => TempStorageProvider::new()
+                .into_reader_writer(content_length)
+                .map(boxed),
+            StreamStorage::Memory => MemoryStorageProvider
+                .into_reader_writer(content_length)
+                .map(boxed),
+            StreamStorage::BoundedMemory(size) => {
+                BoundedStorageProvider::new(MemoryStorageProvider, size)
+                    .into_reader_writer(content_length)
+                    .map(boxed)
+            }
+            StreamStorage::CacheDir(dir) => {
+                fs::create_dir_all(&dir.path)?;
+                let path = dir.file_path(&self.key);
+                remove_if_exists(&path)?;
+                dir.evict(content_length.unwrap_or(0))?;
+                let writer = File::create(&path)?;
+                let reader = BufReader::new(File::open(&path)?);
+                Ok(boxed((reader, writer)))
+            }
+        }
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
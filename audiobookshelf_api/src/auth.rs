@@ -0,0 +1,140 @@
+//! Authentication other than with username and password: API keys, OpenID and custom
+//! token sources.
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::{StatusCode, Url};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::errors::{AuthError, FusedError};
+use crate::schema::AuthResponse;
+use crate::{ClientConfig, Credentials, UserClient};
+
+pub type AuthFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<String, FusedError<AuthError>>> + Send + 'a>>;
+
+/// Source of access tokens, such as external secret store or SSO helper.
+///
+/// Used for initial authentication, and again then server rejects current token.
+pub trait AuthProvider: Debug + Send + Sync {
+    fn authenticate<'a>(&'a self, config: &'a ClientConfig) -> AuthFuture<'a>;
+}
+
+/// OpenID login started with `UserClient::openid_login`.
+#[derive(Debug, Clone)]
+pub struct OpenIdLogin {
+    /// Page to open in browser
+    pub url: Url,
+    /// Expected to be returned to `redirect_uri` unchanged
+    pub state: String,
+    code_verifier: String,
+}
+
+impl ClientConfig {
+    fn openid_url(&self) -> Url {
+        self.endpoints.url(["auth", "openid"])
+    }
+
+    fn openid_callback_url(&self) -> Url {
+        self.endpoints.url(["auth", "openid", "callback"])
+    }
+}
+
+impl UserClient {
+    /// Client authorized with API key, created in server settings. Keys are long lived
+    /// and can't be renewed, so rejected key is reported as error.
+    pub fn from_api_key(config: ClientConfig, api_key: String) -> Self {
+        Self::from_token(config, api_key)
+    }
+
+    /// Client obtaining its tokens from `provider`.
+    pub async fn auth_provider(
+        config: ClientConfig,
+        provider: Arc<dyn AuthProvider>,
+    ) -> Result<Self, FusedError<AuthError>> {
+        let token = provider.authenticate(&config).await?;
+        Ok(Self::from_token(config, token).with_credentials(Credentials::Provider(provider)))
+    }
+
+    /// Start OpenID login, using PKCE flow of mobile apps.
+    ///
+    /// `login.url` is to be opened in browser. Once user logs in, server redirects to
+    /// `redirect_uri` with `code` and `state` query parameters, which are passed to
+    /// `auth_openid`. `redirect_uri` must be allowed in server authentication settings.
+    pub fn openid_login(config: &ClientConfig, redirect_uri: &str) -> OpenIdLogin {
+        let code_verifier = random_string();
+        let state = random_string();
+        let code_challenge = URL_SAFE_NO_PAD.encode(digest(&SHA256, code_verifier.as_bytes()));
+        let mut url = config.openid_url();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state);
+        OpenIdLogin {
+            url,
+            state,
+            code_verifier,
+        }
+    }
+
+    /// Finish OpenID login with `code` and `state` server redirected to.
+    pub async fn auth_openid(
+        config: ClientConfig,
+        login: &OpenIdLogin,
+        code: &str,
+        state: &str,
+    ) -> Result<Self, FusedError<AuthError>> {
+        if state != login.state {
+            return Err(FusedError::DomainError(AuthError::InvalidCredentials));
+        }
+        let client = config.http_client();
+        let transport = config.transport();
+        let mut request_builder = client
+            .get(config.openid_callback_url())
+            .query(&[
+                ("state", state),
+                ("code", code),
+                ("code_verifier", &login.code_verifier),
+            ])
+            .header("x-return-tokens", "true");
+        if let Some(timeout) = config.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+        let response: AuthResponse = Self::send_anonymous(&*transport, request_builder)
+            .await
+            .map_err(|error| match error {
+                FusedError::DomainError(error)
+                    if error.status == StatusCode::UNAUTHORIZED
+                        || error.status == StatusCode::BAD_REQUEST =>
+                {
+                    FusedError::DomainError(AuthError::InvalidCredentials)
+                }
+                error => FusedError::APIError(error.to_api_error()),
+            })?;
+
+        let token = response.user.access_token.unwrap_or(response.user.token);
+        let client = Self::from_token(config, token);
+        Ok(match response.user.refresh_token {
+            Some(refresh_token) => {
+                client.with_credentials(Credentials::RefreshToken(refresh_token))
+            }
+            None => client,
+        })
+    }
+}
+
+/// 32 random bytes, base64 encoded.
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("System random generator is unavailable");
+    URL_SAFE_NO_PAD.encode(bytes)
+}
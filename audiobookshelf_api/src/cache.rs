@@ -0,0 +1,387 @@
+//! Persistent cache of libraries, items and covers, so client can start offline.
+//!
+//! Entries remember when they were fetched. `fetch_*` methods ask server first and fall
+//! back to cached value when server is unreachable, while `refresh_stale` reconciles
+//! outdated entries once connection is back.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::errors::{APIError, FusedError, RequestError};
+use crate::params::{CoverParams, LibraryItemParams};
+use crate::schema::{Author, Id, Image, Library, LibraryItem, LibraryItemMinified};
+use crate::UserClient;
+
+/// Cached value, with time it was received from server.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl<T> Cached<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        let age = Utc::now() - self.fetched_at;
+        age.to_std().is_ok_and(|age| age > max_age)
+    }
+}
+
+/// Cover or author image, with its content type stored separately from image data.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageMeta {
+    content_type: String,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Directory with cached server data.
+///
+/// Layout is `libraries.json`, `library_items/<library id>.json`, `items/<item id>.json`,
+/// `covers/<item id>` and `authors/<author id>_<width>`.
+#[derive(Debug, Clone)]
+pub struct MetadataCache {
+    root: PathBuf,
+    max_age: Duration,
+}
+
+impl MetadataCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            max_age: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// Age after which entries are considered stale. One day by default.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub async fn libraries(&self) -> std::io::Result<Option<Cached<Vec<Library>>>> {
+        self.read(self.libraries_path()).await
+    }
+
+    pub async fn store_libraries(&self, libraries: &[Library]) -> std::io::Result<()> {
+        self.write(self.libraries_path(), &Cached::new(libraries))
+            .await
+    }
+
+    pub async fn library_items(
+        &self,
+        id: &Id<Library>,
+    ) -> std::io::Result<Option<Cached<Vec<LibraryItemMinified>>>> {
+        self.read(self.library_items_path(id)).await
+    }
+
+    pub async fn store_library_items(
+        &self,
+        id: &Id<Library>,
+        items: &[LibraryItemMinified],
+    ) -> std::io::Result<()> {
+        self.write(self.library_items_path(id), &Cached::new(items))
+            .await
+    }
+
+    pub async fn library_item(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> std::io::Result<Option<Cached<LibraryItem>>> {
+        self.read(self.item_path(id)).await
+    }
+
+    pub async fn store_library_item(&self, item: &LibraryItem) -> std::io::Result<()> {
+        self.write(self.item_path(&item.id), &Cached::new(item))
+            .await
+    }
+
+    /// Ids of all cached library items.
+    pub async fn cached_items(&self) -> std::io::Result<Vec<Id<LibraryItem>>> {
+        let mut ids = vec![];
+        let mut entries = match fs::read_dir(self.root.join("items")).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ids),
+            Err(e) => return Err(e),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                ids.push(Id::new(id));
+            }
+        }
+        Ok(ids)
+    }
+
+    pub async fn remove_library_item(&self, id: &Id<LibraryItem>) -> std::io::Result<()> {
+        remove_if_exists(&self.item_path(id)).await?;
+        remove_if_exists(&self.cover_path(id)).await?;
+        remove_if_exists(&self.cover_path(id).with_extension("json")).await
+    }
+
+    pub async fn cover(&self, id: &Id<LibraryItem>) -> std::io::Result<Option<Cached<Image>>> {
+        self.read_image(self.cover_path(id)).await
+    }
+
+    pub async fn store_cover(&self, id: &Id<LibraryItem>, image: &Image) -> std::io::Result<()> {
+        self.write_image(self.cover_path(id), image).await
+    }
+
+    /// Author image of given `width`, `None` standing for original size.
+    pub async fn author_image(
+        &self,
+        id: &Id<Author>,
+        width: Option<u32>,
+    ) -> std::io::Result<Option<Cached<Image>>> {
+        self.read_image(self.author_image_path(id, width)).await
+    }
+
+    pub async fn store_author_image(
+        &self,
+        id: &Id<Author>,
+        width: Option<u32>,
+        image: &Image,
+    ) -> std::io::Result<()> {
+        self.write_image(self.author_image_path(id, width), image)
+            .await
+    }
+
+    /// Remove everything from cache.
+    pub async fn clear(&self) -> std::io::Result<()> {
+        match fs::remove_dir_all(&self.root).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Libraries from server, or from cache if server is unreachable.
+    pub async fn fetch_libraries(
+        &self,
+        client: &UserClient,
+    ) -> Result<Cached<Vec<Library>>, FusedError<RequestError>> {
+        let cached = self.libraries().await.ok().flatten();
+        match client.libraries().await {
+            Ok(libraries) => {
+                let _ = self.store_libraries(&libraries).await;
+                Ok(Cached::new(libraries))
+            }
+            Err(error) => fallback(error, cached),
+        }
+    }
+
+    /// All items of library from server, or from cache if server is unreachable.
+    pub async fn fetch_library_items(
+        &self,
+        client: &UserClient,
+        id: &Id<Library>,
+    ) -> Result<Cached<Vec<LibraryItemMinified>>, FusedError<RequestError>> {
+        let cached = self.library_items(id).await.ok().flatten();
+        match client.library_items(id, LibraryItemParams::default()).await {
+            Ok(items) => {
+                let _ = self.store_library_items(id, &items).await;
+                Ok(Cached::new(items))
+            }
+            Err(error) => fallback(error, cached),
+        }
+    }
+
+    /// Library item from server, or from cache if server is unreachable.
+    pub async fn fetch_library_item(
+        &self,
+        client: &UserClient,
+        id: &Id<LibraryItem>,
+    ) -> Result<Cached<LibraryItem>, FusedError<RequestError>> {
+        let cached = self.library_item(id).await.ok().flatten();
+        match client.library_item(id).await {
+            Ok(item) => {
+                let _ = self.store_library_item(&item).await;
+                Ok(Cached::new(item))
+            }
+            Err(error) => fallback(error, cached),
+        }
+    }
+
+    /// Cover of library item. Covers rarely change, so cached one is used until it is stale.
+    pub async fn fetch_cover(
+        &self,
+        client: &UserClient,
+        id: &Id<LibraryItem>,
+    ) -> Result<Cached<Image>, FusedError<RequestError>> {
+        let cached = self.cover(id).await.ok().flatten();
+        if let Some(cached) = cached.as_ref().filter(|c| !c.is_stale(self.max_age)) {
+            return Ok(cached.clone());
+        }
+        match client.item_cover(id, CoverParams::default()).await {
+            Ok(image) => {
+                let _ = self.store_cover(id, &image).await;
+                Ok(Cached::new(image))
+            }
+            Err(error) => fallback(error, cached),
+        }
+    }
+
+    /// Author image, for rendering author lists. Like covers, cached image is used until
+    /// it is stale.
+    pub async fn fetch_author_image(
+        &self,
+        client: &UserClient,
+        id: &Id<Author>,
+        width: Option<u32>,
+    ) -> Result<Cached<Image>, FusedError<RequestError>> {
+        let cached = self.author_image(id, width).await.ok().flatten();
+        if let Some(cached) = cached.as_ref().filter(|c| !c.is_stale(self.max_age)) {
+            return Ok(cached.clone());
+        }
+        match client.author_image(id, width).await {
+            Ok(image) => {
+                let _ = self.store_author_image(id, width, &image).await;
+                Ok(Cached::new(image))
+            }
+            Err(error) => fallback(error, cached),
+        }
+    }
+
+    /// Fetch again stale libraries and library items. Items deleted on server are removed.
+    ///
+    /// Stops on first network error, leaving remaining entries stale.
+    pub async fn refresh_stale(&self, client: &UserClient) -> Result<(), FusedError<RequestError>> {
+        if let Ok(Some(libraries)) = self.libraries().await {
+            if libraries.is_stale(self.max_age) {
+                self.fetch_libraries(client).await?;
+            }
+        }
+        for id in self.cached_items().await.unwrap_or_default() {
+            let Ok(Some(item)) = self.library_item(&id).await else {
+                continue;
+            };
+            if !item.is_stale(self.max_age) {
+                continue;
+            }
+            match client.library_item(&id).await {
+                Ok(item) => {
+                    let _ = self.store_library_item(&item).await;
+                }
+                Err(FusedError::DomainError(RequestError::NotFound(_))) => {
+                    let _ = self.remove_library_item(&id).await;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    fn libraries_path(&self) -> PathBuf {
+        self.root.join("libraries.json")
+    }
+
+    fn library_items_path(&self, id: &Id<Library>) -> PathBuf {
+        self.root
+            .join("library_items")
+            .join(format!("{}.json", id.as_str()))
+    }
+
+    fn item_path(&self, id: &Id<LibraryItem>) -> PathBuf {
+        self.root
+            .join("items")
+            .join(format!("{}.json", id.as_str()))
+    }
+
+    fn cover_path(&self, id: &Id<LibraryItem>) -> PathBuf {
+        self.root.join("covers").join(id.as_str())
+    }
+
+    fn author_image_path(&self, id: &Id<Author>, width: Option<u32>) -> PathBuf {
+        let width = width.map_or("original".to_string(), |width| width.to_string());
+        self.root
+            .join("authors")
+            .join(format!("{}_{width}", id.as_str()))
+    }
+
+    /// Image data, with metadata in `.json` file next to it.
+    async fn read_image(&self, path: PathBuf) -> std::io::Result<Option<Cached<Image>>> {
+        let Some(meta) = self.read::<ImageMeta>(path.with_extension("json")).await? else {
+            return Ok(None);
+        };
+        let data = match fs::read(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(Some(Cached {
+            value: Image {
+                content_type: meta.content_type,
+                data,
+            },
+            fetched_at: meta.fetched_at,
+        }))
+    }
+
+    async fn write_image(&self, path: PathBuf, image: &Image) -> std::io::Result<()> {
+        create_parent(&path).await?;
+        fs::write(&path, &image.data).await?;
+        let meta = ImageMeta {
+            content_type: image.content_type.clone(),
+            fetched_at: Utc::now(),
+        };
+        self.write(path.with_extension("json"), &meta).await
+    }
+
+    /// Read JSON file. Missing or unreadable (e.g. written by older version) entries
+    /// are treated as not cached.
+    async fn read<T: DeserializeOwned>(&self, path: PathBuf) -> std::io::Result<Option<T>> {
+        match fs::read(&path).await {
+            Ok(data) => Ok(serde_json::from_slice(&data).ok()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn write<T: Serialize>(&self, path: PathBuf, value: &T) -> std::io::Result<()> {
+        create_parent(&path).await?;
+        let data = serde_json::to_vec(value)?;
+        // Write then rename, so interrupted write leaves previous entry intact
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, data).await?;
+        fs::rename(&temporary, &path).await
+    }
+}
+
+/// Use cached value if request failed due to unreachable server.
+fn fallback<T>(
+    error: FusedError<RequestError>,
+    cached: Option<Cached<T>>,
+) -> Result<Cached<T>, FusedError<RequestError>> {
+    match (error, cached) {
+        (FusedError::APIError(APIError::NetworkError { .. }), Some(cached)) => Ok(cached),
+        (error, _) => Err(error),
+    }
+}
+
+async fn create_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).await,
+        None => Ok(()),
+    }
+}
+
+async fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
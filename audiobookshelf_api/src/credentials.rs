@@ -0,0 +1,318 @@
+//! Persisting tokens between runs, so passwords are needed only for first login or once
+//! saved tokens expire. Passwords themselves are never stored.
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AuthError, CredentialError, FusedError, RequestError};
+use crate::{ClientConfig, Credentials, Url, UserClient};
+
+/// Tokens of account, as saved in `CredentialStore`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredToken {
+    pub token: String,
+    /// Refresh token, for servers supporting them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+/// Storage of tokens, keyed by server and username.
+pub trait CredentialStore: Debug + Send + Sync {
+    fn load(&self, server: &Url, username: &str) -> Result<Option<StoredToken>, CredentialError>;
+    fn save(
+        &self,
+        server: &Url,
+        username: &str,
+        token: &StoredToken,
+    ) -> Result<(), CredentialError>;
+    /// Forget token. Deleting missing token is not an error.
+    fn delete(&self, server: &Url, username: &str) -> Result<(), CredentialError>;
+}
+
+/// Store backed by keyring of OS: Keychain on macOS, Credential Manager on Windows and
+/// kernel keyring on Linux, which does not survive reboot.
+#[cfg(feature = "keyring")]
+#[derive(Debug, Clone)]
+pub struct KeyringStore {
+    service: String,
+}
+
+/// Store in file encrypted with key derived from passphrase.
+///
+/// File consists of salt, nonce and ChaCha20-Poly1305 encrypted JSON map of tokens.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    salt: [u8; SALT_LEN],
+    key: LessSafeKey,
+    /// Serializes read-modify-write of file
+    lock: Mutex<()>,
+}
+
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// Account whose tokens are saved once client obtains them.
+#[derive(Debug)]
+pub(crate) struct StoredAccount {
+    store: Arc<dyn CredentialStore>,
+    server: Url,
+    username: String,
+}
+
+fn account_key(server: &Url, username: &str) -> String {
+    format!("{username}@{server}")
+}
+
+fn random(buffer: &mut [u8]) -> Result<(), CredentialError> {
+    SystemRandom::new()
+        .fill(buffer)
+        .map_err(|_| std::io::Error::other("Random number generator failed").into())
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringStore {
+    /// Store keeping tokens under `service`, typically name of application.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn entry(&self, server: &Url, username: &str) -> Result<keyring::Entry, CredentialError> {
+        Ok(keyring::Entry::new(
+            &self.service,
+            &account_key(server, username),
+        )?)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl CredentialStore for KeyringStore {
+    fn load(&self, server: &Url, username: &str) -> Result<Option<StoredToken>, CredentialError> {
+        match self.entry(server, username)?.get_password() {
+            Ok(secret) => Ok(Some(serde_json::from_str(&secret)?)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save(
+        &self,
+        server: &Url,
+        username: &str,
+        token: &StoredToken,
+    ) -> Result<(), CredentialError> {
+        let secret = serde_json::to_string(token)?;
+        Ok(self.entry(server, username)?.set_password(&secret)?)
+    }
+
+    fn delete(&self, server: &Url, username: &str) -> Result<(), CredentialError> {
+        match self.entry(server, username)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl EncryptedFileStore {
+    /// Open store at `path`, creating it on first save. Fails if existing file can't be
+    /// read; wrong passphrase is only detected once tokens are loaded.
+    pub fn open(path: impl Into<PathBuf>, passphrase: &str) -> Result<Self, CredentialError> {
+        let path = path.into();
+        let mut salt = [0; SALT_LEN];
+        match std::fs::read(&path) {
+            Ok(content) if content.len() >= SALT_LEN => salt.copy_from_slice(&content[..SALT_LEN]),
+            Ok(_) => return Err(CredentialError::Decrypt),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => random(&mut salt)?,
+            Err(error) => return Err(error.into()),
+        }
+
+        let mut key = [0; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+            &salt,
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &key).unwrap());
+        Ok(Self {
+            path,
+            salt,
+            key,
+            lock: Mutex::default(),
+        })
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, StoredToken>, CredentialError> {
+        let mut content = match std::fs::read(&self.path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(BTreeMap::new())
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if content.len() < SALT_LEN + NONCE_LEN {
+            return Err(CredentialError::Decrypt);
+        }
+        let mut encrypted = content.split_off(SALT_LEN + NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&content[SALT_LEN..])
+            .map_err(|_| CredentialError::Decrypt)?;
+        let plain = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut encrypted)
+            .map_err(|_| CredentialError::Decrypt)?;
+        Ok(serde_json::from_slice(plain)?)
+    }
+
+    fn write(&self, tokens: &BTreeMap<String, StoredToken>) -> Result<(), CredentialError> {
+        let mut nonce = [0; NONCE_LEN];
+        random(&mut nonce)?;
+        let mut data = serde_json::to_vec(tokens)?;
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| CredentialError::Decrypt)?;
+
+        let mut content = Vec::with_capacity(SALT_LEN + NONCE_LEN + data.len());
+        content.extend_from_slice(&self.salt);
+        content.extend_from_slice(&nonce);
+        content.extend_from_slice(&data);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Written aside and renamed, so interrupted write keeps old tokens
+        let temporary = self.path.with_extension("tmp");
+        std::fs::write(&temporary, content)?;
+        std::fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Debug for EncryptedFileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedFileStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn load(&self, server: &Url, username: &str) -> Result<Option<StoredToken>, CredentialError> {
+        let _lock = self.lock.lock().unwrap();
+        Ok(self.read()?.remove(&account_key(server, username)))
+    }
+
+    fn save(
+        &self,
+        server: &Url,
+        username: &str,
+        token: &StoredToken,
+    ) -> Result<(), CredentialError> {
+        let _lock = self.lock.lock().unwrap();
+        let mut tokens = self.read()?;
+        tokens.insert(account_key(server, username), token.clone());
+        self.write(&tokens)
+    }
+
+    fn delete(&self, server: &Url, username: &str) -> Result<(), CredentialError> {
+        let _lock = self.lock.lock().unwrap();
+        let mut tokens = self.read()?;
+        if tokens.remove(&account_key(server, username)).is_some() {
+            self.write(&tokens)?;
+        }
+        Ok(())
+    }
+}
+
+impl StoredAccount {
+    fn load(&self) -> Result<Option<StoredToken>, CredentialError> {
+        self.store.load(&self.server, &self.username)
+    }
+}
+
+impl UserClient {
+    /// Client authorized with token saved in `store`. Logs in with `password` if no
+    /// token is saved or it is rejected, failing with `AuthError::InvalidToken` if
+    /// `password` is not given then. Tokens obtained later are saved to `store`.
+    pub async fn auth_with_store(
+        config: ClientConfig,
+        username: String,
+        password: Option<String>,
+        store: Arc<dyn CredentialStore>,
+    ) -> Result<Self, FusedError<AuthError>> {
+        let account = Arc::new(StoredAccount {
+            store,
+            server: config.root_url().clone(),
+            username: username.clone(),
+        });
+        let store_error = |error| FusedError::DomainError(AuthError::CredentialStore(error));
+
+        if let Some(saved) = account.load().map_err(store_error)? {
+            let mut client = Self::from_token(config.clone(), saved.token);
+            client.stored = Some(account.clone());
+            let login = password.clone().map(|password| Credentials::Password {
+                username: username.clone(),
+                password,
+            });
+            match saved.refresh_token {
+                Some(refresh_token) => {
+                    client = client.with_credentials(Credentials::RefreshToken(refresh_token));
+                    *client.fallback.write().unwrap() = login;
+                }
+                None => {
+                    if let Some(login) = login {
+                        client = client.with_credentials(login);
+                    }
+                }
+            }
+            // Expired token is renewed by `me`, and saved by `reauthenticate`
+            match client.me().await {
+                Ok(_) => return Ok(client),
+                Err(FusedError::DomainError(RequestError::Unauthorized(_))) => {}
+                Err(error) => return Err(FusedError::APIError(error.to_api_error())),
+            }
+        }
+
+        let Some(password) = password else {
+            return Err(FusedError::DomainError(AuthError::InvalidToken));
+        };
+        let mut client = Self::auth(config, username, password).await?;
+        client.stored = Some(account);
+        client.save_token().map_err(store_error)?;
+        Ok(client)
+    }
+
+    /// Remove token of this client from store it was loaded from.
+    pub fn forget_token(&self) -> Result<(), CredentialError> {
+        match &self.stored {
+            Some(account) => account.store.delete(&account.server, &account.username),
+            None => Ok(()),
+        }
+    }
+
+    /// Save current tokens, if client was created with `auth_with_store`.
+    pub(crate) fn save_token(&self) -> Result<(), CredentialError> {
+        let Some(account) = &self.stored else {
+            return Ok(());
+        };
+        let refresh_token = match &*self.credentials.read().unwrap() {
+            Some(Credentials::RefreshToken(refresh_token)) => Some(refresh_token.clone()),
+            _ => None,
+        };
+        let token = StoredToken {
+            token: self.token(),
+            refresh_token,
+        };
+        account
+            .store
+            .save(&account.server, &account.username, &token)
+    }
+}
@@ -0,0 +1,69 @@
+//! Downloading of item files as byte streams.
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pub use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use reqwest::header::{HeaderName, CONTENT_DISPOSITION, CONTENT_TYPE};
+
+use crate::errors::{APIError, RequestInfo};
+use crate::rate_limit::{Throttle, Throttled};
+
+/// Body of file being downloaded.
+pub struct Download {
+    /// Size in bytes, if known upfront
+    pub content_length: Option<u64>,
+    pub content_type: Option<String>,
+    /// File name suggested by server
+    pub file_name: Option<String>,
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes, APIError>> + Send>>,
+}
+
+impl Download {
+    pub(crate) fn from_response(request: RequestInfo, response: reqwest::Response) -> Self {
+        let headers = response.headers();
+        let header = |name: HeaderName| {
+            let value = headers.get(name)?.to_str().ok()?;
+            Some(value.to_string())
+        };
+        let content_type = header(CONTENT_TYPE);
+        let file_name = header(CONTENT_DISPOSITION).and_then(|value| file_name(&value));
+        Self {
+            content_length: response.content_length(),
+            content_type,
+            file_name,
+            stream: Box::pin(response.bytes_stream().map(move |chunk| {
+                #[cfg(feature = "metrics")]
+                if let Ok(bytes) = &chunk {
+                    crate::telemetry::bytes_received("download", bytes.len());
+                }
+                chunk.map_err(|error| APIError::network(&request, error))
+            })),
+        }
+    }
+
+    /// Receive no faster than `throttle` allows.
+    pub fn throttled(self, throttle: Arc<Throttle>) -> Self {
+        Self {
+            stream: Box::pin(Throttled::new(self.stream, throttle)),
+            ..self
+        }
+    }
+}
+
+impl Stream for Download {
+    type Item = Result<Bytes, APIError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
+
+/// Extract `filename` from `Content-Disposition` header value.
+fn file_name(disposition: &str) -> Option<String> {
+    disposition.split(';').find_map(|part| {
+        let name = part.trim().strip_prefix("filename=")?;
+        Some(name.trim_matches('"').to_string())
+    })
+}
@@ -0,0 +1,612 @@
+//! Managed downloads of library items for offline listening.
+//!
+//! Each item is stored in its own directory under download root, laid out as in item
+//! folder on server, and described in `manifest.json`, keyed by item id. Files are fetched
+//! by `ChunkedDownloader`.
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::SeekFrom;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::broadcast;
+
+use crate::errors::{APIError, DownloadError, FusedError, RequestError};
+use crate::rate_limit::Throttle;
+use crate::retry::RetryPolicy;
+use crate::schema::{AudioFile, Id, LibraryItem, LibraryMedia};
+use crate::UserClient;
+
+const MANIFEST: &str = "manifest.json";
+const PARTIAL_EXTENSION: &str = "part";
+const STATE_EXTENSION: &str = "part.json";
+const VERSION_EXTENSION: &str = "json";
+
+/// Downloads queued library items into local directory.
+pub struct DownloadManager {
+    client: UserClient,
+    root: PathBuf,
+    /// Files of single item downloaded at once
+    parallel_files: usize,
+    chunks: ChunkedDownloader,
+    queue: Mutex<VecDeque<Id<LibraryItem>>>,
+    /// Serializes manifest updates
+    manifest: tokio::sync::Mutex<()>,
+    events: broadcast::Sender<DownloadEvent>,
+}
+
+/// Progress of downloads, received via `DownloadManager::subscribe`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DownloadEvent {
+    Queued(Id<LibraryItem>),
+    Started {
+        id: Id<LibraryItem>,
+        total_bytes: u64,
+    },
+    Progress {
+        id: Id<LibraryItem>,
+        downloaded_bytes: u64,
+        total_bytes: u64,
+    },
+    Finished(Id<LibraryItem>),
+    Failed {
+        id: Id<LibraryItem>,
+        error: String,
+    },
+}
+
+/// Downloaded items.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub items: BTreeMap<Id<LibraryItem>, ItemManifest>,
+}
+
+/// Downloaded library item, with metadata at the time of download.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemManifest {
+    pub item: LibraryItem,
+    pub files: Vec<LocalFile>,
+    pub downloaded_at: DateTime<Utc>,
+}
+
+/// Audio file, stored locally.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalFile {
+    pub ino: String,
+    /// Path relative to download root
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Downloads single file with parallel range requests.
+///
+/// Chunks are written in place into preallocated `.part` file and recorded in `.part.json`
+/// next to it, so interrupted download, even by restart, continues with missing chunks
+/// only. Recorded chunks are discarded if file changed on server since. Version of
+/// completed file is recorded in `.json` next to it.
+#[derive(Clone)]
+pub struct ChunkedDownloader {
+    client: UserClient,
+    /// Size of single range request
+    chunk_size: u64,
+    /// Chunks of single file downloaded at once
+    parallel_chunks: usize,
+    retry: RetryPolicy,
+    throttle: Option<Arc<Throttle>>,
+}
+
+/// Version of file on server, telling whether local copy is up to date.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FileVersion {
+    ino: String,
+    size: u64,
+    mtime: DateTime<Utc>,
+}
+
+/// Chunks of partial file already written, stored next to it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ChunkState {
+    #[serde(flatten)]
+    version: FileVersion,
+    chunk_size: u64,
+    completed: BTreeSet<u64>,
+}
+
+impl FileVersion {
+    fn of(file: &AudioFile) -> Self {
+        Self {
+            ino: file.ino.clone(),
+            size: file.metadata.size as u64,
+            mtime: file.metadata.mtime_ms,
+        }
+    }
+}
+
+impl ItemManifest {
+    /// Path of downloaded file, relative to download root.
+    pub fn file(&self, ino: &str) -> Option<&LocalFile> {
+        self.files.iter().find(|file| file.ino == ino)
+    }
+}
+
+impl DownloadManager {
+    pub fn new(client: UserClient, root: impl Into<PathBuf>) -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            client: client.clone(),
+            root: root.into(),
+            parallel_files: 2,
+            chunks: ChunkedDownloader::new(client),
+            queue: Mutex::default(),
+            manifest: tokio::sync::Mutex::default(),
+            events,
+        }
+    }
+
+    pub fn with_parallel_files(mut self, parallel_files: usize) -> Self {
+        self.parallel_files = parallel_files.max(1);
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunks = self.chunks.with_chunk_size(chunk_size);
+        self
+    }
+
+    pub fn with_parallel_chunks(mut self, parallel_chunks: usize) -> Self {
+        self.chunks = self.chunks.with_parallel_chunks(parallel_chunks);
+        self
+    }
+
+    /// Retries of single chunk.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.chunks = self.chunks.with_retry(retry);
+        self
+    }
+
+    /// Bandwidth limit, shared by all files being downloaded.
+    pub fn with_throttle(mut self, throttle: Arc<Throttle>) -> Self {
+        self.chunks = self.chunks.with_throttle(throttle);
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.events.subscribe()
+    }
+
+    /// Add item to queue, processed by `run`.
+    pub fn enqueue(&self, id: Id<LibraryItem>) {
+        let mut queue = self.queue.lock().unwrap();
+        if !queue.contains(&id) {
+            queue.push_back(id.clone());
+            self.emit(DownloadEvent::Queued(id));
+        }
+    }
+
+    pub fn queued(&self) -> Vec<Id<LibraryItem>> {
+        self.queue.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Download queued items one by one, until queue is empty.
+    ///
+    /// Failed items are reported via `DownloadEvent::Failed` and skipped.
+    pub async fn run(&self) {
+        loop {
+            let next = self.queue.lock().unwrap().pop_front();
+            let Some(id) = next else {
+                return;
+            };
+            if let Err(error) = self.download(&id).await {
+                self.emit(DownloadEvent::Failed {
+                    id,
+                    error: error.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Download all audio files of item and record it in the manifest.
+    ///
+    /// Files partially downloaded before are continued from where they stopped.
+    pub async fn download(&self, id: &Id<LibraryItem>) -> Result<ItemManifest, DownloadError> {
+        let item = self.client.library_item(id).await?;
+        let files = audio_files(&item);
+        let total_bytes = files.iter().map(|file| file.metadata.size as u64).sum();
+        self.emit(DownloadEvent::Started {
+            id: id.clone(),
+            total_bytes,
+        });
+
+        let dir = PathBuf::from(id.as_str());
+        fs::create_dir_all(self.root.join(&dir)).await?;
+        let progress = Arc::new(Mutex::new(0u64));
+        let local_files = stream::iter(files.into_iter().cloned())
+            .map(|file| {
+                let progress = progress.clone();
+                let dir = &dir;
+                async move {
+                    let path = dir.join(relative_path(&file)?);
+                    let size = self
+                        .chunks
+                        .download(id, &file, &self.root.join(&path), |bytes| {
+                            let mut downloaded = progress.lock().unwrap();
+                            *downloaded += bytes;
+                            self.emit(DownloadEvent::Progress {
+                                id: id.clone(),
+                                downloaded_bytes: *downloaded,
+                                total_bytes,
+                            });
+                        })
+                        .await?;
+                    Ok::<_, DownloadError>(LocalFile {
+                        ino: file.ino,
+                        path,
+                        size,
+                    })
+                }
+            })
+            .buffered(self.parallel_files)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let entry = ItemManifest {
+            item,
+            files: local_files,
+            downloaded_at: Utc::now(),
+        };
+        self.update_manifest(|manifest| {
+            manifest.items.insert(id.clone(), entry.clone());
+        })
+        .await?;
+        self.emit(DownloadEvent::Finished(id.clone()));
+        Ok(entry)
+    }
+
+    /// Downloaded items.
+    pub async fn manifest(&self) -> Result<Manifest, DownloadError> {
+        let _guard = self.manifest.lock().await;
+        self.read_manifest().await
+    }
+
+    /// Downloaded item, if present.
+    pub async fn item(&self, id: &Id<LibraryItem>) -> Result<Option<ItemManifest>, DownloadError> {
+        Ok(self.manifest().await?.items.remove(id))
+    }
+
+    /// Absolute path of downloaded audio file.
+    pub async fn local_path(
+        &self,
+        id: &Id<LibraryItem>,
+        ino: &str,
+    ) -> Result<Option<PathBuf>, DownloadError> {
+        let item = self.item(id).await?;
+        Ok(item
+            .as_ref()
+            .and_then(|item| item.file(ino))
+            .map(|file| self.root.join(&file.path)))
+    }
+
+    /// Delete downloaded files of item.
+    pub async fn remove(&self, id: &Id<LibraryItem>) -> Result<(), DownloadError> {
+        self.update_manifest(|manifest| {
+            manifest.items.remove(id);
+        })
+        .await?;
+        match fs::remove_dir_all(self.root.join(id.as_str())).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn read_manifest(&self) -> Result<Manifest, DownloadError> {
+        match fs::read(self.root.join(MANIFEST)).await {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn update_manifest(
+        &self,
+        update: impl FnOnce(&mut Manifest),
+    ) -> Result<(), DownloadError> {
+        let _guard = self.manifest.lock().await;
+        let mut manifest = self.read_manifest().await?;
+        update(&mut manifest);
+        fs::create_dir_all(&self.root).await?;
+        // Written to temporary file first, so interrupted write can't corrupt manifest
+        let temporary = self.root.join(format!("{MANIFEST}.{PARTIAL_EXTENSION}"));
+        fs::write(&temporary, serde_json::to_vec_pretty(&manifest)?).await?;
+        fs::rename(&temporary, self.root.join(MANIFEST)).await?;
+        Ok(())
+    }
+
+    fn emit(&self, event: DownloadEvent) {
+        // No subscribers is not an error
+        let _ = self.events.send(event);
+    }
+}
+
+impl ChunkedDownloader {
+    pub fn new(client: UserClient) -> Self {
+        Self {
+            client,
+            chunk_size: 8 * 1024 * 1024,
+            parallel_chunks: 4,
+            retry: RetryPolicy::default(),
+            throttle: None,
+        }
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn with_parallel_chunks(mut self, parallel_chunks: usize) -> Self {
+        self.parallel_chunks = parallel_chunks.max(1);
+        self
+    }
+
+    /// Retries of single chunk.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Bandwidth limit, shared by all chunks.
+    pub fn with_throttle(mut self, throttle: Arc<Throttle>) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Download audio file of item into `target`, returning its size. `on_progress` is
+    /// called with number of bytes written, including ones found from previous attempt.
+    ///
+    /// Existing `target` is considered already downloaded if it was downloaded by this
+    /// downloader from the same version of file. Missing parent directories of `target`
+    /// are created.
+    pub async fn download(
+        &self,
+        id: &Id<LibraryItem>,
+        file: &AudioFile,
+        target: &Path,
+        on_progress: impl Fn(u64),
+    ) -> Result<u64, DownloadError> {
+        let expected = file.metadata.size as u64;
+        let version = FileVersion::of(file);
+        let version_path = with_added_extension(target, VERSION_EXTENSION);
+        if is_downloaded(target, &version_path, &version).await {
+            on_progress(expected);
+            return Ok(expected);
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let partial = with_added_extension(target, PARTIAL_EXTENSION);
+        let state_path = with_added_extension(target, STATE_EXTENSION);
+        let state = self.resume(file, &partial, &state_path).await?;
+        on_progress(
+            state
+                .completed
+                .iter()
+                .map(|&index| self.chunk_range(index, expected).1)
+                .sum(),
+        );
+
+        let pending: Vec<u64> = (0..expected.div_ceil(self.chunk_size))
+            .filter(|index| !state.completed.contains(index))
+            .collect();
+        let state = tokio::sync::Mutex::new(state);
+        stream::iter(pending)
+            .map(|index| {
+                let (start, length) = self.chunk_range(index, expected);
+                let (partial, state_path, state, on_progress) =
+                    (&partial, &state_path, &state, &on_progress);
+                async move {
+                    let chunk = self.download_chunk(id, file, start, length).await?;
+                    let mut output = OpenOptions::new().write(true).open(partial).await?;
+                    output.seek(SeekFrom::Start(start)).await?;
+                    output.write_all(&chunk).await?;
+                    output.sync_data().await?;
+
+                    let mut state = state.lock().await;
+                    state.completed.insert(index);
+                    fs::write(state_path, serde_json::to_vec(&*state)?).await?;
+                    on_progress(length);
+                    Ok::<_, DownloadError>(())
+                }
+            })
+            .buffer_unordered(self.parallel_chunks)
+            .try_collect::<()>()
+            .await?;
+
+        let actual = fs::metadata(&partial).await?.len();
+        if actual != expected {
+            return Err(DownloadError::SizeMismatch {
+                file: file.metadata.filename.clone(),
+                expected,
+                actual,
+            });
+        }
+        fs::rename(&partial, target).await?;
+        // Recorded only once file is in place, so interrupted download is not considered
+        // complete
+        fs::write(&version_path, serde_json::to_vec(&version)?).await?;
+        fs::remove_file(&state_path).await?;
+        Ok(expected)
+    }
+
+    /// State of previous attempt if it was downloading the same version of file, or new
+    /// state with preallocated partial file otherwise.
+    async fn resume(
+        &self,
+        file: &AudioFile,
+        partial: &Path,
+        state_path: &Path,
+    ) -> Result<ChunkState, DownloadError> {
+        let fresh = ChunkState {
+            version: FileVersion::of(file),
+            chunk_size: self.chunk_size,
+            completed: BTreeSet::new(),
+        };
+        let previous = match fs::read(state_path).await {
+            Ok(data) => serde_json::from_slice::<ChunkState>(&data).ok(),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+        let partial_size = fs::metadata(partial).await.map(|metadata| metadata.len());
+        if let Some(previous) = previous {
+            let same_file =
+                previous.version == fresh.version && previous.chunk_size == fresh.chunk_size;
+            if same_file && partial_size.is_ok_and(|size| size == fresh.version.size) {
+                return Ok(previous);
+            }
+        }
+
+        let output = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(partial)
+            .await?;
+        output.set_len(fresh.version.size).await?;
+        fs::write(state_path, serde_json::to_vec(&fresh)?).await?;
+        Ok(fresh)
+    }
+
+    /// Start and length of chunk.
+    fn chunk_range(&self, index: u64, size: u64) -> (u64, u64) {
+        let start = index * self.chunk_size;
+        (start, self.chunk_size.min(size - start))
+    }
+
+    /// `length` bytes of file from `start`, retried according to policy.
+    async fn download_chunk(
+        &self,
+        id: &Id<LibraryItem>,
+        file: &AudioFile,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, DownloadError> {
+        let mut attempt = 0;
+        loop {
+            match self.try_download_chunk(id, file, start, length).await {
+                Err(DownloadError::Request(error))
+                    if attempt + 1 < self.retry.max_attempts && self.retries(&error) =>
+                {
+                    attempt += 1;
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn try_download_chunk(
+        &self,
+        id: &Id<LibraryItem>,
+        file: &AudioFile,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, DownloadError> {
+        let mut download = self
+            .client
+            .download_library_file_range(id, &file.ino, start, Some(start + length - 1))
+            .await?;
+        if let Some(throttle) = &self.throttle {
+            download = download.throttled(throttle.clone());
+        }
+        let mut chunk = Vec::with_capacity(length as usize);
+        while let Some(bytes) = download.next().await {
+            chunk.extend_from_slice(&bytes.map_err(|error| DownloadError::Request(error.into()))?);
+        }
+        // Server ignoring range would send whole file instead
+        if chunk.len() as u64 != length {
+            return Err(DownloadError::SizeMismatch {
+                file: file.metadata.filename.clone(),
+                expected: length,
+                actual: chunk.len() as u64,
+            });
+        }
+        Ok(chunk)
+    }
+
+    /// Whether failed chunk is worth requesting again. Unlike single requests, connection
+    /// lost while receiving body is retried too.
+    fn retries(&self, error: &FusedError<RequestError>) -> bool {
+        match error {
+            FusedError::APIError(APIError::NetworkError { .. }) => self.retry.retry_network_errors,
+            FusedError::DomainError(error) => self.retry.retries_status(error.status()),
+            FusedError::APIError(_) => false,
+        }
+    }
+}
+
+/// Whether `target` is complete download of file of `version`, as recorded at
+/// `version_path`.
+async fn is_downloaded(target: &Path, version_path: &Path, version: &FileVersion) -> bool {
+    let Ok(metadata) = fs::metadata(target).await else {
+        return false;
+    };
+    let recorded = fs::read(version_path)
+        .await
+        .ok()
+        .and_then(|data| serde_json::from_slice::<FileVersion>(&data).ok());
+    metadata.len() == version.size && recorded.as_ref() == Some(version)
+}
+
+/// Path of audio file relative to item directory, as on server. Paths leaving item
+/// directory are rejected.
+fn relative_path(file: &AudioFile) -> Result<PathBuf, DownloadError> {
+    let unsafe_path = || DownloadError::UnsafePath(file.metadata.rel_path.clone());
+    let mut path = PathBuf::new();
+    for component in Path::new(&file.metadata.rel_path).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(unsafe_path())
+            }
+        }
+    }
+    if path.as_os_str().is_empty() {
+        return Err(unsafe_path());
+    }
+    Ok(path)
+}
+
+/// `path` with `extension` added after existing one, so `01.mp3` and `01.m4a` don't share
+/// partial file.
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+/// Audio files of book, or of all podcast episodes.
+fn audio_files(item: &LibraryItem) -> Vec<&AudioFile> {
+    match &item.media {
+        LibraryMedia::Book { audio_files, .. } => {
+            audio_files.iter().filter(|file| !file.exclude).collect()
+        }
+        LibraryMedia::Podcast { episodes, .. } => {
+            episodes.iter().map(|episode| &episode.audio_file).collect()
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Building of endpoint urls relative to server address, which may include subpath
+//! such as `https://host/audiobookshelf/`.
+use reqwest::Url;
+
+use crate::errors::UrlError;
+
+/// Server address, with methods to build urls under it.
+///
+/// Path segments are percent encoded, so ids can't escape their place in url.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoints {
+    /// Always ends with `/`, without query or fragment.
+    root: Url,
+}
+
+impl Endpoints {
+    /// Endpoints of server at `root`. Query and fragment of `root` are dropped.
+    pub fn new(mut root: Url) -> Result<Self, UrlError> {
+        if !matches!(root.scheme(), "http" | "https") {
+            return Err(UrlError::UnsupportedScheme(root));
+        }
+        root.set_query(None);
+        root.set_fragment(None);
+        if !root.path().ends_with('/') {
+            let path = format!("{}/", root.path());
+            root.set_path(&path);
+        }
+        Ok(Self { root })
+    }
+
+    pub fn parse(root: &str) -> Result<Self, UrlError> {
+        Self::new(Url::parse(root)?)
+    }
+
+    /// Server address, ending with `/`.
+    pub fn root(&self) -> &Url {
+        &self.root
+    }
+
+    /// Url of `<root>/<segments>`.
+    pub fn url<S: AsRef<str>>(&self, segments: impl IntoIterator<Item = S>) -> Url {
+        let mut url = self.root.clone();
+        url.path_segments_mut()
+            .expect("http url is always a base")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    /// Url of `<root>/api/<segments>`.
+    pub fn api<S: AsRef<str>>(&self, segments: impl IntoIterator<Item = S>) -> Url {
+        let mut url = self.url(["api"]);
+        url.path_segments_mut()
+            .expect("http url is always a base")
+            .extend(segments);
+        url
+    }
+
+    /// Resolve url given by server, such as track `content_url`.
+    ///
+    /// Absolute urls are returned as is. Paths starting with `/` are taken relative to
+    /// server root, unless they already start with its subpath.
+    pub fn resolve(&self, target: &str) -> Result<Url, UrlError> {
+        match Url::parse(target) {
+            Ok(url) => return Ok(url),
+            Err(url::ParseError::RelativeUrlWithoutBase) => {}
+            Err(error) => return Err(error.into()),
+        }
+        let relative = target
+            .strip_prefix(self.root.path())
+            .or_else(|| target.strip_prefix('/'))
+            .unwrap_or(target);
+        Ok(self.root.join(relative)?)
+    }
+}
+
+impl TryFrom<Url> for Endpoints {
+    type Error = UrlError;
+
+    fn try_from(root: Url) -> Result<Self, UrlError> {
+        Self::new(root)
+    }
+}
@@ -1,40 +1,235 @@
 use std::fmt::Display;
 
-use reqwest::StatusCode;
+use reqwest::{Method, StatusCode, Url};
+use serde::Deserialize;
 use thiserror::Error;
 
+use crate::schema::Permission;
+
+/// Method and url of failed request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestInfo {
+    pub method: Method,
+    pub url: Url,
+}
+
+impl RequestInfo {
+    pub(crate) fn of(request: &reqwest::Request) -> Self {
+        Self {
+            method: request.method().clone(),
+            url: request.url().clone(),
+        }
+    }
+}
+
+impl Display for RequestInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.url)
+    }
+}
+
 #[derive(Error, Debug)]
 pub struct ResponseError {
+    pub request: Box<RequestInfo>,
     pub status: StatusCode,
-    pub response: String,
+    pub error: ServerError,
 }
 
 impl Display for ResponseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Response failed. Status={}, response={}",
-            self.status, self.response
+            "{} failed. Status={}, error={}",
+            self.request, self.status, self.error
         )
     }
 }
 
+/// Error description sent by server. Server responds either with JSON object or plain text,
+/// which is kept as `message`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ServerError {
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ServerError {
+    pub fn parse(body: &str) -> Self {
+        match serde_json::from_str::<ServerError>(body) {
+            Ok(error) if error.error.is_some() || error.message.is_some() => error,
+            _ => Self {
+                error: None,
+                message: (!body.is_empty()).then(|| body.to_string()),
+            },
+        }
+    }
+}
+
+impl Display for ServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.error, &self.message) {
+            (Some(error), Some(message)) => write!(f, "{error}: {message}"),
+            (Some(text), None) | (None, Some(text)) => write!(f, "{text}"),
+            (None, None) => write!(f, "no details"),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum APIError {
-    #[error("Connection failed")]
-    NetworkError(reqwest::Error),
+    #[error("{request}: connection failed")]
+    NetworkError {
+        request: Box<RequestInfo>,
+        #[source]
+        source: reqwest::Error,
+    },
     #[error("Connection failed")]
     UnknownError(Box<dyn std::error::Error + Send + Sync>),
-    #[error("Invalid Response")]
-    InvalidResponseSchema(serde_path_to_error::Error<serde_json::Error>),
+    #[error("{request}: invalid response")]
+    InvalidResponseSchema {
+        request: Box<RequestInfo>,
+        #[source]
+        source: serde_path_to_error::Error<serde_json::Error>,
+    },
     #[error("Invalid Request")]
     InvalidRequestSchema(serde_json::Error),
+    #[error("{0}: cancelled")]
+    Cancelled(Box<RequestInfo>),
+    #[error("{0}")]
+    InvalidUrl(#[from] UrlError),
+}
+
+impl APIError {
+    pub(crate) fn network(request: &RequestInfo, source: reqwest::Error) -> Self {
+        Self::NetworkError {
+            request: Box::new(request.clone()),
+            source,
+        }
+    }
+}
+
+/// Unsuccessful response, classified by its status code.
+#[derive(Error, Debug)]
+pub enum RequestError {
+    #[error("Bad request: {0}")]
+    BadRequest(ResponseError),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(ResponseError),
+    #[error("Forbidden: {0}")]
+    Forbidden(ResponseError),
+    #[error("Not found: {0}")]
+    NotFound(ResponseError),
+    #[error("Rate limited: {0}")]
+    RateLimited(ResponseError),
+    #[error("Server error: {0}")]
+    ServerError(ResponseError),
+    #[error("{0}")]
+    Other(ResponseError),
+    /// Rejected by client before sending request, as permissions of logged in user don't
+    /// allow it
+    #[error("Permission denied: {0} is not allowed")]
+    PermissionDenied(Permission),
+}
+
+impl RequestError {
+    /// Response of server, `None` for requests rejected before sending.
+    pub fn response(&self) -> Option<&ResponseError> {
+        match self {
+            RequestError::BadRequest(response)
+            | RequestError::Unauthorized(response)
+            | RequestError::Forbidden(response)
+            | RequestError::NotFound(response)
+            | RequestError::RateLimited(response)
+            | RequestError::ServerError(response)
+            | RequestError::Other(response) => Some(response),
+            RequestError::PermissionDenied(_) => None,
+        }
+    }
+
+    /// Status of response. `PermissionDenied` is reported as 403, which server would
+    /// respond with.
+    pub fn status(&self) -> StatusCode {
+        match self.response() {
+            Some(response) => response.status,
+            None => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl From<ResponseError> for RequestError {
+    fn from(response: ResponseError) -> Self {
+        match response.status {
+            StatusCode::BAD_REQUEST => RequestError::BadRequest(response),
+            StatusCode::UNAUTHORIZED => RequestError::Unauthorized(response),
+            StatusCode::FORBIDDEN => RequestError::Forbidden(response),
+            StatusCode::NOT_FOUND => RequestError::NotFound(response),
+            StatusCode::TOO_MANY_REQUESTS => RequestError::RateLimited(response),
+            status if status.is_server_error() => RequestError::ServerError(response),
+            _ => RequestError::Other(response),
+        }
+    }
+}
+
+/// String which can not be used as id.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("Invalid id: {0:?}")]
+pub struct InvalidId(pub String);
+
+/// String which can not be parsed as `Query`.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("Invalid query: {0:?}")]
+pub struct InvalidQuery(pub String);
+
+/// Url which can't be used as server address, or resolved against it.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum UrlError {
+    #[error("Invalid url: {0}")]
+    Parse(#[from] url::ParseError),
+    #[error("{0} is not http or https url")]
+    UnsupportedScheme(Url),
+}
+
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    #[error("Request failed: {0}")]
+    Request(#[from] FusedError<RequestError>),
+    #[error("Failed to store download: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+    #[error("Size of {file} is {actual} bytes, expected {expected}")]
+    SizeMismatch {
+        file: String,
+        expected: u64,
+        actual: u64,
+    },
+    /// Path given by server would place file outside of item directory
+    #[error("Unsafe file path: {0}")]
+    UnsafePath(String),
 }
 
 #[derive(Error, Debug)]
 pub enum AuthError {
     #[error("Invalid credentials")]
     InvalidCredentials,
+    #[error("Invalid token")]
+    InvalidToken,
+    #[error("Credential store failed: {0}")]
+    CredentialStore(#[from] CredentialError),
+}
+
+/// Failure of `CredentialStore`.
+#[derive(Error, Debug)]
+pub enum CredentialError {
+    #[error("Failed to access credential file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unable to decrypt credentials: wrong passphrase or corrupted file")]
+    Decrypt,
+    #[error("Invalid stored credentials: {0}")]
+    Format(#[from] serde_json::Error),
+    #[cfg(feature = "keyring")]
+    #[error("Keyring failed: {0}")]
+    Keyring(#[from] keyring::Error),
 }
 
 #[derive(Error, Debug)]
@@ -70,7 +265,7 @@ impl<T> FusedError<T> {
     }
 }
 
-impl FusedError<ResponseError> {
+impl<T: std::error::Error + Send + Sync + 'static> FusedError<T> {
     pub fn to_api_error(self) -> APIError {
         match self {
             FusedError::APIError(error) => error,
@@ -78,3 +273,9 @@ impl FusedError<ResponseError> {
         }
     }
 }
+
+impl FusedError<ResponseError> {
+    pub fn to_request_error(self) -> FusedError<RequestError> {
+        self.map_domain_error(RequestError::from)
+    }
+}
@@ -0,0 +1,569 @@
+//! Real-time server events, delivered over audiobookshelf socket.io endpoint.
+//!
+//! Only websocket transport of engine.io v4 is supported, which is enough for audiobookshelf.
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, Stream, StreamExt};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::endpoints::Endpoints;
+use crate::errors::{APIError, AuthError, FusedError};
+use crate::params::{LibraryItemParams, Sort};
+use crate::schema::{
+    Backup, BatchQuickMatchResult, EpisodeDownload, Id, LibraryItem, LibraryScan,
+    MediaProgressUpdate, OnlineUser, PlaybackSession, UserData,
+};
+use crate::UserClient;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Event pushed by server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AbsEvent {
+    ItemAdded(Box<LibraryItem>),
+    ItemUpdated(Box<LibraryItem>),
+    ItemRemoved(Box<LibraryItem>),
+    UserUpdated(Box<UserData>),
+    MediaProgressUpdated(Box<MediaProgressUpdate>),
+    EpisodeDownloadQueued(EpisodeDownload),
+    EpisodeDownloadFinished(EpisodeDownload),
+    BackupCompleted(Backup),
+    /// Playback session of user started or progressed
+    SessionUpdated(Box<OnlineUser>),
+    SessionClosed(Id<PlaybackSession>),
+    ScanStarted(LibraryScan),
+    ScanProgress(LibraryScan),
+    ScanCompleted(LibraryScan),
+    BatchQuickMatchCompleted(BatchQuickMatchResult),
+    /// Event which is not known, or which payload does not match expected schema
+    Raw(String, Value),
+    /// Change of connection state, emitted by client itself
+    Connection(ConnectionState),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connected,
+    Disconnected,
+    /// Waiting `delay` before next connection attempt
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+    },
+    /// Reconnection attempts are exhausted, stream ends
+    GaveUp,
+}
+
+/// Exponential backoff between reconnection attempts.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// Give up after this many failed attempts in a row. Never give up if `None`.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Policy which never reconnects.
+    pub fn never() -> Self {
+        Self {
+            max_attempts: Some(0),
+            ..Self::default()
+        }
+    }
+
+    /// Delay before `attempt`, counted from zero.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(attempt as i32);
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()))
+    }
+}
+
+impl AbsEvent {
+    pub fn from_raw(name: String, data: Value) -> Self {
+        fn parse<T: DeserializeOwned>(
+            name: String,
+            data: Value,
+            variant: impl Fn(T) -> AbsEvent,
+        ) -> AbsEvent {
+            match T::deserialize(&data) {
+                Ok(payload) => variant(payload),
+                Err(_) => AbsEvent::Raw(name, data),
+            }
+        }
+
+        match name.as_str() {
+            "item_added" => parse(name, data, |item| Self::ItemAdded(Box::new(item))),
+            "item_updated" => parse(name, data, |item| Self::ItemUpdated(Box::new(item))),
+            "item_removed" => parse(name, data, |item| Self::ItemRemoved(Box::new(item))),
+            "user_updated" => parse(name, data, |user| Self::UserUpdated(Box::new(user))),
+            "user_item_progress_updated" => parse(name, data, |progress| {
+                Self::MediaProgressUpdated(Box::new(progress))
+            }),
+            "episode_download_queued" => parse(name, data, Self::EpisodeDownloadQueued),
+            "episode_download_finished" => parse(name, data, Self::EpisodeDownloadFinished),
+            "backup_completed" => parse(name, data, Self::BackupCompleted),
+            "user_stream_update" => parse(name, data, |user| Self::SessionUpdated(Box::new(user))),
+            "user_session_closed" => parse(name, data, Self::SessionClosed),
+            "scan_start" => parse(name, data, Self::ScanStarted),
+            "scan_progress" => parse(name, data, Self::ScanProgress),
+            "scan_complete" => parse(name, data, Self::ScanCompleted),
+            "batch_quickmatch_complete" => parse(name, data, Self::BatchQuickMatchCompleted),
+            _ => Self::Raw(name, data),
+        }
+    }
+}
+
+/// Stream of server events.
+///
+/// Connection is maintained by background task, which is stopped then stream is dropped.
+/// Stream ends if reconnection attempts are exhausted.
+pub struct EventStream {
+    receiver: mpsc::Receiver<AbsEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for EventStream {
+    type Item = AbsEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+struct Handshake {
+    /// Milliseconds
+    ping_interval: u64,
+    /// Milliseconds
+    ping_timeout: u64,
+}
+
+impl Handshake {
+    /// Time without any message from server after which connection is considered lost.
+    fn silence_limit(&self) -> Duration {
+        Duration::from_millis(self.ping_interval + self.ping_timeout)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Packet {
+    Open(String),
+    Close,
+    Ping,
+    Connect,
+    ConnectError(String),
+    Event(String, Value),
+    Other,
+}
+
+impl Packet {
+    /// Parse engine.io packet, and socket.io packet inside if it is a message.
+    fn parse(text: &str) -> Self {
+        let (kind, payload) = text.split_at(text.len().min(1));
+        match kind {
+            "0" => Self::Open(payload.to_string()),
+            "1" => Self::Close,
+            "2" => Self::Ping,
+            "4" => Self::parse_message(payload),
+            _ => Self::Other,
+        }
+    }
+
+    fn parse_message(text: &str) -> Self {
+        let (kind, payload) = text.split_at(text.len().min(1));
+        match kind {
+            "0" => Self::Connect,
+            // Server left namespace, no more events come over this connection
+            "1" => Self::Close,
+            "4" => Self::ConnectError(payload.to_string()),
+            "2" => {
+                // Skip acknowledgement id, if any
+                let payload = payload.trim_start_matches(|c: char| c.is_ascii_digit());
+                match serde_json::from_str::<Vec<Value>>(payload) {
+                    Ok(values) => {
+                        let mut values = values.into_iter();
+                        match values.next() {
+                            Some(Value::String(name)) => {
+                                Self::Event(name, values.next().unwrap_or(Value::Null))
+                            }
+                            _ => Self::Other,
+                        }
+                    }
+                    Err(_) => Self::Other,
+                }
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+fn socket_error(error: tokio_tungstenite::tungstenite::Error) -> APIError {
+    APIError::UnknownError(error.into())
+}
+
+fn protocol_error(message: &str) -> APIError {
+    APIError::UnknownError(message.into())
+}
+
+/// Build websocket URL of socket.io endpoint.
+pub(crate) fn socket_url(endpoints: &Endpoints) -> Url {
+    let mut url = endpoints.url(["socket.io", ""]);
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    url.set_scheme(scheme).unwrap();
+    url.set_query(Some("EIO=4&transport=websocket"));
+    url
+}
+
+pub(crate) async fn connect(
+    client: UserClient,
+    url: Url,
+    policy: ReconnectPolicy,
+) -> Result<EventStream, FusedError<AuthError>> {
+    let connection = authorized_handshake(&client, &url).await?;
+    let (sender, receiver) = mpsc::channel(64);
+    let task = tokio::spawn(async move {
+        maintain(client, url, policy, connection, sender).await;
+    });
+    Ok(EventStream { receiver, task })
+}
+
+/// Forward events, reconnecting then connection is lost.
+async fn maintain(
+    client: UserClient,
+    url: Url,
+    policy: ReconnectPolicy,
+    connection: (Socket, Handshake),
+    sender: mpsc::Sender<AbsEvent>,
+) {
+    let notify = |state| sender.send(AbsEvent::Connection(state));
+    let mut connection = Some(connection);
+    let mut disconnected_at = None;
+    let mut attempt = 0;
+    loop {
+        if let Some((mut socket, handshake)) = connection.take() {
+            attempt = 0;
+            if notify(ConnectionState::Connected).await.is_err() {
+                return;
+            }
+            if let Some(since) = disconnected_at {
+                // Server may be reachable only partially, missed events are best effort
+                let _ = resync(&client, since, &sender).await;
+            }
+            if let Ok(Closed::ByReceiver) = run(&mut socket, handshake, &sender).await {
+                return;
+            }
+            disconnected_at = Some(Utc::now());
+            if notify(ConnectionState::Disconnected).await.is_err() {
+                return;
+            }
+        }
+
+        if policy.max_attempts.is_some_and(|max| attempt >= max) {
+            let _ = notify(ConnectionState::GaveUp).await;
+            return;
+        }
+        let delay = policy.delay(attempt);
+        attempt += 1;
+        let state = ConnectionState::Reconnecting { attempt, delay };
+        if notify(state).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+        connection = authorized_handshake(&client, &url).await.ok();
+    }
+}
+
+/// Emit events which could have been missed since `since`: user data, media progress and
+/// library items updated after it.
+async fn resync(
+    client: &UserClient,
+    since: DateTime<Utc>,
+    sender: &mpsc::Sender<AbsEvent>,
+) -> Result<(), APIError> {
+    const PAGE_SIZE: usize = 50;
+    const MAX_PAGES: usize = 20;
+
+    let user = client.me().await.map_err(FusedError::to_api_error)?;
+    let mut progress: Vec<_> = user
+        .media_progress
+        .iter()
+        .filter(|progress| progress.last_update > since)
+        .cloned()
+        .collect();
+    progress.sort_by_key(|progress| progress.last_update);
+    if sender
+        .send(AbsEvent::UserUpdated(Box::new(user)))
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+    for progress in progress {
+        // Session is unknown, so update is reported as made by other device
+        let update = MediaProgressUpdate {
+            id: progress.id.clone(),
+            session_id: None,
+            device_description: None,
+            data: progress,
+            #[cfg(feature = "extra-fields")]
+            extra: Default::default(),
+        };
+        let event = AbsEvent::MediaProgressUpdated(Box::new(update));
+        if sender.send(event).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    for library in client.libraries().await.map_err(FusedError::to_api_error)? {
+        for page in 0..MAX_PAGES {
+            let params = LibraryItemParams {
+                limit: PAGE_SIZE,
+                page,
+                sort: Some(Sort::UpdatedAt),
+                desc: true,
+                ..Default::default()
+            };
+            let items = client
+                .library_items(&library.id, params)
+                .await
+                .map_err(FusedError::to_api_error)?;
+            let updated: Vec<_> = items
+                .iter()
+                .take_while(|item| item.updated_at > since)
+                .collect();
+            for item in &updated {
+                let full_item = Box::new(
+                    client
+                        .library_item(&item.id)
+                        .await
+                        .map_err(FusedError::to_api_error)?,
+                );
+                let event = if item.added_at > since {
+                    AbsEvent::ItemAdded(full_item)
+                } else {
+                    AbsEvent::ItemUpdated(full_item)
+                };
+                if sender.send(event).await.is_err() {
+                    return Ok(());
+                }
+            }
+            if updated.len() < PAGE_SIZE {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handshake with current token, renewing it once if server rejects it, e.g. then it
+/// expired while connection was lost.
+async fn authorized_handshake(
+    client: &UserClient,
+    url: &Url,
+) -> Result<(Socket, Handshake), FusedError<AuthError>> {
+    let token = client.token();
+    match handshake(url, &token).await {
+        Err(FusedError::DomainError(AuthError::InvalidToken)) => {
+            client.reauthenticate_rejected(&token).await?;
+            handshake(url, &client.token()).await
+        }
+        result => result,
+    }
+}
+
+/// Open connection, join default namespace and authenticate.
+async fn handshake(url: &Url, token: &str) -> Result<(Socket, Handshake), FusedError<AuthError>> {
+    let (mut socket, _) = connect_async(url.as_str()).await.map_err(socket_error)?;
+
+    let handshake = match next_packet(&mut socket, Duration::from_secs(30)).await? {
+        Packet::Open(payload) => serde_json::from_str::<Handshake>(&payload)
+            .map_err(|_| protocol_error("Invalid engine.io handshake"))?,
+        _ => return Err(protocol_error("Expected engine.io handshake").into()),
+    };
+
+    send(&mut socket, "40".into()).await?;
+    let auth = Value::Array(vec!["auth".into(), token.into()]);
+    loop {
+        match next_packet(&mut socket, handshake.silence_limit()).await? {
+            Packet::Ping => send(&mut socket, "3".into()).await?,
+            Packet::Connect => send(&mut socket, format!("42{auth}")).await?,
+            Packet::ConnectError(_) => {
+                return Err(protocol_error("Unable to join socket.io namespace").into())
+            }
+            Packet::Event(name, _) if name == "init" => return Ok((socket, handshake)),
+            Packet::Event(name, _) if name == "invalid_token" || name == "auth_failed" => {
+                return Err(FusedError::DomainError(AuthError::InvalidToken))
+            }
+            Packet::Close => return Err(protocol_error("Connection closed").into()),
+            _ => {}
+        }
+    }
+}
+
+enum Closed {
+    ByServer,
+    ByReceiver,
+}
+
+/// Forward events until connection is lost or stream is dropped.
+async fn run(
+    socket: &mut Socket,
+    handshake: Handshake,
+    sender: &mpsc::Sender<AbsEvent>,
+) -> Result<Closed, APIError> {
+    loop {
+        match next_packet(socket, handshake.silence_limit()).await? {
+            Packet::Ping => send(socket, "3".into()).await?,
+            Packet::Event(name, data) => {
+                // Receiver is gone, nobody listens anymore
+                let sent = sender.send(AbsEvent::from_raw(name, data)).await;
+                if sent.is_err() {
+                    return Ok(Closed::ByReceiver);
+                }
+            }
+            Packet::Close => return Ok(Closed::ByServer),
+            _ => {}
+        }
+    }
+}
+
+async fn next_packet(socket: &mut Socket, limit: Duration) -> Result<Packet, APIError> {
+    loop {
+        let message = timeout(limit, socket.next())
+            .await
+            .map_err(|_| protocol_error("Server stopped responding"))?
+            .ok_or_else(|| protocol_error("Connection closed"))?
+            .map_err(socket_error)?;
+        match message {
+            Message::Text(text) => return Ok(Packet::parse(&text)),
+            Message::Close(_) => return Ok(Packet::Close),
+            _ => {}
+        }
+    }
+}
+
+async fn send(socket: &mut Socket, text: String) -> Result<(), APIError> {
+    socket.send(Message::Text(text)).await.map_err(socket_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_engine_io_packets() {
+        assert_eq!(
+            Packet::parse(r#"0{"sid":"a","pingInterval":25000,"pingTimeout":20000}"#),
+            Packet::Open(r#"{"sid":"a","pingInterval":25000,"pingTimeout":20000}"#.to_string())
+        );
+        assert_eq!(Packet::parse("2"), Packet::Ping);
+        assert_eq!(Packet::parse("1"), Packet::Close);
+        assert_eq!(Packet::parse("40"), Packet::Connect);
+        assert_eq!(Packet::parse("41"), Packet::Close);
+        assert_eq!(
+            Packet::parse(r#"44{"message":"Not allowed"}"#),
+            Packet::ConnectError(r#"{"message":"Not allowed"}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn parses_socket_io_events() {
+        assert_eq!(
+            Packet::parse(r#"42["user_updated",{"id":"usr_1"}]"#),
+            Packet::Event(
+                "user_updated".to_string(),
+                serde_json::json!({"id": "usr_1"})
+            )
+        );
+        // Acknowledgement id precedes payload
+        assert_eq!(
+            Packet::parse(r#"4217["init"]"#),
+            Packet::Event("init".to_string(), Value::Null)
+        );
+    }
+
+    #[test]
+    fn ignores_malformed_packets() {
+        assert_eq!(Packet::parse(""), Packet::Other);
+        assert_eq!(Packet::parse("9"), Packet::Other);
+        assert_eq!(Packet::parse("42not json"), Packet::Other);
+        assert_eq!(Packet::parse(r#"42[17,"user_updated"]"#), Packet::Other);
+        assert_eq!(Packet::parse("42[]"), Packet::Other);
+    }
+
+    /// Serve single socket.io connection, answering authentication with `reply` event.
+    async fn server(reply: &'static str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let open = r#"0{"sid":"a","pingInterval":25000,"pingTimeout":20000}"#;
+            socket.send(Message::Text(open.into())).await.unwrap();
+            socket.send(Message::Text("2".into())).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                match text.as_str() {
+                    "40" => socket.send(Message::Text("40".into())).await.unwrap(),
+                    "3" => {}
+                    text if text.starts_with(r#"42["auth","token"]"#) => {
+                        let reply = format!(r#"42["{reply}"]"#);
+                        socket.send(Message::Text(reply)).await.unwrap();
+                    }
+                    text => panic!("Unexpected packet {text}"),
+                }
+            }
+        });
+        Url::parse(&format!(
+            "ws://{address}/socket.io/?EIO=4&transport=websocket"
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn handshake_authenticates() {
+        let url = server("init").await;
+        let (_, handshake) = handshake(&url, "token").await.unwrap();
+        assert_eq!(handshake.silence_limit(), Duration::from_secs(45));
+    }
+
+    #[tokio::test]
+    async fn handshake_reports_invalid_token() {
+        let url = server("invalid_token").await;
+        assert!(matches!(
+            handshake(&url, "token").await,
+            Err(FusedError::DomainError(AuthError::InvalidToken))
+        ));
+    }
+}
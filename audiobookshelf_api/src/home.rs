@@ -0,0 +1,198 @@
+//! Home screen of library: personalized shelves joined with progress of user, ready
+//! to be rendered.
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use crate::errors::{FusedError, RequestError};
+use crate::schema::{
+    Id, Library, LibraryItemMinified, MediaProgress, PodcastEpisode, Series, Shelf, ShelfAuthor,
+    ShelfEntities, ShelfSeries, UserData,
+};
+use crate::UserClient;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HomeScreen {
+    /// In order given by server
+    pub shelves: Vec<HomeShelf>,
+    /// Next unfinished book of each series user finished some books of, most recently
+    /// listened series first
+    pub up_next: Vec<UpNext>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HomeShelf {
+    /// Kind of shelf, such as `continue-listening` or `recent-series`
+    pub id: String,
+    pub label: String,
+    /// Number of matching entities, which may exceed number of entries
+    pub total: usize,
+    pub entries: Vec<HomeEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HomeEntry {
+    Item(Box<ItemEntry>),
+    Series(SeriesEntry),
+    Author(ShelfAuthor),
+}
+
+/// Book, podcast or podcast episode with its progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemEntry {
+    pub item: LibraryItemMinified,
+    pub episode: Option<PodcastEpisode>,
+    pub progress: Option<MediaProgress>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesEntry {
+    pub series: ShelfSeries,
+    /// Number of books of series user finished
+    pub finished: usize,
+    /// Index of first unfinished book in `series.books`, `None` once every book is
+    /// finished
+    pub next: Option<usize>,
+}
+
+/// Book to continue series with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpNext {
+    pub series_id: Id<Series>,
+    pub series_name: String,
+    pub item: LibraryItemMinified,
+    /// Progress of `item`, if user already started it
+    pub progress: Option<MediaProgress>,
+}
+
+impl ItemEntry {
+    /// Part of item listened, from 0 to 1, for drawing progress bar.
+    pub fn fraction(&self) -> f64 {
+        match &self.progress {
+            Some(progress) if progress.is_finished => 1.0,
+            Some(progress) => progress.progress.clamp(0.0, 1.0),
+            None => 0.0,
+        }
+    }
+}
+
+impl SeriesEntry {
+    /// Part of series finished, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        if self.series.books.is_empty() {
+            return 0.0;
+        }
+        self.finished as f64 / self.series.books.len() as f64
+    }
+
+    /// First unfinished book.
+    pub fn next_book(&self) -> Option<&LibraryItemMinified> {
+        self.next.map(|index| &self.series.books[index])
+    }
+}
+
+impl HomeScreen {
+    /// Join `shelves` with progress of `user`.
+    pub fn new(shelves: Vec<Shelf>, user: &UserData) -> Self {
+        let mut up_next = vec![];
+        let mut seen_series = HashSet::new();
+        let shelves = shelves
+            .into_iter()
+            .map(|shelf| {
+                let entries = match shelf.entities {
+                    ShelfEntities::Book { entities: items }
+                    | ShelfEntities::Podcast { entities: items }
+                    | ShelfEntities::Episode { entities: items } => items
+                        .into_iter()
+                        .map(|entity| {
+                            let episode_id = entity.recent_episode.as_ref().map(|e| &e.id);
+                            let progress = user.progress_for(&entity.item.id, episode_id).cloned();
+                            HomeEntry::Item(Box::new(ItemEntry {
+                                item: entity.item,
+                                episode: entity.recent_episode,
+                                progress,
+                            }))
+                        })
+                        .collect(),
+                    ShelfEntities::Series { entities: series } => series
+                        .into_iter()
+                        .map(|series| {
+                            let entry = series_entry(series, user);
+                            if seen_series.insert(entry.series.id.clone()) {
+                                up_next.extend(self::up_next(&entry, user));
+                            }
+                            HomeEntry::Series(entry)
+                        })
+                        .collect(),
+                    ShelfEntities::Authors { entities: authors } => {
+                        authors.into_iter().map(HomeEntry::Author).collect()
+                    }
+                    _ => vec![],
+                };
+                HomeShelf {
+                    id: shelf.id,
+                    label: shelf.label,
+                    total: shelf.total,
+                    entries,
+                }
+            })
+            .collect();
+
+        up_next.sort_by_key(|(last_update, _)| std::cmp::Reverse(*last_update));
+        HomeScreen {
+            shelves,
+            up_next: up_next.into_iter().map(|(_, up_next)| up_next).collect(),
+        }
+    }
+}
+
+fn series_entry(series: ShelfSeries, user: &UserData) -> SeriesEntry {
+    let is_finished = |book: &LibraryItemMinified| {
+        user.progress_for(&book.id, None)
+            .is_some_and(|progress| progress.is_finished)
+    };
+    let finished = series.books.iter().filter(|book| is_finished(book)).count();
+    let next = series.books.iter().position(|book| !is_finished(book));
+    SeriesEntry {
+        series,
+        finished,
+        next,
+    }
+}
+
+/// Next book of series, along with time series was last listened, for ordering.
+fn up_next(entry: &SeriesEntry, user: &UserData) -> Option<(DateTime<Utc>, UpNext)> {
+    if entry.finished == 0 {
+        return None;
+    }
+    let item = entry.next_book()?.clone();
+    let last_update = entry
+        .series
+        .books
+        .iter()
+        .filter_map(|book| user.progress_for(&book.id, None))
+        .map(|progress| progress.last_update)
+        .max()?;
+    let progress = user.progress_for(&item.id, None).cloned();
+    Some((
+        last_update,
+        UpNext {
+            series_id: entry.series.id.clone(),
+            series_name: entry.series.name.clone(),
+            item,
+            progress,
+        },
+    ))
+}
+
+impl UserClient {
+    /// Home screen of library, with up to `limit` entries on each shelf.
+    pub async fn home_screen(
+        &self,
+        id: &Id<Library>,
+        limit: usize,
+    ) -> Result<HomeScreen, FusedError<RequestError>> {
+        let (shelves, user) = tokio::try_join!(self.personalized(id, limit), self.me())?;
+        Ok(HomeScreen::new(shelves, &user))
+    }
+}
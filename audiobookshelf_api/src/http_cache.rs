@@ -0,0 +1,128 @@
+//! Cache of GET responses, revalidated with conditional requests.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+
+use crate::Url;
+
+/// Responses to GET requests, stored by url together with their `ETag` and `Last-Modified`
+/// headers. Stored response is returned then server confirms that it is not modified.
+///
+/// Responses are stored per url, so cache should not be shared between clients of
+/// different users.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    /// Directory to persist responses into, so they survive restarts
+    directory: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    pub(crate) body: String,
+}
+
+impl ResponseCache {
+    /// Cache kept in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache kept in memory and persisted into `directory`, which is created if needed.
+    pub fn with_directory(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            entries: Default::default(),
+            directory: Some(directory),
+        })
+    }
+
+    /// Drop all stored responses.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        if let Some(directory) = &self.directory {
+            if let Ok(files) = std::fs::read_dir(directory) {
+                for file in files.flatten() {
+                    let _ = std::fs::remove_file(file.path());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, url: &Url) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get(url.as_str()) {
+            return Some(entry.clone());
+        }
+        let entry = self.load(url)?;
+        entries.insert(url.to_string(), entry.clone());
+        Some(entry)
+    }
+
+    /// Store response, if it has headers allowing revalidation.
+    pub(crate) fn insert(&self, url: &Url, headers: &HeaderMap, body: &str) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let entry = CachedResponse {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            body: body.to_string(),
+        };
+        if entry.etag.is_none() && entry.last_modified.is_none() {
+            return;
+        }
+        if let Some(path) = self.path(url) {
+            if let Ok(data) = serde_json::to_vec(&entry) {
+                let _ = std::fs::write(path, data);
+            }
+        }
+        self.entries.lock().unwrap().insert(url.to_string(), entry);
+    }
+
+    fn load(&self, url: &Url) -> Option<CachedResponse> {
+        let data = std::fs::read(self.path(url)?).ok()?;
+        let entry: CachedResponse = serde_json::from_slice(&data).ok()?;
+        // Guard against hash collisions
+        (entry.url == url.as_str()).then_some(entry)
+    }
+
+    fn path(&self, url: &Url) -> Option<PathBuf> {
+        let directory = self.directory.as_ref()?;
+        Some(directory.join(format!("{:016x}.json", fnv1a(url.as_str()))))
+    }
+}
+
+impl CachedResponse {
+    /// Make request conditional on response being changed since it was stored.
+    pub(crate) fn add_conditions(&self, headers: &mut HeaderMap) {
+        if let Some(value) = self.etag.as_ref().and_then(|etag| etag.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self
+            .last_modified
+            .as_ref()
+            .and_then(|date| date.parse().ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+    }
+}
+
+/// Hash stable between runs, used to name persisted files.
+pub(crate) fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,162 @@
+//! Incremental parsing of paginated responses, so items are handed out as they arrive
+//! instead of holding whole response body, and every item parsed from it, in memory.
+use std::fmt;
+use std::io::BufReader;
+use std::marker::PhantomData;
+
+use futures_util::{stream, Stream, TryStreamExt};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess};
+use tokio::sync::mpsc;
+use tokio_util::io::{StreamReader, SyncIoBridge};
+
+use crate::errors::{APIError, FusedError, RequestInfo, ResponseError};
+
+/// Number of parsed items waiting to be consumed before parser blocks.
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Part of paginated response.
+pub(crate) enum PageEvent<T> {
+    Item(T),
+    /// All items of page are parsed. `total` is number of items across all pages.
+    End {
+        total: usize,
+    },
+}
+
+type Event<T> = Result<PageEvent<T>, FusedError<ResponseError>>;
+
+/// Parse `results` of paginated response one item at a time, followed by `PageEvent::End`.
+///
+/// Parsing runs on blocking thread, and is stopped once returned stream is dropped.
+pub(crate) fn paginated_items<T>(
+    info: RequestInfo,
+    response: reqwest::Response,
+) -> impl Stream<Item = Event<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    let body = StreamReader::new(response.bytes_stream().map_err(std::io::Error::other));
+    let reader = BufReader::new(SyncIoBridge::new(body));
+    tokio::task::spawn_blocking(move || {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let mut track = serde_path_to_error::Track::new();
+        let seed = PageSeed { sender: &sender };
+        let result = seed
+            .deserialize(serde_path_to_error::Deserializer::new(
+                &mut deserializer,
+                &mut track,
+            ))
+            .map_err(|error| serde_path_to_error::Error::new(track.path(), error));
+        let event = match result {
+            Ok(total) => Ok(PageEvent::End { total }),
+            Err(error) => Err(to_api_error(&info, error).into()),
+        };
+        // Fails only if stream is already dropped, with nobody to report to
+        let _ = sender.blocking_send(event);
+    });
+    stream::unfold(receiver, |mut receiver| async move {
+        let event = receiver.recv().await?;
+        Some((event, receiver))
+    })
+}
+
+/// Failure to read body is reported as network error, rather than invalid response.
+fn to_api_error(
+    info: &RequestInfo,
+    error: serde_path_to_error::Error<serde_json::Error>,
+) -> APIError {
+    if !error.inner().is_io() {
+        return APIError::InvalidResponseSchema {
+            request: Box::new(info.clone()),
+            source: error,
+        };
+    }
+    let error = std::io::Error::from(error.into_inner());
+    match error.into_inner() {
+        Some(error) => match error.downcast::<reqwest::Error>() {
+            Ok(error) => APIError::network(info, *error),
+            Err(error) => APIError::UnknownError(error),
+        },
+        None => APIError::UnknownError("Failed to read response body".into()),
+    }
+}
+
+/// Sends items of `results` field as they are parsed, and returns `total` field.
+struct PageSeed<'a, T> {
+    sender: &'a mpsc::Sender<Event<T>>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for PageSeed<'_, T> {
+    type Value = usize;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> de::Visitor<'de> for PageSeed<'_, T> {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("paginated response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<usize, A::Error> {
+        let mut total = None;
+        let mut has_results = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "results" => {
+                    map.next_value_seed(ItemsSeed {
+                        sender: self.sender,
+                        item: PhantomData,
+                    })?;
+                    has_results = true;
+                }
+                "total" => total = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !has_results {
+            return Err(de::Error::missing_field("results"));
+        }
+        total.ok_or_else(|| de::Error::missing_field("total"))
+    }
+}
+
+struct ItemsSeed<'a, T> {
+    sender: &'a mpsc::Sender<Event<T>>,
+    item: PhantomData<T>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for ItemsSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> de::Visitor<'de> for ItemsSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("list of items")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(item) = seq.next_element::<T>()? {
+            if self
+                .sender
+                .blocking_send(Ok(PageEvent::Item(item)))
+                .is_err()
+            {
+                return Err(de::Error::custom("Response stream dropped"));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,86 +1,601 @@
+pub mod admin;
+pub mod audio_stream;
+pub mod auth;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod credentials;
+pub mod download;
+pub mod downloads;
+pub mod endpoints;
 pub mod errors;
+#[cfg(feature = "events")]
+pub mod events;
+pub mod home;
+pub mod http_cache;
+mod json_stream;
 pub mod params;
+pub mod progress_sync;
+pub mod query;
+pub mod rate_limit;
+pub mod retry;
 pub mod schema;
+pub mod servers;
+pub mod session;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod transport;
 
-use std::sync::OnceLock;
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::{Seek, SeekFrom};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use std::time::Duration;
 
-use errors::{APIError, AuthError, FusedError, ResponseError};
-use params::{LibraryItemParams, PlayLibraryItemParams};
+use audio_stream::{ResumableStream, StreamOptions, StreamProgress, StreamStorageProvider};
+use auth::AuthProvider;
+use bytes::Bytes;
+use credentials::StoredAccount;
+use download::Download;
+use endpoints::Endpoints;
+use errors::{
+    APIError, AuthError, FusedError, RequestError, RequestInfo, ResponseError, ServerError,
+    UrlError,
+};
+use futures_util::stream::BoxStream;
+use futures_util::{future, stream, Stream, StreamExt};
+use http_cache::ResponseCache;
+use json_stream::PageEvent;
+use params::{
+    BatchItemsParams, BatchMediaProgressParams, BatchQuickMatchOptions, BatchQuickMatchParams,
+    BatchUpdateParams, BookSearchParams, BookmarkParams, ChaptersParams, CoverParams,
+    CoverPathParams, CoverUrlParams, CreateCollectionParams, CreatePlaylistParams,
+    CreatePodcastParams, FeedEntity, Filter, IdParams, LibraryItemParams, LocalSessionParams,
+    LocalSessionsParams, MatchAuthorParams, MatchOptions, MatchParams, MediaProgressParams,
+    MetadataProvider, OpenFeedBody, OpenFeedParams, OpmlCreateParams, OpmlParseParams,
+    PlayLibraryItemParams, PlaylistItemsParams, PodcastFeedParams, Sort, SyncSessionParams,
+    TrackOrder, TracksParams, UpdateAuthorParams, UpdateCollectionParams, UpdateEpisodeParams,
+    UpdateMediaParams, UpdatePlaylistParams, UploadFile, UploadParams, UploadProgress,
+    UploadSource,
+};
+use rate_limit::{RateLimited, RateLimiter};
 use reqwest::header::{HeaderMap, HeaderValue};
-pub use reqwest::{self, StatusCode, Url};
+use reqwest::multipart::{Form, Part};
+pub use reqwest::{self, Method, StatusCode, Url};
+use retry::RetryPolicy;
 use schema::{
-    AuthRequest, AuthResponse, Id, Libraries, Library, LibraryItem, LibraryItemMinified,
-    LibraryWithFilters, PaginatedResponse, PlaybackSessionExtended, UserData,
+    AudioBookmark, AudnexusChapters, AuthRequest, AuthResponse, Author, AuthorExpanded,
+    AuthorUpdate, BatchUpdate, BookMatch, Chapter, ChapterSearchResult, ChaptersUpdate, Collection,
+    CollectionExpanded, Collections, CoverSearchResult, CoverUpdate, EpisodeDownload,
+    EpisodeDownloadQueue, EpisodeDownloads, EpisodesMatch, Feed, FeedResponse, Feeds, Id, Image,
+    Libraries, Library, LibraryItem, LibraryItemMinified, LibraryItemUpdate, LibraryItemsBatch,
+    LibraryMedia, LibrarySearchResult, LibrarySeries, LibraryStats, LibraryWithFilters,
+    LocalSessionResult, LocalSessionResults, MediaProgress, Narrator, Narrators, NewEpisodes,
+    OpmlFeed, OpmlFeeds, PaginatedResponse, Permission, Ping, PlaybackSession,
+    PlaybackSessionExtended, Playlist, PlaylistExpanded, PlaylistItem, Playlists, PodcastEpisode,
+    PodcastFeed, PodcastFeedEpisode, PodcastFeedResponse, PodcastSearchResult, Series,
+    SeriesDetails, ServerStatus, Shelf, UserData, UserPermissions, YearStats,
 };
+use serde::de::DeserializeOwned;
 pub use stream_download;
-use stream_download::{
-    http::HttpStream,
-    storage::{temp::TempStorageProvider, StorageProvider},
-    Settings, StreamDownload,
-};
+use stream_download::{source::SourceStream, Settings, StreamDownload, StreamPhase};
+use tokio::sync::Semaphore;
+use tokio_util::io::ReaderStream;
+use tokio_util::sync::CancellationToken;
+use transport::Transport;
 
 static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 
+#[derive(Clone, Debug)]
 pub struct ClientConfig {
-    pub root_url: Url,
+    /// Server address, possibly with subpath
+    pub endpoints: Endpoints,
+    /// Client used for requests. If set, http settings below are ignored.
+    pub http_client: Option<reqwest::Client>,
+    pub user_agent: Option<String>,
+    /// Headers added to every request
+    pub default_headers: HeaderMap,
+    pub proxy: Option<reqwest::Proxy>,
+    /// Additional trusted root certificates, e.g. for self-signed server
+    pub root_certificates: Vec<reqwest::Certificate>,
+    pub accept_invalid_certs: bool,
+    /// Retrying of transient failures. Never retries by default.
+    pub retry: RetryPolicy,
+    /// Time limit for API requests, from connecting until response body is read.
+    ///
+    /// Does not apply to file downloads and audio streams.
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    /// Cache of GET responses. Disabled by default.
+    pub cache: Option<Arc<ResponseCache>>,
+    /// Transport used instead of http client, e.g. `MockTransport` in tests.
+    pub transport: Option<Arc<dyn Transport>>,
+    /// Limit of request rate, shared by all clients using this config. Disabled by default.
+    ///
+    /// Applies to every request sent through transport, including retries and
+    /// authentication, but not to file downloads and audio streams.
+    pub rate_limit: Option<Arc<RateLimiter>>,
+}
+
+impl ClientConfig {
+    /// Config of server at `root_url`, which must be http or https url.
+    pub fn new(root_url: Url) -> Result<Self, UrlError> {
+        Ok(Self {
+            endpoints: Endpoints::new(root_url)?,
+            http_client: None,
+            user_agent: None,
+            default_headers: HeaderMap::new(),
+            proxy: None,
+            root_certificates: Vec::new(),
+            accept_invalid_certs: false,
+            retry: RetryPolicy::never(),
+            timeout: None,
+            connect_timeout: None,
+            cache: None,
+            transport: None,
+            rate_limit: None,
+        })
+    }
+
+    pub fn root_url(&self) -> &Url {
+        self.endpoints.root()
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Transport executing requests, which is http client unless configured otherwise.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(self.http_client()),
+        };
+        match &self.rate_limit {
+            Some(limiter) => Arc::new(RateLimited {
+                inner: transport,
+                limiter: limiter.clone(),
+            }),
+            None => transport,
+        }
+    }
+
+    pub fn with_rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limit = Some(Arc::new(limiter));
+        self
+    }
+
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn with_header(mut self, name: reqwest::header::HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn with_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    pub fn with_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Client builder with configured http settings applied.
+    pub fn http_client_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder()
+            .default_headers(self.default_headers.clone())
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        builder
+    }
+
+    /// Client used for requests. Clients without custom settings share connection pool.
+    ///
+    /// # Panics
+    ///
+    /// If TLS backend cannot be initialized.
+    pub fn http_client(&self) -> reqwest::Client {
+        if let Some(client) = &self.http_client {
+            return client.clone();
+        }
+        let is_default = self.user_agent.is_none()
+            && self.default_headers.is_empty()
+            && self.proxy.is_none()
+            && self.root_certificates.is_empty()
+            && !self.accept_invalid_certs
+            && self.connect_timeout.is_none();
+        if is_default {
+            return CLIENT.get_or_init(reqwest::Client::new).clone();
+        }
+        self.http_client_builder()
+            .build()
+            .expect("Failed to initialize http client")
+    }
 }
 
+/// Client acting on behalf of user.
+///
+/// Clones share token and opened sessions.
+#[derive(Clone)]
 pub struct UserClient {
     client: reqwest::Client,
-    token: String,
+    transport: Arc<dyn Transport>,
+    token: Arc<RwLock<String>>,
+    credentials: Arc<RwLock<Option<Credentials>>>,
+    /// Password kept once server issued refresh token, used if refresh token is rejected
+    fallback: Arc<RwLock<Option<Credentials>>>,
+    /// Held while token is renewed, so concurrent rejected requests renew it once
+    reauthentication: Arc<tokio::sync::Mutex<()>>,
+    /// Permissions of logged in user, once known from login or `me`
+    permissions: Arc<RwLock<Option<UserPermissions>>>,
+    /// Store tokens are saved to once renewed
+    stored: Option<Arc<StoredAccount>>,
     config: ClientConfig,
+    /// Playback sessions opened by this client and not closed yet
+    sessions: Arc<Mutex<HashSet<String>>>,
+    /// Overrides `config.timeout`
+    timeout: Option<Duration>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl ClientConfig {
     fn login_url(&self) -> Url {
-        self.root_url.join("login").unwrap()
+        self.endpoints.url(["login"])
+    }
+
+    /// `path` is one of `status`, `ping`, `healthcheck` or `logout`
+    fn server_url(&self, path: &str) -> Url {
+        self.endpoints.url([path])
+    }
+
+    fn refresh_url(&self) -> Url {
+        self.endpoints.url(["auth", "refresh"])
     }
 
     fn me_url(&self) -> Url {
-        self.root_url.join("api/me").unwrap()
+        self.endpoints.api(["me"])
+    }
+
+    fn me_year_stats_url(&self, year: i32) -> Url {
+        self.endpoints
+            .api(["me", "stats", "year", &year.to_string()])
+    }
+
+    fn media_progress_url(&self, id: &str, episode_id: Option<&str>) -> Url {
+        self.endpoints
+            .api(["me", "progress", id].into_iter().chain(episode_id))
+    }
+
+    fn media_progress_batch_url(&self) -> Url {
+        self.endpoints.api(["me", "progress", "batch", "update"])
+    }
+
+    fn bookmark_url(&self, id: &str, time: Option<f64>) -> Url {
+        let time = time.map(|time| time.to_string());
+        self.endpoints.api(
+            ["me", "item", id, "bookmark"]
+                .into_iter()
+                .chain(time.as_deref()),
+        )
     }
 
     fn libraries_url(&self) -> Url {
-        self.root_url.join("api/libraries").unwrap()
+        self.endpoints.api(["libraries"])
     }
 
     fn library_url(&self, id: &str) -> Url {
-        Url::parse(&format!("{root}/api/libraries/{id}", root = self.root_url)).unwrap()
+        self.endpoints.api(["libraries", id])
+    }
+
+    /// Url of `/api/libraries/<ID>/<path>`
+    fn library_sub_url(&self, id: &str, path: &str) -> Url {
+        self.endpoints.api(["libraries", id, path])
     }
 
     fn library_items_url(&self, id: &str) -> Url {
-        Url::parse(&format!(
-            "{root}/api/libraries/{id}/items",
-            root = self.root_url
-        ))
-        .unwrap()
+        self.library_sub_url(id, "items")
+    }
+
+    fn library_search_url(&self, id: &str) -> Url {
+        self.library_sub_url(id, "search")
+    }
+
+    fn library_series_url(&self, id: &str, series_id: Option<&str>) -> Url {
+        self.endpoints
+            .api(["libraries", id, "series"].into_iter().chain(series_id))
+    }
+
+    fn library_collections_url(&self, id: &str) -> Url {
+        self.library_sub_url(id, "collections")
+    }
+
+    fn collections_url(&self) -> Url {
+        self.endpoints.api(["collections"])
+    }
+
+    fn collection_url(&self, id: &str) -> Url {
+        self.endpoints.api(["collections", id])
+    }
+
+    fn collection_book_url(&self, id: &str, book_id: Option<&str>) -> Url {
+        self.endpoints
+            .api(["collections", id, "book"].into_iter().chain(book_id))
+    }
+
+    fn library_playlists_url(&self, id: &str) -> Url {
+        self.library_sub_url(id, "playlists")
+    }
+
+    fn playlists_url(&self) -> Url {
+        self.endpoints.api(["playlists"])
+    }
+
+    fn playlist_url(&self, id: &str) -> Url {
+        self.endpoints.api(["playlists", id])
+    }
+
+    fn playlist_item_url(&self, id: &str, item: Option<&PlaylistItem>) -> Url {
+        let item_id = item.map(|item| item.library_item_id.as_str());
+        let episode_id = item.and_then(|item| item.episode_id.as_ref().map(Id::as_str));
+        self.endpoints.api(
+            ["playlists", id, "item"]
+                .into_iter()
+                .chain(item_id)
+                .chain(episode_id),
+        )
+    }
+
+    /// `action` is either `add` or `remove`
+    fn playlist_batch_url(&self, id: &str, action: &str) -> Url {
+        self.endpoints.api(["playlists", id, "batch", action])
+    }
+
+    fn playlist_from_collection_url(&self, collection_id: &str) -> Url {
+        self.endpoints
+            .api(["playlists", "collection", collection_id])
+    }
+
+    fn feeds_url(&self) -> Url {
+        self.endpoints.api(["feeds"])
+    }
+
+    fn feed_open_url(&self, entity: &FeedEntity) -> Url {
+        self.endpoints
+            .api(["feeds", entity.type_str(), entity.id_str(), "open"])
+    }
+
+    fn feed_close_url(&self, id: &str) -> Url {
+        self.endpoints.api(["feeds", id, "close"])
+    }
+
+    fn upload_url(&self) -> Url {
+        self.endpoints.api(["upload"])
     }
 
     fn library_item_url(&self, id: &str) -> Url {
-        Url::parse(&format!("{root}/api/items/{id}", root = self.root_url)).unwrap()
+        self.endpoints.api(["items", id])
+    }
+
+    /// `action` is one of `get`, `update`, `delete` or `quickmatch`
+    fn items_batch_url(&self, action: &str) -> Url {
+        self.endpoints.api(["items", "batch", action])
+    }
+
+    /// Url of `/api/items/<ID>/<path>`
+    fn library_item_sub_url(&self, id: &str, path: &[&str]) -> Url {
+        self.endpoints.api(["items", id].iter().chain(path))
+    }
+
+    fn library_item_cover_url(&self, id: &str) -> Url {
+        self.library_item_sub_url(id, &["cover"])
+    }
+
+    fn library_item_play_url(&self, id: &str, episode_id: Option<&str>) -> Url {
+        self.endpoints
+            .api(["items", id, "play"].into_iter().chain(episode_id))
+    }
+
+    fn podcast_search_url(&self) -> Url {
+        self.endpoints.api(["search", "podcast"])
+    }
+
+    /// `kind` is one of `books`, `covers` or `chapters`
+    fn metadata_search_url(&self, kind: &str) -> Url {
+        self.endpoints.api(["search", kind])
+    }
+
+    fn podcast_feed_url(&self) -> Url {
+        self.endpoints.api(["podcasts", "feed"])
+    }
+
+    fn podcasts_url(&self, path: &[&str]) -> Url {
+        self.endpoints.api(["podcasts"].iter().chain(path))
+    }
+
+    /// `action` is one of `checknew`, `downloads`, `download-episodes` or `match-episodes`
+    fn podcast_url(&self, id: &str, action: &str) -> Url {
+        self.endpoints.api(["podcasts", id, action])
+    }
+
+    fn podcast_episode_url(&self, id: &str, episode_id: &str) -> Url {
+        self.endpoints.api(["podcasts", id, "episode", episode_id])
+    }
+
+    fn library_episode_downloads_url(&self, id: &str) -> Url {
+        self.library_sub_url(id, "episode-downloads")
+    }
+
+    /// `action` is either `image` or `match`, or none for author itself
+    fn author_url(&self, id: &str, action: Option<&str>) -> Url {
+        self.endpoints
+            .api(["authors", id].into_iter().chain(action))
+    }
+
+    /// `action` is either `sync` or `close`
+    fn session_url(&self, id: &str, action: &str) -> Url {
+        self.endpoints.api(["session", id, action])
     }
 
-    fn library_item_play_url(&self, id: &str) -> Url {
-        Url::parse(&format!("{root}/api/items/{id}/play", root = self.root_url)).unwrap()
+    fn local_session_url(&self, all: bool) -> Url {
+        let action = if all { "local-all" } else { "local" };
+        self.endpoints.api(["session", action])
     }
 }
 
+/// Means to obtain new token then current one is rejected.
+#[derive(Clone, Debug)]
+pub enum Credentials {
+    Password {
+        username: String,
+        password: String,
+    },
+    /// Refresh token, supported by server since 2.26
+    RefreshToken(String),
+    Provider(Arc<dyn AuthProvider>),
+}
+
 impl UserClient {
     pub fn from_token(config: ClientConfig, token: String) -> Self {
         Self {
-            client: CLIENT.get_or_init(reqwest::Client::new).clone(),
+            client: config.http_client(),
+            transport: config.transport(),
             config,
-            token,
+            token: Arc::new(RwLock::new(token)),
+            credentials: Default::default(),
+            fallback: Default::default(),
+            reauthentication: Default::default(),
+            permissions: Default::default(),
+            stored: None,
+            sessions: Default::default(),
+            timeout: None,
+            cancellation: None,
+        }
+    }
+
+    /// Re-authenticate with `credentials` and retry once then request is rejected with 401.
+    pub fn with_credentials(self, credentials: Credentials) -> Self {
+        *self.credentials.write().unwrap() = Some(credentials);
+        self
+    }
+
+    /// Check requests against `permissions` before sending them, for clients created
+    /// from token. Otherwise permissions are known after login or `me`.
+    pub fn with_permissions(self, permissions: UserPermissions) -> Self {
+        *self.permissions.write().unwrap() = Some(permissions);
+        self
+    }
+
+    /// Permissions of logged in user, if known.
+    pub fn permissions(&self) -> Option<UserPermissions> {
+        self.permissions.read().unwrap().clone()
+    }
+
+    /// Fail with `RequestError::PermissionDenied` if user is known to lack `permission`.
+    fn require(&self, permission: Permission) -> Result<(), FusedError<RequestError>> {
+        match &*self.permissions.read().unwrap() {
+            Some(permissions) if !permissions.allows(permission) => Err(FusedError::DomainError(
+                RequestError::PermissionDenied(permission),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Client sharing state with this one, whose requests are limited by `timeout`
+    /// instead of one from config.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self.clone()
         }
     }
 
+    /// Client sharing state with this one, whose requests fail with `APIError::Cancelled`
+    /// once `token` is cancelled. Dropping returned futures cancels requests as well.
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..self.clone()
+        }
+    }
+
+    /// Start request without authorization.
+    fn anonymous(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        let request_builder = self.client.request(method, url);
+        match self.timeout.or(self.config.timeout) {
+            Some(timeout) => request_builder.timeout(timeout),
+            None => request_builder,
+        }
+    }
+
+    /// Start authorized request with JSON content type.
+    fn request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        self.anonymous(method, url)
+            .bearer_auth(self.token())
+            .header("Content-Type", "application/json")
+    }
+
+    /// Current access token.
+    pub fn token(&self) -> String {
+        self.token.read().unwrap().clone()
+    }
+
     pub fn client(&self) -> reqwest::Client {
         self.client.clone()
     }
 
-    pub fn build_abs_url(&self, url: &str) -> Url {
-        self.config.root_url.join(url).unwrap()
+    /// Resolve url given by server, such as `AudioTrack::content_url`.
+    pub fn build_abs_url(&self, url: &str) -> Result<Url, UrlError> {
+        self.config.endpoints.resolve(url)
     }
 
     pub async fn auth(
@@ -88,175 +603,2362 @@ impl UserClient {
         username: String,
         password: String,
     ) -> Result<Self, FusedError<AuthError>> {
-        let client = reqwest::Client::new();
+        let client = config.http_client();
+        let transport = config.transport();
         let url = config.login_url();
 
-        let body = serde_json::to_string(&AuthRequest { username, password }).unwrap();
-        let response: AuthResponse = Self::send(
-            client
-                .post(url)
-                .header("Content-Type", "application/json")
-                .body(body),
-        )
-        .await
-        .map_err(|error| match error {
-            FusedError::APIError(error) => FusedError::APIError(error),
-            FusedError::DomainError(error) if error.status == StatusCode::UNAUTHORIZED => {
-                FusedError::DomainError(AuthError::InvalidCredentials)
-            }
-            _ => FusedError::APIError(error.to_api_error()),
-        })?;
+        let body = serde_json::to_string(&AuthRequest {
+            username: username.clone(),
+            password: password.clone(),
+        })
+        .unwrap();
+        let mut request_builder = client
+            .post(url)
+            .header("Content-Type", "application/json")
+            // Ask servers supporting refresh tokens to return them
+            .header("x-return-tokens", "true")
+            .body(body);
+        if let Some(timeout) = config.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+        let response: AuthResponse = Self::send_anonymous(&*transport, request_builder)
+            .await
+            .map_err(|error| match error {
+                FusedError::APIError(error) => FusedError::APIError(error),
+                FusedError::DomainError(error) if error.status == StatusCode::UNAUTHORIZED => {
+                    FusedError::DomainError(AuthError::InvalidCredentials)
+                }
+                _ => FusedError::APIError(error.to_api_error()),
+            })?;
 
+        let password = Credentials::Password { username, password };
+        let (credentials, fallback) = match response.user.refresh_token {
+            Some(refresh_token) => (Credentials::RefreshToken(refresh_token), Some(password)),
+            None => (password, None),
+        };
+        let token = response.user.access_token.unwrap_or(response.user.token);
         Ok(Self {
-            client: reqwest::Client::new(),
+            client,
+            transport,
             config,
-            token: response.user.token,
+            token: Arc::new(RwLock::new(token)),
+            credentials: Arc::new(RwLock::new(Some(credentials))),
+            fallback: Arc::new(RwLock::new(fallback)),
+            reauthentication: Default::default(),
+            permissions: Arc::new(RwLock::new(Some(response.user.permissions))),
+            stored: None,
+            sessions: Default::default(),
+            timeout: None,
+            cancellation: None,
         })
     }
 
-    pub async fn me(&self) -> Result<UserData, APIError> {
-        let request_builder = self
-            .client
-            .get(self.config.me_url())
-            .bearer_auth(self.token.clone())
-            .header("Content-Type", "application/json");
+    /// Obtain new token using stored credentials, falling back to password if refresh
+    /// token is rejected.
+    pub async fn reauthenticate(&self) -> Result<(), FusedError<AuthError>> {
+        let _guard = self.reauthentication.lock().await;
+        self.renew_token().await
+    }
+
+    /// Renew `rejected` token, unless it was renewed already by concurrent request.
+    async fn reauthenticate_rejected(&self, rejected: &str) -> Result<(), FusedError<AuthError>> {
+        let _guard = self.reauthentication.lock().await;
+        if self.token() != rejected {
+            return Ok(());
+        }
+        self.renew_token().await
+    }
+
+    async fn renew_token(&self) -> Result<(), FusedError<AuthError>> {
+        let credentials = self.credentials.read().unwrap().clone();
+        match self.authenticate_with(credentials).await {
+            Err(FusedError::DomainError(AuthError::InvalidCredentials)) => {
+                let fallback = self.fallback.read().unwrap().clone();
+                match fallback {
+                    Some(fallback) => self.authenticate_with(Some(fallback)).await,
+                    None => Err(FusedError::DomainError(AuthError::InvalidCredentials)),
+                }
+            }
+            result => result,
+        }
+    }
+
+    async fn authenticate_with(
+        &self,
+        credentials: Option<Credentials>,
+    ) -> Result<(), FusedError<AuthError>> {
+        let request_builder = match credentials {
+            Some(Credentials::Password { username, password }) => {
+                let body = serde_json::to_string(&AuthRequest { username, password }).unwrap();
+                self.anonymous(Method::POST, self.config.login_url())
+                    .header("Content-Type", "application/json")
+                    .header("x-return-tokens", "true")
+                    .body(body)
+            }
+            Some(Credentials::RefreshToken(refresh_token)) => self
+                .anonymous(Method::POST, self.config.refresh_url())
+                .header("x-refresh-token", refresh_token),
+            Some(Credentials::Provider(provider)) => {
+                let token = provider.authenticate(&self.config).await?;
+                *self.token.write().unwrap() = token;
+                let _ = self.save_token();
+                return Ok(());
+            }
+            None => return Err(FusedError::DomainError(AuthError::InvalidToken)),
+        };
 
-        let response = Self::send(request_builder)
+        let response: AuthResponse = Self::send_anonymous(&*self.transport, request_builder)
             .await
-            .map_err(FusedError::to_api_error)?;
+            .map_err(|error| match error {
+                FusedError::DomainError(error) if error.status == StatusCode::UNAUTHORIZED => {
+                    FusedError::DomainError(AuthError::InvalidCredentials)
+                }
+                error => FusedError::APIError(error.to_api_error()),
+            })?;
 
-        Ok(response)
+        if let Some(refresh_token) = response.user.refresh_token {
+            let previous = self
+                .credentials
+                .write()
+                .unwrap()
+                .replace(Credentials::RefreshToken(refresh_token));
+            if let Some(Credentials::Password { .. }) = previous {
+                *self.fallback.write().unwrap() = previous;
+            }
+        }
+        *self.permissions.write().unwrap() = Some(response.user.permissions);
+        let token = response.user.access_token.unwrap_or(response.user.token);
+        *self.token.write().unwrap() = token;
+        // Failing to save is not fatal, as the new token is still usable in this run
+        let _ = self.save_token();
+        Ok(())
     }
 
-    pub async fn libraries(&self) -> Result<Vec<Library>, APIError> {
-        let request_builder = self
-            .client
-            .get(self.config.libraries_url())
-            .bearer_auth(self.token.clone())
-            .header("Content-Type", "application/json");
+    /// Close playback sessions opened by this client and invalidate its token on server.
+    ///
+    /// Clones sharing the token can't be used afterwards either.
+    pub async fn logout(self) -> Result<(), FusedError<RequestError>> {
+        let sessions: Vec<_> = self.sessions.lock().unwrap().iter().cloned().collect();
+        for id in sessions {
+            self.close_session(&Id::new(id)).await?;
+        }
 
-        let result: Libraries = Self::send(request_builder)
+        let mut request_builder = self.request(Method::POST, self.config.server_url("logout"));
+        let credentials = self.credentials.write().unwrap().take();
+        self.fallback.write().unwrap().take();
+        if let Some(Credentials::RefreshToken(refresh_token)) = credentials {
+            request_builder = request_builder.header("x-refresh-token", refresh_token);
+        }
+        self.send_raw(request_builder)
             .await
-            .map_err(FusedError::to_api_error)?;
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
 
-        Ok(result.libraries)
+    /// Server version and initialization state. Does not require authorization.
+    pub async fn server_status(&self) -> Result<ServerStatus, FusedError<RequestError>> {
+        let request_builder = self.anonymous(Method::GET, self.config.server_url("status"));
+
+        Self::send_anonymous::<ServerStatus>(&*self.transport, request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
     }
 
-    pub async fn library(&self, id: &Id<Library>) -> Result<LibraryWithFilters, APIError> {
-        let request_builder = self
-            .client
-            .get(self.config.library_url(id.as_str()))
-            .query(&[("include", "filterdata")])
-            .bearer_auth(self.token.clone())
-            .header("Content-Type", "application/json");
+    /// Check that server is reachable and responds.
+    pub async fn ping(&self) -> Result<bool, FusedError<RequestError>> {
+        let request_builder = self.anonymous(Method::GET, self.config.server_url("ping"));
 
-        Self::send::<LibraryWithFilters>(request_builder)
+        let result = Self::send_anonymous::<Ping>(&*self.transport, request_builder)
             .await
-            .map_err(FusedError::to_api_error)
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.success)
     }
 
-    pub async fn library_items(
-        &self,
-        id: &Id<Library>,
-        params: LibraryItemParams,
-    ) -> Result<Vec<LibraryItemMinified>, APIError> {
-        let request_builder = self
-            .client
-            .get(self.config.library_items_url(id.as_str()))
-            .query(&params.build_query())
-            .bearer_auth(self.token.clone())
-            .header("Content-Type", "application/json");
+    /// Check that server is healthy, including its database.
+    pub async fn healthcheck(&self) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self.anonymous(Method::GET, self.config.server_url("healthcheck"));
 
-        let result = Self::send::<PaginatedResponse<LibraryItemMinified>>(request_builder)
+        let request = Self::build(request_builder)?;
+        let info = RequestInfo::of(&request);
+        let response = self
+            .transport
+            .execute(request)
             .await
-            .map_err(FusedError::to_api_error)?;
-        Ok(result.results)
+            .map_err(|error| APIError::network(&info, error))?;
+        Self::check_status(&info, response)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
     }
 
-    pub async fn library_item(&self, id: &Id<LibraryItem>) -> Result<LibraryItem, APIError> {
-        let request_builder = self
-            .client
-            .get(self.config.library_item_url(id.as_str()))
-            .query(&[("include", "authors")])
-            .bearer_auth(self.token.clone())
-            .header("Content-Type", "application/json");
+    pub async fn me(&self) -> Result<UserData, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.me_url());
 
-        Self::send::<LibraryItem>(request_builder)
+        let response: UserData = self
+            .send(request_builder)
             .await
-            .map_err(FusedError::to_api_error)
+            .map_err(FusedError::to_request_error)?;
+
+        *self.permissions.write().unwrap() = Some(response.permissions.clone());
+        Ok(response)
     }
 
-    /// Receive data neccesary to play media item.
-    ///
-    /// Note: despite name `play` suggesting that it is statefull, it does not update user media progress. That sould be done manually by using `library_item/`
-    pub async fn library_item_play(
+    /// Listening summary of user for `year`, such as top authors and books finished.
+    pub async fn year_stats(&self, year: i32) -> Result<YearStats, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.me_year_stats_url(year));
+
+        self.send::<YearStats>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn libraries(&self) -> Result<Vec<Library>, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.libraries_url());
+
+        let result: Libraries = self
+            .send(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+
+        Ok(result.libraries)
+    }
+
+    pub async fn library(
         &self,
-        id: &Id<LibraryItem>,
-        params: &PlayLibraryItemParams,
-    ) -> Result<PlaybackSessionExtended, APIError> {
-        let body = serde_json::to_string(params).unwrap();
+        id: &Id<Library>,
+    ) -> Result<LibraryWithFilters, FusedError<RequestError>> {
         let request_builder = self
-            .client
-            .post(self.config.library_item_play_url(id.as_str()))
-            .query(&[("include", "authors")])
-            .bearer_auth(self.token.clone())
-            .body(body)
-            .header("Content-Type", "application/json");
+            .request(Method::GET, self.config.library_url(id.as_str()))
+            .query(&[("include", "filterdata")]);
 
-        Self::send::<PlaybackSessionExtended>(request_builder)
+        self.send::<LibraryWithFilters>(request_builder)
             .await
-            .map_err(FusedError::to_api_error)
+            .map_err(FusedError::to_request_error)
     }
 
-    async fn send<ResponseSchema>(
-        request_builder: reqwest::RequestBuilder,
-    ) -> Result<ResponseSchema, FusedError<ResponseError>>
-    where
-        ResponseSchema: for<'a> serde::Deserialize<'a>,
-    {
-        let response = request_builder
-            .send()
+    /// Narrators of books in library, with number of their books. Use
+    /// `Filter::Narrators` with narrator name to list their books.
+    pub async fn library_narrators(
+        &self,
+        id: &Id<Library>,
+    ) -> Result<Vec<Narrator>, FusedError<RequestError>> {
+        let url = self.config.library_sub_url(id.as_str(), "narrators");
+        let request_builder = self.request(Method::GET, url);
+
+        let result = self
+            .send::<Narrators>(request_builder)
             .await
-            .map_err(APIError::NetworkError)?;
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.narrators)
+    }
 
-        let status = response.status();
-        if response.status().is_success() {
-            let body = response.text().await.map_err(APIError::NetworkError)?;
-            let json_deserializer = &mut serde_json::Deserializer::from_str(&body);
-            let result = serde_path_to_error::deserialize(json_deserializer);
-            match result {
-                Ok(result) => Ok(result),
-                Err(err) => Err(FusedError::APIError(APIError::InvalidResponseSchema(err))),
-            }
-        } else {
-            Err(FusedError::DomainError(ResponseError {
-                status,
-                response: response.text().await.map_err(APIError::NetworkError)?,
-            }))
-        }
+    /// Totals and top items of library.
+    pub async fn library_stats(
+        &self,
+        id: &Id<Library>,
+    ) -> Result<LibraryStats, FusedError<RequestError>> {
+        let url = self.config.library_sub_url(id.as_str(), "stats");
+        let request_builder = self.request(Method::GET, url);
+
+        self.send::<LibraryStats>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
     }
 
-    pub async fn audiofile_stream(
+    /// Home page shelves of library, personalized for user. Each shelf holds up to `limit`
+    /// entities. Use `home_screen` to combine them with user progress.
+    pub async fn personalized(
         &self,
-        url: &str,
-    ) -> Result<StreamDownload<TempStorageProvider>, APIError> {
-        let mut headers = HeaderMap::new();
-        let header: HeaderValue = format!("Bearer {}", self.token).parse().unwrap();
-        headers.insert("Authorization", header);
-        let client = reqwest::Client::builder()
-            .connect_timeout(Duration::from_secs(5))
-            .default_headers(headers)
-            .build()
-            .unwrap();
+        id: &Id<Library>,
+        limit: usize,
+    ) -> Result<Vec<Shelf>, FusedError<RequestError>> {
+        let url = self.config.library_sub_url(id.as_str(), "personalized");
+        let request_builder = self.request(Method::GET, url).query(&[
+            ("limit", limit.to_string()),
+            ("include", "rssfeed,numEpisodesIncomplete".to_string()),
+        ]);
 
-        let stream = HttpStream::new(client, self.build_abs_url(url))
+        self.send::<Vec<Shelf>>(request_builder)
             .await
-            .map_err(|e| APIError::UnknownError(e.into()))?;
+            .map_err(FusedError::to_request_error)
+    }
 
-        let download =
-            StreamDownload::from_stream(stream, TempStorageProvider::new(), Settings::default())
-                .await
-                .map_err(|e| APIError::UnknownError(e.into()))?;
+    /// Single page of library items. Use `library_items_stream` to get all of them.
+    pub async fn library_items(
+        &self,
+        id: &Id<Library>,
+        params: LibraryItemParams,
+    ) -> Result<Vec<LibraryItemMinified>, FusedError<RequestError>> {
+        Ok(self.library_items_page(id, params).await?.results)
+    }
+
+    /// Single page of library items, along with pagination data.
+    pub async fn library_items_page(
+        &self,
+        id: &Id<Library>,
+        params: LibraryItemParams,
+    ) -> Result<PaginatedResponse<LibraryItemMinified>, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.library_items_url(id.as_str()))
+            .query(&params.build_query());
+
+        self.send::<PaginatedResponse<LibraryItemMinified>>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// All library items, starting from `params.page`, fetched page by page.
+    ///
+    /// If `params.limit` is zero, everything is fetched with single request.
+    /// Items are parsed as response arrives, so even huge pages are not held in memory.
+    /// Stream ends after first error.
+    pub fn library_items_stream(
+        &self,
+        id: &Id<Library>,
+        params: LibraryItemParams,
+    ) -> impl Stream<Item = Result<LibraryItemMinified, FusedError<RequestError>>> {
+        self.paginated_stream(self.config.library_items_url(id.as_str()), params)
+    }
+
+    /// Page of series in library, with their books.
+    ///
+    /// `params` are interpreted same way as for `library_items`, with `sort` being
+    /// `Sort::Other` with one of series fields.
+    pub async fn library_series(
+        &self,
+        id: &Id<Library>,
+        params: LibraryItemParams,
+    ) -> Result<PaginatedResponse<LibrarySeries>, FusedError<RequestError>> {
+        let request_builder = self
+            .request(
+                Method::GET,
+                self.config.library_series_url(id.as_str(), None),
+            )
+            .query(&params.build_query());
+
+        self.send::<PaginatedResponse<LibrarySeries>>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// All series in library, fetched page by page same way as `library_items_stream`.
+    pub fn library_series_stream(
+        &self,
+        id: &Id<Library>,
+        params: LibraryItemParams,
+    ) -> impl Stream<Item = Result<LibrarySeries, FusedError<RequestError>>> {
+        self.paginated_stream(self.config.library_series_url(id.as_str(), None), params)
+    }
+
+    /// Series along with progress of user through it.
+    pub async fn series(
+        &self,
+        library_id: &Id<Library>,
+        id: &Id<Series>,
+    ) -> Result<SeriesDetails, FusedError<RequestError>> {
+        let url = self
+            .config
+            .library_series_url(library_id.as_str(), Some(id.as_str()));
+        let request_builder = self
+            .request(Method::GET, url)
+            .query(&[("include", "progress")]);
+
+        self.send::<SeriesDetails>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Book following given one in its series, skipping ones user already finished.
+    /// First series of book is used, if it belongs to several. `None` if item is not
+    /// book of series, or it has no unfinished successors.
+    pub async fn next_in_series(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<Option<LibraryItemMinified>, FusedError<RequestError>> {
+        let item = self.library_item(id).await?;
+        let LibraryMedia::Book { metadata, .. } = &item.media else {
+            return Ok(None);
+        };
+        let Some(series) = metadata.series.first() else {
+            return Ok(None);
+        };
+        let params = LibraryItemParams {
+            sort: Some(Sort::Sequence),
+            filter: Some(Filter::Series(series.id.clone())),
+            ..Default::default()
+        };
+        let (books, details) = tokio::try_join!(
+            self.library_items(&item.library_id, params),
+            self.series(&item.library_id, &series.id),
+        )?;
+        let finished = details
+            .progress
+            .map(|progress| progress.library_item_ids_finished)
+            .unwrap_or_default();
+        let next = books
+            .into_iter()
+            .skip_while(|book| &book.id != id)
+            .skip(1)
+            .find(|book| !finished.contains(&book.id));
+        Ok(next)
+    }
+
+    /// Search library for books, podcasts, authors, series, tags, genres and narrators matching `query`.
+    pub async fn search_library(
+        &self,
+        id: &Id<Library>,
+        query: &str,
+        limit: usize,
+    ) -> Result<LibrarySearchResult, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.library_search_url(id.as_str()))
+            .query(&[("q", query.to_string()), ("limit", limit.to_string())]);
+
+        self.send::<LibrarySearchResult>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn library_item(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<LibraryItem, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.library_item_url(id.as_str()))
+            .query(&[("include", "authors")]);
+
+        self.send::<LibraryItem>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Several library items, fetched with single request. Ids not found on server are
+    /// skipped.
+    pub async fn library_items_batch(
+        &self,
+        ids: &[Id<LibraryItem>],
+    ) -> Result<Vec<LibraryItem>, FusedError<RequestError>> {
+        let body = serde_json::to_string(&BatchItemsParams {
+            library_item_ids: ids,
+        })
+        .unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.items_batch_url("get"))
+            .body(body);
+
+        let result = self
+            .send::<LibraryItemsBatch>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.library_items)
+    }
+
+    /// Fetch items one request per item, with at most `concurrency` requests at once.
+    /// Results are in order of `ids`.
+    ///
+    /// Unlike `library_items_batch`, failure of single item doesn't fail others.
+    pub async fn library_items_expanded(
+        &self,
+        ids: &[Id<LibraryItem>],
+        concurrency: usize,
+    ) -> Vec<Result<LibraryItem, FusedError<RequestError>>> {
+        self.batch(ids.iter().cloned(), concurrency, |client, id| async move {
+            client.library_item(&id).await
+        })
+        .await
+    }
+
+    /// Run `request` for every input, with at most `concurrency` of them running at
+    /// once. Results are in order of `inputs`.
+    pub async fn batch<I, F, Fut>(
+        &self,
+        inputs: impl IntoIterator<Item = I>,
+        concurrency: usize,
+        request: F,
+    ) -> Vec<Fut::Output>
+    where
+        F: Fn(UserClient, I) -> Fut,
+        Fut: Future,
+    {
+        let semaphore = Semaphore::new(concurrency.max(1));
+        let requests = inputs.into_iter().map(|input| {
+            let request = request(self.clone(), input);
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("Semaphore is never closed");
+                request.await
+            }
+        });
+        future::join_all(requests).await
+    }
+
+    /// Edit several items with single request. Returns number of items changed.
+    pub async fn batch_update_library_items(
+        &self,
+        updates: &[BatchUpdateParams],
+    ) -> Result<usize, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(updates).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.items_batch_url("update"))
+            .body(body);
+
+        let result = self
+            .send::<BatchUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.updates)
+    }
+
+    /// Remove items from library. With `hard` set their files are deleted from disk too.
+    pub async fn batch_delete_library_items(
+        &self,
+        ids: &[Id<LibraryItem>],
+        hard: bool,
+    ) -> Result<(), FusedError<RequestError>> {
+        self.require(Permission::Delete)?;
+        let body = serde_json::to_string(&BatchItemsParams {
+            library_item_ids: ids,
+        })
+        .unwrap();
+        let mut request_builder = self
+            .request(Method::POST, self.config.items_batch_url("delete"))
+            .body(body);
+        if hard {
+            request_builder = request_builder.query(&[("hard", "1")]);
+        }
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Edit metadata, tags or chapters of book or podcast.
+    pub async fn update_library_item_media(
+        &self,
+        id: &Id<LibraryItem>,
+        params: &UpdateMediaParams,
+    ) -> Result<LibraryItemUpdate, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(params).unwrap();
+        let url = self.config.library_item_sub_url(id.as_str(), &["media"]);
+        let request_builder = self.request(Method::PATCH, url).body(body);
+
+        self.send::<LibraryItemUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Search metadata provider for item, and apply best match.
+    pub async fn match_library_item(
+        &self,
+        id: &Id<LibraryItem>,
+        params: &MatchParams,
+    ) -> Result<LibraryItemUpdate, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(params).unwrap();
+        let url = self.config.library_item_sub_url(id.as_str(), &["match"]);
+        let request_builder = self.request(Method::POST, url).body(body);
+
+        self.send::<LibraryItemUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Start matching of multiple items in background. Its outcome is reported with
+    /// `batch_quickmatch_complete` server event.
+    pub async fn batch_quick_match(
+        &self,
+        ids: &[Id<LibraryItem>],
+        provider: &MetadataProvider,
+        options: &MatchOptions,
+    ) -> Result<(), FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(&BatchQuickMatchParams {
+            library_item_ids: ids,
+            options: BatchQuickMatchOptions { provider, options },
+        })
+        .unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.items_batch_url("quickmatch"))
+            .body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Replace chapters of book, e.g. with ones found by `search_chapters`.
+    ///
+    /// Returns whether chapters changed.
+    pub async fn update_item_chapters(
+        &self,
+        id: &Id<LibraryItem>,
+        chapters: &[Chapter],
+    ) -> Result<bool, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(&ChaptersParams { chapters }).unwrap();
+        let url = self.config.library_item_sub_url(id.as_str(), &["chapters"]);
+        let request_builder = self.request(Method::POST, url).body(body);
+
+        let result = self
+            .send::<ChaptersUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.updated)
+    }
+
+    /// Receive data neccesary to play media item.
+    ///
+    /// Note: despite name `play` suggesting that it is statefull, it does not update user media progress. That sould be done manually by using `update_media_progress`
+    pub async fn library_item_play(
+        &self,
+        id: &Id<LibraryItem>,
+        params: &PlayLibraryItemParams,
+    ) -> Result<PlaybackSessionExtended, FusedError<RequestError>> {
+        let url = self.config.library_item_play_url(id.as_str(), None);
+        self.play(url, params).await
+    }
+
+    /// Receive data neccesary to play podcast episode. Same as `library_item_play` otherwise.
+    ///
+    /// Progress of episode is updated with `update_episode_progress`.
+    pub async fn library_item_play_episode(
+        &self,
+        id: &Id<LibraryItem>,
+        episode_id: &Id<PodcastEpisode>,
+        params: &PlayLibraryItemParams,
+    ) -> Result<PlaybackSessionExtended, FusedError<RequestError>> {
+        let url = self
+            .config
+            .library_item_play_url(id.as_str(), Some(episode_id.as_str()));
+        self.play(url, params).await
+    }
+
+    async fn play(
+        &self,
+        url: Url,
+        params: &PlayLibraryItemParams,
+    ) -> Result<PlaybackSessionExtended, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, url)
+            .query(&[("include", "authors")])
+            .body(body);
+
+        let playback = self
+            .send::<PlaybackSessionExtended>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(playback.playback_session.id.id.clone());
+        Ok(playback)
+    }
+
+    /// Collections of all libraries accessible to user.
+    pub async fn collections(&self) -> Result<Vec<CollectionExpanded>, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.collections_url());
+
+        let result = self
+            .send::<Collections>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.collections)
+    }
+
+    pub async fn library_collections(
+        &self,
+        id: &Id<Library>,
+    ) -> Result<Vec<CollectionExpanded>, FusedError<RequestError>> {
+        let request_builder = self.request(
+            Method::GET,
+            self.config.library_collections_url(id.as_str()),
+        );
+
+        let result = self
+            .send::<PaginatedResponse<CollectionExpanded>>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.results)
+    }
+
+    pub async fn collection(
+        &self,
+        id: &Id<Collection>,
+    ) -> Result<CollectionExpanded, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.collection_url(id.as_str()));
+
+        self.send::<CollectionExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn create_collection(
+        &self,
+        params: &CreateCollectionParams,
+    ) -> Result<CollectionExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.collections_url())
+            .body(body);
+
+        self.send::<CollectionExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn update_collection(
+        &self,
+        id: &Id<Collection>,
+        params: &UpdateCollectionParams,
+    ) -> Result<CollectionExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::PATCH, self.config.collection_url(id.as_str()))
+            .body(body);
+
+        self.send::<CollectionExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn delete_collection(
+        &self,
+        id: &Id<Collection>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self.request(Method::DELETE, self.config.collection_url(id.as_str()));
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    pub async fn collection_add_book(
+        &self,
+        id: &Id<Collection>,
+        book_id: &Id<LibraryItem>,
+    ) -> Result<CollectionExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(&IdParams {
+            id: book_id.as_str(),
+        })
+        .unwrap();
+        let request_builder = self
+            .request(
+                Method::POST,
+                self.config.collection_book_url(id.as_str(), None),
+            )
+            .body(body);
+
+        self.send::<CollectionExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn collection_remove_book(
+        &self,
+        id: &Id<Collection>,
+        book_id: &Id<LibraryItem>,
+    ) -> Result<CollectionExpanded, FusedError<RequestError>> {
+        let url = self
+            .config
+            .collection_book_url(id.as_str(), Some(book_id.as_str()));
+        let request_builder = self.request(Method::DELETE, url);
+
+        self.send::<CollectionExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn create_bookmark(
+        &self,
+        id: &Id<LibraryItem>,
+        params: &BookmarkParams,
+    ) -> Result<AudioBookmark, FusedError<RequestError>> {
+        self.send_bookmark(Method::POST, id, params).await
+    }
+
+    /// Rename bookmark at `params.time`.
+    pub async fn update_bookmark(
+        &self,
+        id: &Id<LibraryItem>,
+        params: &BookmarkParams,
+    ) -> Result<AudioBookmark, FusedError<RequestError>> {
+        self.send_bookmark(Method::PATCH, id, params).await
+    }
+
+    async fn send_bookmark(
+        &self,
+        method: Method,
+        id: &Id<LibraryItem>,
+        params: &BookmarkParams,
+    ) -> Result<AudioBookmark, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(method, self.config.bookmark_url(id.as_str(), None))
+            .body(body);
+
+        self.send::<AudioBookmark>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Delete bookmark of item at `time` seconds.
+    pub async fn delete_bookmark(
+        &self,
+        id: &Id<LibraryItem>,
+        time: f64,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self.config.bookmark_url(id.as_str(), Some(time));
+        let request_builder = self.request(Method::DELETE, url);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Playlists of current user across all libraries.
+    pub async fn playlists(&self) -> Result<Vec<PlaylistExpanded>, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.playlists_url());
+
+        let result = self
+            .send::<Playlists>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.playlists)
+    }
+
+    pub async fn library_playlists(
+        &self,
+        id: &Id<Library>,
+    ) -> Result<Vec<PlaylistExpanded>, FusedError<RequestError>> {
+        let request_builder =
+            self.request(Method::GET, self.config.library_playlists_url(id.as_str()));
+
+        let result = self
+            .send::<PaginatedResponse<PlaylistExpanded>>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.results)
+    }
+
+    pub async fn playlist(
+        &self,
+        id: &Id<Playlist>,
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.playlist_url(id.as_str()));
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn create_playlist(
+        &self,
+        params: &CreatePlaylistParams,
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.playlists_url())
+            .body(body);
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Create playlist of current user containing all books of collection.
+    pub async fn create_playlist_from_collection(
+        &self,
+        collection_id: &Id<Collection>,
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let url = self
+            .config
+            .playlist_from_collection_url(collection_id.as_str());
+        let request_builder = self.request(Method::POST, url);
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn update_playlist(
+        &self,
+        id: &Id<Playlist>,
+        params: &UpdatePlaylistParams,
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::PATCH, self.config.playlist_url(id.as_str()))
+            .body(body);
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn delete_playlist(&self, id: &Id<Playlist>) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self.request(Method::DELETE, self.config.playlist_url(id.as_str()));
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    pub async fn playlist_add_item(
+        &self,
+        id: &Id<Playlist>,
+        item: &PlaylistItem,
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(item).unwrap();
+        let request_builder = self
+            .request(
+                Method::POST,
+                self.config.playlist_item_url(id.as_str(), None),
+            )
+            .body(body);
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Remove item from playlist. Server deletes playlist once it has no items left.
+    pub async fn playlist_remove_item(
+        &self,
+        id: &Id<Playlist>,
+        item: &PlaylistItem,
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let url = self.config.playlist_item_url(id.as_str(), Some(item));
+        let request_builder = self.request(Method::DELETE, url);
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    pub async fn playlist_add_items(
+        &self,
+        id: &Id<Playlist>,
+        items: &[PlaylistItem],
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        self.playlist_batch(id, "add", items).await
+    }
+
+    pub async fn playlist_remove_items(
+        &self,
+        id: &Id<Playlist>,
+        items: &[PlaylistItem],
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        self.playlist_batch(id, "remove", items).await
+    }
+
+    async fn playlist_batch(
+        &self,
+        id: &Id<Playlist>,
+        action: &str,
+        items: &[PlaylistItem],
+    ) -> Result<PlaylistExpanded, FusedError<RequestError>> {
+        let body = serde_json::to_string(&PlaylistItemsParams { items }).unwrap();
+        let request_builder = self
+            .request(
+                Method::POST,
+                self.config.playlist_batch_url(id.as_str(), action),
+            )
+            .body(body);
+
+        self.send::<PlaylistExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Update user progress of book.
+    pub async fn update_media_progress(
+        &self,
+        id: &Id<LibraryItem>,
+        params: &MediaProgressParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        self.patch_media_progress(self.config.media_progress_url(id.as_str(), None), params)
+            .await
+    }
+
+    /// Update reading position of book's ebook. `progress` is fraction read, from 0 to 1.
+    pub async fn update_ebook_progress(
+        &self,
+        id: &Id<LibraryItem>,
+        location: &str,
+        progress: f64,
+    ) -> Result<(), FusedError<RequestError>> {
+        let params = MediaProgressParams::new()
+            .ebook_location(location)
+            .ebook_progress(progress);
+        self.update_media_progress(id, &params).await
+    }
+
+    /// Mark book as finished.
+    pub async fn mark_finished(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<(), FusedError<RequestError>> {
+        self.update_media_progress(id, &MediaProgressParams::new().is_finished(true))
+            .await
+    }
+
+    /// Update progress of several items or episodes at once.
+    pub async fn batch_update_media_progress(
+        &self,
+        updates: &[BatchMediaProgressParams],
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(updates).unwrap();
+        let request_builder = self
+            .request(Method::PATCH, self.config.media_progress_batch_url())
+            .body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Remove progress entry, resetting item or episode to not started.
+    pub async fn delete_media_progress(
+        &self,
+        id: &Id<MediaProgress>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self.config.media_progress_url(id.as_str(), None);
+        let request_builder = self.request(Method::DELETE, url);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Update user progress of podcast episode.
+    pub async fn update_episode_progress(
+        &self,
+        id: &Id<LibraryItem>,
+        episode_id: &Id<PodcastEpisode>,
+        params: &MediaProgressParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let url = self
+            .config
+            .media_progress_url(id.as_str(), Some(episode_id.as_str()));
+        self.patch_media_progress(url, params).await
+    }
+
+    async fn patch_media_progress(
+        &self,
+        url: Url,
+        params: &MediaProgressParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self.request(Method::PATCH, url).body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Connect to server event stream, reconnecting with default policy then connection is lost.
+    #[cfg(feature = "events")]
+    pub async fn events(&self) -> Result<events::EventStream, FusedError<AuthError>> {
+        self.events_with_reconnect(events::ReconnectPolicy::default())
+            .await
+    }
+
+    /// Connect to server event stream.
+    ///
+    /// After reconnecting, events missed meanwhile are recovered by fetching user data and
+    /// recently updated library items.
+    #[cfg(feature = "events")]
+    pub async fn events_with_reconnect(
+        &self,
+        policy: events::ReconnectPolicy,
+    ) -> Result<events::EventStream, FusedError<AuthError>> {
+        events::connect(
+            self.clone(),
+            events::socket_url(&self.config.endpoints),
+            policy,
+        )
+        .await
+    }
+
+    /// Progress updates made by other devices of current user.
+    ///
+    /// Updates made through sessions opened by this client are skipped. Updates made directly,
+    /// with `update_media_progress`, are not attributed to any session and are always reported.
+    #[cfg(feature = "events")]
+    pub async fn watch_progress(
+        &self,
+    ) -> Result<impl Stream<Item = schema::MediaProgressUpdate>, FusedError<AuthError>> {
+        let sessions = self.sessions.clone();
+        let events = self.events().await?;
+        Ok(events.filter_map(move |event| {
+            let update = match event {
+                events::AbsEvent::MediaProgressUpdated(update) => {
+                    let sessions = sessions.lock().unwrap();
+                    let own = update
+                        .session_id
+                        .as_ref()
+                        .is_some_and(|id| sessions.contains(id.as_str()));
+                    (!own).then_some(*update)
+                }
+                _ => None,
+            };
+            std::future::ready(update)
+        }))
+    }
+
+    /// Author along with their books and series.
+    pub async fn author(
+        &self,
+        id: &Id<Author>,
+    ) -> Result<AuthorExpanded, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.author_url(id.as_str(), None))
+            .query(&[("include", "items,series")]);
+
+        self.send::<AuthorExpanded>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Update author details. Renaming author to name of existing one merges them.
+    pub async fn update_author(
+        &self,
+        id: &Id<Author>,
+        params: &UpdateAuthorParams,
+    ) -> Result<AuthorUpdate, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::PATCH, self.config.author_url(id.as_str(), None))
+            .body(body);
+
+        self.send::<AuthorUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Fill author description and image from Audible.
+    pub async fn match_author(
+        &self,
+        id: &Id<Author>,
+        params: &MatchAuthorParams,
+    ) -> Result<AuthorUpdate, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(
+                Method::POST,
+                self.config.author_url(id.as_str(), Some("match")),
+            )
+            .body(body);
+
+        self.send::<AuthorUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Search metadata provider for book candidates, which can be applied with
+    /// `update_library_item_media`.
+    pub async fn search_books(
+        &self,
+        params: &BookSearchParams,
+    ) -> Result<Vec<BookMatch>, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.metadata_search_url("books"))
+            .query(params);
+
+        self.send::<Vec<BookMatch>>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Search metadata provider for cover urls.
+    pub async fn search_covers(
+        &self,
+        params: &BookSearchParams,
+    ) -> Result<Vec<String>, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.metadata_search_url("covers"))
+            .query(params);
+
+        let result = self
+            .send::<CoverSearchResult>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.results)
+    }
+
+    /// Look up chapters of audible book by its ASIN. `region` is audible region, e.g. `us`.
+    ///
+    /// Returns `None` if book is not known.
+    pub async fn search_chapters(
+        &self,
+        asin: &str,
+        region: &str,
+    ) -> Result<Option<AudnexusChapters>, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.metadata_search_url("chapters"))
+            .query(&[("asin", asin), ("region", region)]);
+
+        let result = self
+            .send::<ChapterSearchResult>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        match result {
+            ChapterSearchResult::Found(chapters) => Ok(Some(chapters)),
+            ChapterSearchResult::NotFound { .. } => Ok(None),
+        }
+    }
+
+    /// All open RSS feeds. Requires admin user.
+    pub async fn feeds(&self) -> Result<Vec<Feed>, FusedError<RequestError>> {
+        let request_builder = self.request(Method::GET, self.config.feeds_url());
+
+        let result = self
+            .send::<Feeds>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.feeds)
+    }
+
+    /// Open RSS feed, served by server under `/feed/<slug>`.
+    pub async fn open_feed(
+        &self,
+        entity: &FeedEntity,
+        params: &OpenFeedParams,
+    ) -> Result<Feed, FusedError<RequestError>> {
+        let server_address = self.config.endpoints.root().as_str().trim_end_matches('/');
+        let body = serde_json::to_string(&OpenFeedBody {
+            server_address,
+            slug: &params.slug,
+            metadata_details: params,
+        })
+        .unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.feed_open_url(entity))
+            .body(body);
+
+        let result = self
+            .send::<FeedResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.feed)
+    }
+
+    pub async fn close_feed(&self, id: &Id<Feed>) -> Result<(), FusedError<RequestError>> {
+        let request_builder = self.request(Method::POST, self.config.feed_close_url(id.as_str()));
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Search iTunes for podcasts matching `term`.
+    pub async fn search_podcasts(
+        &self,
+        term: &str,
+    ) -> Result<Vec<PodcastSearchResult>, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.podcast_search_url())
+            .query(&[("term", term)]);
+
+        self.send::<Vec<PodcastSearchResult>>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Fetch and parse podcast RSS feed, e.g. `PodcastSearchResult::feed_url`.
+    pub async fn podcast_feed(
+        &self,
+        rss_feed: &str,
+    ) -> Result<PodcastFeed, FusedError<RequestError>> {
+        let body = serde_json::to_string(&PodcastFeedParams { rss_feed }).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.podcast_feed_url())
+            .body(body);
+
+        let result = self
+            .send::<PodcastFeedResponse>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.podcast)
+    }
+
+    /// Add podcast to library, e.g. one built with `CreatePodcastParams::from_feed`.
+    pub async fn create_podcast(
+        &self,
+        params: &CreatePodcastParams,
+    ) -> Result<LibraryItem, FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.podcasts_url(&[]))
+            .body(body);
+
+        self.send::<LibraryItem>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Podcast feeds listed in OPML file, as exported by podcast apps.
+    pub async fn parse_opml(
+        &self,
+        opml_text: &str,
+    ) -> Result<Vec<OpmlFeed>, FusedError<RequestError>> {
+        let body = serde_json::to_string(&OpmlParseParams { opml_text }).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.podcasts_url(&["opml", "parse"]))
+            .body(body);
+
+        let result = self
+            .send::<OpmlFeeds>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.feeds)
+    }
+
+    /// Add podcasts of several feeds to library. Podcasts are created in background, and
+    /// reported with `item_added` server events. Requires admin.
+    pub async fn create_podcasts_from_opml(
+        &self,
+        params: &OpmlCreateParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.podcasts_url(&["opml", "create"]))
+            .body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Check podcast feed for episodes newer than latest one in library. Server queues their
+    /// download if auto download is enabled for podcast.
+    ///
+    /// `limit` caps number of episodes returned, server default is 3.
+    pub async fn podcast_check_new_episodes(
+        &self,
+        id: &Id<LibraryItem>,
+        limit: Option<usize>,
+    ) -> Result<Vec<PodcastFeedEpisode>, FusedError<RequestError>> {
+        let mut request_builder = self.request(
+            Method::GET,
+            self.config.podcast_url(id.as_str(), "checknew"),
+        );
+        if let Some(limit) = limit {
+            request_builder = request_builder.query(&[("limit", limit)]);
+        }
+
+        let result = self
+            .send::<NewEpisodes>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.episodes)
+    }
+
+    /// Queued downloads of podcast episodes.
+    pub async fn podcast_downloads(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<Vec<EpisodeDownload>, FusedError<RequestError>> {
+        let request_builder = self.request(
+            Method::GET,
+            self.config.podcast_url(id.as_str(), "downloads"),
+        );
+
+        let result = self
+            .send::<EpisodeDownloads>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.downloads)
+    }
+
+    /// Download in progress and queued downloads of all podcasts in library.
+    pub async fn library_episode_downloads(
+        &self,
+        id: &Id<Library>,
+    ) -> Result<EpisodeDownloadQueue, FusedError<RequestError>> {
+        let url = self.config.library_episode_downloads_url(id.as_str());
+        let request_builder = self.request(Method::GET, url);
+
+        self.send::<EpisodeDownloadQueue>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Queue download of podcast episodes, as returned by `podcast_feed` or
+    /// `podcast_check_new_episodes`.
+    pub async fn podcast_download_episodes(
+        &self,
+        id: &Id<LibraryItem>,
+        episodes: &[PodcastFeedEpisode],
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(episodes).unwrap();
+        let url = self.config.podcast_url(id.as_str(), "download-episodes");
+        let request_builder = self.request(Method::POST, url).body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Edit metadata of podcast episode, returning podcast after update.
+    pub async fn update_podcast_episode(
+        &self,
+        id: &Id<LibraryItem>,
+        episode_id: &Id<PodcastEpisode>,
+        params: &UpdateEpisodeParams,
+    ) -> Result<LibraryItem, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(params).unwrap();
+        let url = self
+            .config
+            .podcast_episode_url(id.as_str(), episode_id.as_str());
+        let request_builder = self.request(Method::PATCH, url).body(body);
+
+        self.send::<LibraryItem>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Remove episode from podcast, returning podcast after update. Audio file is deleted
+    /// from disk only if `hard` is set.
+    pub async fn delete_podcast_episode(
+        &self,
+        id: &Id<LibraryItem>,
+        episode_id: &Id<PodcastEpisode>,
+        hard: bool,
+    ) -> Result<LibraryItem, FusedError<RequestError>> {
+        self.require(Permission::Delete)?;
+        let url = self
+            .config
+            .podcast_episode_url(id.as_str(), episode_id.as_str());
+        let mut request_builder = self.request(Method::DELETE, url);
+        if hard {
+            request_builder = request_builder.query(&[("hard", "1")]);
+        }
+
+        self.send::<LibraryItem>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Fill in missing metadata of podcast episodes from matching episodes of its feed.
+    /// With `override_existing`, metadata already present is replaced too.
+    ///
+    /// Returns number of updated episodes.
+    pub async fn match_podcast_episodes(
+        &self,
+        id: &Id<LibraryItem>,
+        override_existing: bool,
+    ) -> Result<usize, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let mut request_builder = self.request(
+            Method::POST,
+            self.config.podcast_url(id.as_str(), "match-episodes"),
+        );
+        if override_existing {
+            request_builder = request_builder.query(&[("override", "1")]);
+        }
+
+        let result = self
+            .send::<EpisodesMatch>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.num_episodes_updated)
+    }
+
+    /// Close playback session, so it is no longer reported as open on server.
+    pub async fn close_session(
+        &self,
+        id: &Id<PlaybackSession>,
+    ) -> Result<(), FusedError<RequestError>> {
+        let request_builder =
+            self.request(Method::POST, self.config.session_url(id.as_str(), "close"));
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        self.sessions.lock().unwrap().remove(id.as_str());
+        Ok(())
+    }
+
+    /// Report playback position of open session. Media progress is updated along.
+    pub async fn sync_session(
+        &self,
+        id: &Id<PlaybackSession>,
+        params: &SyncSessionParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.session_url(id.as_str(), "sync"))
+            .body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Report session played from downloaded files.
+    pub async fn sync_local_session(
+        &self,
+        params: &LocalSessionParams,
+    ) -> Result<(), FusedError<RequestError>> {
+        let body = serde_json::to_string(params).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.local_session_url(false))
+            .body(body);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Report sessions played from downloaded files in bulk, such as ones recorded while
+    /// offline. Sessions are processed independently, with outcome reported for each.
+    pub async fn sync_local_sessions(
+        &self,
+        sessions: &[LocalSessionParams],
+    ) -> Result<Vec<LocalSessionResult>, FusedError<RequestError>> {
+        let body = serde_json::to_string(&LocalSessionsParams { sessions }).unwrap();
+        let request_builder = self
+            .request(Method::POST, self.config.local_session_url(true))
+            .body(body);
+
+        let result = self
+            .send::<LocalSessionResults>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.results)
+    }
+
+    async fn send<ResponseSchema>(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<ResponseSchema, FusedError<ResponseError>>
+    where
+        ResponseSchema: for<'a> serde::Deserialize<'a>,
+    {
+        let (info, body) = self.send_text(request_builder).await?;
+        Self::parse(&info, &body)
+    }
+
+    /// Send request, returning response body as text. GET requests are served from cache
+    /// if it is enabled and server confirms that stored response is still valid.
+    async fn send_text(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<(RequestInfo, String), FusedError<ResponseError>> {
+        let mut request = Self::build(request_builder)?;
+        let info = RequestInfo::of(&request);
+        let cache = (request.method() == Method::GET)
+            .then_some(self.config.cache.as_ref())
+            .flatten();
+        let cached = cache.and_then(|cache| cache.get(&info.url));
+        if let Some(cached) = &cached {
+            cached.add_conditions(request.headers_mut());
+        }
+
+        let response = match (self.execute(request).await, cached) {
+            (Err(FusedError::DomainError(error)), Some(cached))
+                if error.status == StatusCode::NOT_MODIFIED =>
+            {
+                return Ok((info, cached.body));
+            }
+            (result, _) => result?,
+        };
+        let headers = response.headers().clone();
+        let body = response
+            .text()
+            .await
+            .map_err(|error| APIError::network(&info, error))?;
+        #[cfg(feature = "tracing")]
+        trace::response_body(&info.url, &body);
+        if let Some(cache) = cache {
+            cache.insert(&info.url, &headers, &body);
+        }
+        Ok((info, body))
+    }
+
+    /// Items of paginated list at `url`, starting from `params.page`, fetched page by page.
+    ///
+    /// Responses are parsed incrementally, bypassing response cache.
+    fn paginated_stream<T>(
+        &self,
+        url: Url,
+        params: LibraryItemParams,
+    ) -> impl Stream<Item = Result<T, FusedError<RequestError>>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        struct Pages<T> {
+            client: UserClient,
+            url: Url,
+            params: Option<LibraryItemParams>,
+            items: Option<BoxStream<'static, Result<PageEvent<T>, FusedError<ResponseError>>>>,
+            received: usize,
+        }
+
+        let pages = Pages {
+            client: self.clone(),
+            url,
+            params: Some(params),
+            items: None,
+            received: 0,
+        };
+        stream::unfold(pages, |mut pages| async move {
+            loop {
+                let Some(items) = pages.items.as_mut() else {
+                    let params = pages.params.as_ref()?;
+                    let request_builder = pages
+                        .client
+                        .request(Method::GET, pages.url.clone())
+                        .query(&params.clone().build_query());
+                    match pages.client.send_paginated::<T>(request_builder).await {
+                        Ok(items) => {
+                            pages.items = Some(items.boxed());
+                            pages.received = 0;
+                            continue;
+                        }
+                        Err(error) => {
+                            pages.params = None;
+                            return Some((Err(error.to_request_error()), pages));
+                        }
+                    }
+                };
+                match items.next().await {
+                    Some(Ok(PageEvent::Item(item))) => {
+                        pages.received += 1;
+                        return Some((Ok(item), pages));
+                    }
+                    Some(Ok(PageEvent::End { total })) => {
+                        let received = pages.received;
+                        pages.items = None;
+                        pages.params = pages.params.take().and_then(|params| {
+                            let has_more = params.limit != 0
+                                && received != 0
+                                && (params.page + 1) * params.limit < total;
+                            has_more.then(|| LibraryItemParams {
+                                page: params.page + 1,
+                                ..params
+                            })
+                        });
+                    }
+                    Some(Err(error)) => {
+                        pages.items = None;
+                        pages.params = None;
+                        return Some((Err(error.to_request_error()), pages));
+                    }
+                    None => return None,
+                }
+            }
+        })
+    }
+
+    /// Send request for page of paginated list, parsing items as response arrives.
+    async fn send_paginated<T>(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<
+        impl Stream<Item = Result<PageEvent<T>, FusedError<ResponseError>>>,
+        FusedError<ResponseError>,
+    >
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let request = Self::build(request_builder)?;
+        let info = RequestInfo::of(&request);
+        let response = self.execute(request).await?;
+        Ok(json_stream::paginated_items(info, response))
+    }
+
+    /// Send request, which needs no authorization, parsing JSON response.
+    async fn send_anonymous<ResponseSchema>(
+        transport: &dyn Transport,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<ResponseSchema, FusedError<ResponseError>>
+    where
+        ResponseSchema: for<'a> serde::Deserialize<'a>,
+    {
+        let request = Self::build(request_builder)?;
+        let info = RequestInfo::of(&request);
+        #[cfg(feature = "tracing")]
+        let span = trace::request_span(&request);
+        #[cfg(feature = "metrics")]
+        let labels = telemetry::Labels::of(&request);
+        let future = async {
+            let response = transport
+                .execute(request)
+                .await
+                .map_err(|error| APIError::network(&info, error))?;
+            Self::check_status(&info, response).await
+        };
+        #[cfg(feature = "tracing")]
+        let future = trace::instrument(span, future);
+        #[cfg(feature = "metrics")]
+        let future = telemetry::instrument(labels, future);
+        let response = future.await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|error| APIError::network(&info, error))?;
+        #[cfg(feature = "tracing")]
+        trace::response_body(&info.url, &body);
+        Self::parse(&info, &body)
+    }
+
+    fn parse<ResponseSchema>(
+        request: &RequestInfo,
+        body: &str,
+    ) -> Result<ResponseSchema, FusedError<ResponseError>>
+    where
+        ResponseSchema: for<'a> serde::Deserialize<'a>,
+    {
+        let json_deserializer = &mut serde_json::Deserializer::from_str(body);
+        let result = serde_path_to_error::deserialize(json_deserializer);
+        match result {
+            Ok(result) => Ok(result),
+            Err(source) => Err(FusedError::APIError(APIError::InvalidResponseSchema {
+                request: Box::new(request.clone()),
+                source,
+            })),
+        }
+    }
+
+    fn build(request_builder: reqwest::RequestBuilder) -> Result<reqwest::Request, APIError> {
+        request_builder
+            .build()
+            .map_err(|error| APIError::UnknownError(error.into()))
+    }
+
+    /// Send request, returning response body as is.
+    async fn send_raw(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<String, FusedError<ResponseError>> {
+        let (_, body) = self.send_text(request_builder).await?;
+        Ok(body)
+    }
+
+    /// Send request, returning successful response with body not yet read.
+    async fn send_response(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, FusedError<ResponseError>> {
+        self.execute(Self::build(request_builder)?).await
+    }
+
+    /// Send request, returning successful response with body not yet read.
+    async fn execute(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, FusedError<ResponseError>> {
+        #[cfg(feature = "tracing")]
+        let span = trace::request_span(&request);
+        #[cfg(feature = "metrics")]
+        let labels = telemetry::Labels::of(&request);
+        let future = self.execute_cancellable(request);
+        #[cfg(feature = "tracing")]
+        let future = trace::instrument(span, future);
+        #[cfg(feature = "metrics")]
+        let future = telemetry::instrument(labels, future);
+        future.await
+    }
+
+    async fn execute_cancellable(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, FusedError<ResponseError>> {
+        match &self.cancellation {
+            Some(token) => {
+                let info = RequestInfo::of(&request);
+                tokio::select! {
+                    result = self.execute_with_retry(request) => result,
+                    _ = token.cancelled() => Err(APIError::Cancelled(Box::new(info)).into()),
+                }
+            }
+            None => self.execute_with_retry(request).await,
+        }
+    }
+
+    /// Transient failures are retried according to configured `RetryPolicy`.
+    async fn execute_with_retry(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, FusedError<ResponseError>> {
+        let info = RequestInfo::of(&request);
+        let policy = &self.config.retry;
+        let mut request = request;
+        let mut attempt = 1;
+        loop {
+            let next = if attempt < policy.max_attempts && policy.retries_method(request.method()) {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = self.execute_authorized(request).await;
+            let delay;
+            (request, delay) = match (result, next) {
+                (Ok(response), Some(next)) if policy.retries_status(response.status()) => {
+                    match retry::retry_after(response.headers()) {
+                        // Retrying earlier than server asked is pointless
+                        Some(delay) if delay > policy.max_delay => {
+                            return Self::check_status(&info, response).await
+                        }
+                        Some(delay) => (next, delay),
+                        None => (next, policy.delay(attempt)),
+                    }
+                }
+                (Err(error), Some(next)) if policy.retries_error(&error) => {
+                    (next, policy.delay(attempt))
+                }
+                (Ok(response), _) => return Self::check_status(&info, response).await,
+                (Err(error), _) => return Err(APIError::network(&info, error).into()),
+            };
+            #[cfg(feature = "metrics")]
+            telemetry::retry(&request);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// If request is rejected with 401 and credentials are known, token is renewed and
+    /// request is retried once.
+    async fn execute_authorized(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let retry = request.try_clone();
+        let rejected = request
+            .headers()
+            .get(reqwest::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string);
+        let response = self.transport.execute(request).await?;
+
+        let can_reauthenticate = self.credentials.read().unwrap().is_some();
+        match (retry, rejected) {
+            (Some(mut retry), Some(rejected))
+                if response.status() == StatusCode::UNAUTHORIZED && can_reauthenticate =>
+            {
+                if self.reauthenticate_rejected(&rejected).await.is_err() {
+                    return Ok(response);
+                }
+                let Ok(authorization) = HeaderValue::from_str(&format!("Bearer {}", self.token()))
+                else {
+                    return Ok(response);
+                };
+                retry
+                    .headers_mut()
+                    .insert(reqwest::header::AUTHORIZATION, authorization);
+                self.transport.execute(retry).await
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// Turn unsuccessful response into error.
+    async fn check_status(
+        request: &RequestInfo,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, FusedError<ResponseError>> {
+        let status = response.status();
+        if status.is_success() {
+            Ok(response)
+        } else {
+            let body = response
+                .text()
+                .await
+                .map_err(|error| APIError::network(request, error))?;
+            Err(FusedError::DomainError(ResponseError {
+                request: Box::new(request.clone()),
+                status,
+                error: ServerError::parse(&body),
+            }))
+        }
+    }
+
+    /// Request audio file, returning response which body can be read incrementally.
+    pub async fn audiofile_response(
+        &self,
+        url: &str,
+    ) -> Result<reqwest::Response, FusedError<RequestError>> {
+        let url = self.build_abs_url(url).map_err(APIError::from)?;
+        let request_builder = self.request(Method::GET, url);
+        self.send_response(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Upload files as new library item. Requires upload permission.
+    ///
+    /// Server rejects upload if item directory already exists.
+    pub async fn upload(&self, params: UploadParams) -> Result<(), FusedError<RequestError>> {
+        self.require(Permission::Upload)?;
+        type Body = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>>;
+
+        let mut sources = Vec::with_capacity(params.files.len());
+        for file in params.files {
+            let (length, body) = match file.source {
+                UploadSource::Bytes(data) => {
+                    let length = data.len() as u64;
+                    let body: Body = Box::pin(stream::once(async { Ok(data) }));
+                    (length, body)
+                }
+                UploadSource::Path(path) => {
+                    let file = tokio::fs::File::open(&path)
+                        .await
+                        .map_err(|e| APIError::UnknownError(e.into()))?;
+                    let length = file
+                        .metadata()
+                        .await
+                        .map_err(|e| APIError::UnknownError(e.into()))?
+                        .len();
+                    let body: Body = Box::pin(ReaderStream::new(file));
+                    (length, body)
+                }
+            };
+            sources.push((file.name, length, body));
+        }
+
+        let total = sources.iter().map(|(_, length, _)| length).sum();
+        let sent = Arc::new(AtomicU64::new(0));
+        let mut form = Form::new()
+            .text("title", params.title)
+            .text("library", params.library.into_string())
+            .text("folder", params.folder.into_string());
+        if let Some(author) = params.author {
+            form = form.text("author", author);
+        }
+        if let Some(series) = params.series {
+            form = form.text("series", series);
+        }
+        for (index, (name, length, body)) in sources.into_iter().enumerate() {
+            let sent = sent.clone();
+            let on_progress = params.on_progress.clone();
+            let body = body.inspect(move |chunk| {
+                if let (Ok(chunk), Some(on_progress)) = (chunk, &on_progress) {
+                    let sent = sent.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                    on_progress(UploadProgress {
+                        sent: sent + chunk.len() as u64,
+                        total,
+                    });
+                }
+            });
+            let part =
+                Part::stream_with_length(reqwest::Body::wrap_stream(body), length).file_name(name);
+            form = form.part(index.to_string(), part);
+        }
+
+        // No timeout, since sending files may take long
+        let request_builder = self
+            .client
+            .post(self.config.upload_url())
+            .bearer_auth(self.token())
+            .multipart(form);
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Download single file of library item, identified by its inode.
+    pub async fn download_library_file(
+        &self,
+        id: &Id<LibraryItem>,
+        ino: &str,
+    ) -> Result<Download, FusedError<RequestError>> {
+        self.require(Permission::Download)?;
+        let url = self
+            .config
+            .library_item_sub_url(id.as_str(), &["file", ino, "download"]);
+        self.download(url).await
+    }
+
+    /// Single file of library item, served inline with its content type, e.g. for
+    /// displaying images or reading text files.
+    pub async fn library_file(
+        &self,
+        id: &Id<LibraryItem>,
+        ino: &str,
+    ) -> Result<Download, FusedError<RequestError>> {
+        let url = self
+            .config
+            .library_item_sub_url(id.as_str(), &["file", ino]);
+        self.download(url).await
+    }
+
+    /// Delete file of library item from server disk. Requires delete permission.
+    pub async fn delete_library_file(
+        &self,
+        id: &Id<LibraryItem>,
+        ino: &str,
+    ) -> Result<(), FusedError<RequestError>> {
+        self.require(Permission::Delete)?;
+        let url = self
+            .config
+            .library_item_sub_url(id.as_str(), &["file", ino]);
+        let request_builder = self.request(Method::DELETE, url);
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Set order of audio files of book, marking them as manually verified. Files are
+    /// numbered in order of `tracks`, skipping excluded ones. Every file must belong to
+    /// the book.
+    pub async fn update_item_tracks(
+        &self,
+        id: &Id<LibraryItem>,
+        tracks: &[TrackOrder],
+    ) -> Result<LibraryItem, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(&TracksParams {
+            ordered_file_data: tracks,
+        })
+        .unwrap();
+        let url = self.config.library_item_sub_url(id.as_str(), &["tracks"]);
+        let request_builder = self.request(Method::PATCH, url).body(body);
+
+        self.send::<LibraryItem>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)
+    }
+
+    /// Download bytes `start..=end` of single file of library item. Without `end` file
+    /// is downloaded till its end.
+    pub async fn download_library_file_range(
+        &self,
+        id: &Id<LibraryItem>,
+        ino: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Download, FusedError<RequestError>> {
+        self.require(Permission::Download)?;
+        let url = self
+            .config
+            .library_item_sub_url(id.as_str(), &["file", ino, "download"]);
+        let range = match end {
+            Some(end) => format!("bytes={start}-{end}"),
+            None => format!("bytes={start}-"),
+        };
+        let request_builder = self
+            .client
+            .get(url)
+            .bearer_auth(self.token())
+            .header(reqwest::header::RANGE, range);
+        self.download_with(request_builder).await
+    }
+
+    /// Download primary ebook of book.
+    pub async fn download_ebook(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<Download, FusedError<RequestError>> {
+        self.require(Permission::Download)?;
+        let url = self.config.library_item_sub_url(id.as_str(), &["ebook"]);
+        self.download(url).await
+    }
+
+    /// Download ebook file of library item, identified by its inode. Unlike
+    /// `download_ebook`, works for supplementary ebooks too.
+    pub async fn download_ebook_file(
+        &self,
+        id: &Id<LibraryItem>,
+        ino: &str,
+    ) -> Result<Download, FusedError<RequestError>> {
+        self.require(Permission::Download)?;
+        let url = self
+            .config
+            .library_item_sub_url(id.as_str(), &["ebook", ino]);
+        self.download(url).await
+    }
+
+    /// Download all files of library item, as zip archive.
+    pub async fn download_item(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<Download, FusedError<RequestError>> {
+        self.require(Permission::Download)?;
+        let url = self.config.library_item_sub_url(id.as_str(), &["download"]);
+        self.download(url).await
+    }
+
+    async fn download(&self, url: Url) -> Result<Download, FusedError<RequestError>> {
+        // No timeout, since downloading whole body may take long
+        let request_builder = self.client.get(url).bearer_auth(self.token());
+        self.download_with(request_builder).await
+    }
+
+    async fn download_with(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<Download, FusedError<RequestError>> {
+        let request = Self::build(request_builder)?;
+        let info = RequestInfo::of(&request);
+        let response = self
+            .execute(request)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(Download::from_response(info, response))
+    }
+
+    /// Cover image of library item, optionally resized by server.
+    pub async fn item_cover(
+        &self,
+        id: &Id<LibraryItem>,
+        params: CoverParams,
+    ) -> Result<Image, FusedError<RequestError>> {
+        let request_builder = self
+            .request(Method::GET, self.config.library_item_cover_url(id.as_str()))
+            .query(&params);
+        self.send_image(request_builder).await
+    }
+
+    /// Upload new cover image of library item, returning its path on server.
+    pub async fn upload_item_cover(
+        &self,
+        id: &Id<LibraryItem>,
+        file: UploadFile,
+    ) -> Result<String, FusedError<RequestError>> {
+        self.require(Permission::Upload)?;
+        let part = match file.source {
+            UploadSource::Bytes(data) => {
+                let length = data.len() as u64;
+                Part::stream_with_length(data, length)
+            }
+            UploadSource::Path(path) => {
+                let file = tokio::fs::File::open(&path)
+                    .await
+                    .map_err(|e| APIError::UnknownError(e.into()))?;
+                let length = file
+                    .metadata()
+                    .await
+                    .map_err(|e| APIError::UnknownError(e.into()))?
+                    .len();
+                Part::stream_with_length(
+                    reqwest::Body::wrap_stream(ReaderStream::new(file)),
+                    length,
+                )
+            }
+        };
+        let form = Form::new().part("cover", part.file_name(file.name));
+
+        // No timeout, since sending files may take long
+        let request_builder = self
+            .client
+            .post(self.config.library_item_cover_url(id.as_str()))
+            .bearer_auth(self.token())
+            .multipart(form);
+        let result = self
+            .send::<CoverUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.cover)
+    }
+
+    /// Make server download cover of library item from `url`, returning its path on server.
+    pub async fn set_item_cover_from_url(
+        &self,
+        id: &Id<LibraryItem>,
+        url: &str,
+    ) -> Result<String, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(&CoverUrlParams { url }).unwrap();
+        let request_builder = self
+            .request(
+                Method::POST,
+                self.config.library_item_cover_url(id.as_str()),
+            )
+            .body(body);
+
+        let result = self
+            .send::<CoverUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.cover)
+    }
+
+    /// Use image already on server as cover of library item, usually one of its
+    /// `library_files`, given by `FileMetadata::path`.
+    pub async fn set_item_cover_path(
+        &self,
+        id: &Id<LibraryItem>,
+        path: &str,
+    ) -> Result<String, FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let body = serde_json::to_string(&CoverPathParams { cover: path }).unwrap();
+        let request_builder = self
+            .request(
+                Method::PATCH,
+                self.config.library_item_cover_url(id.as_str()),
+            )
+            .body(body);
+
+        let result = self
+            .send::<CoverUpdate>(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(result.cover)
+    }
+
+    /// Remove cover of library item. Image file itself is kept on disk.
+    pub async fn remove_item_cover(
+        &self,
+        id: &Id<LibraryItem>,
+    ) -> Result<(), FusedError<RequestError>> {
+        self.require(Permission::Update)?;
+        let request_builder = self.request(
+            Method::DELETE,
+            self.config.library_item_cover_url(id.as_str()),
+        );
+
+        self.send_raw(request_builder)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        Ok(())
+    }
+
+    /// Image of author, scaled down by server to `width` if given.
+    pub async fn author_image(
+        &self,
+        id: &Id<Author>,
+        width: Option<u32>,
+    ) -> Result<Image, FusedError<RequestError>> {
+        let params = CoverParams {
+            width,
+            ..Default::default()
+        };
+        self.author_image_with(id, params).await
+    }
+
+    /// Image of author, optionally resized or converted by server.
+    pub async fn author_image_with(
+        &self,
+        id: &Id<Author>,
+        params: CoverParams,
+    ) -> Result<Image, FusedError<RequestError>> {
+        let request_builder = self
+            .request(
+                Method::GET,
+                self.config.author_url(id.as_str(), Some("image")),
+            )
+            .query(&params);
+        self.send_image(request_builder).await
+    }
+
+    async fn send_image(
+        &self,
+        request_builder: reqwest::RequestBuilder,
+    ) -> Result<Image, FusedError<RequestError>> {
+        let request = Self::build(request_builder)?;
+        let info = RequestInfo::of(&request);
+        let response = self
+            .execute(request)
+            .await
+            .map_err(FusedError::to_request_error)?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = response
+            .bytes()
+            .await
+            .map_err(|error| APIError::network(&info, error))?;
+        Ok(Image {
+            content_type,
+            data: data.to_vec(),
+        })
+    }
+
+    pub async fn audiofile_stream(
+        &self,
+        url: &str,
+    ) -> Result<StreamDownload<StreamStorageProvider>, FusedError<RequestError>> {
+        self.audiofile_stream_with(url, StreamOptions::default())
+            .await
+    }
+
+    /// Stream audio file, starting at `options.start` and resuming after connection loss.
+    /// Seeking to parts not downloaded yet requests them from server.
+    pub async fn audiofile_stream_with(
+        &self,
+        url: &str,
+        options: StreamOptions,
+    ) -> Result<StreamDownload<StreamStorageProvider>, FusedError<RequestError>> {
+        let mut headers = HeaderMap::new();
+        let header: HeaderValue = format!("Bearer {}", self.token()).parse().unwrap();
+        headers.insert("Authorization", header);
+        let client = self
+            .config
+            .http_client_builder()
+            .connect_timeout(
+                self.config
+                    .connect_timeout
+                    .unwrap_or(Duration::from_secs(5)),
+            )
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let url = self.build_abs_url(url).map_err(APIError::from)?;
+        let storage = StreamStorageProvider::new(options.storage, url.path());
+        let mut stream = ResumableStream::new(client, url, options.retry)
+            .await
+            .map_err(|e| APIError::UnknownError(e.into()))?;
+        if let Some(throttle) = options.throttle {
+            stream = stream.throttled(throttle);
+        }
+
+        let mut settings = Settings::default();
+        if let Some(callback) = options.on_progress {
+            settings = settings.on_progress(move |stream: &ResumableStream, state| {
+                callback(StreamProgress {
+                    position: state.current_position,
+                    content_length: stream.content_length(),
+                    complete: state.phase == StreamPhase::Complete,
+                })
+            });
+        }
+        let mut download = StreamDownload::from_stream(stream, storage, settings)
+            .await
+            .map_err(|e| APIError::UnknownError(e.into()))?;
+
+        if options.start > 0 {
+            // Seek waits for data to arrive, blocking the thread
+            download = tokio::task::spawn_blocking(move || {
+                download
+                    .seek(SeekFrom::Start(options.start))
+                    .map(|_| download)
+            })
+            .await
+            .map_err(|e| APIError::UnknownError(e.into()))?
+            .map_err(|e| APIError::UnknownError(e.into()))?;
+        }
 
         Ok(download)
     }
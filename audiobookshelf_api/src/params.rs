@@ -1,24 +1,301 @@
-use crate::schema::{Author, Id, Progress, Series};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::http_cache::fnv1a;
+use crate::schema::{
+    AudioFile, Author, Chapter, Codec, Collection, CustomMetadataProvider, Folder, Id, Library,
+    LibraryItem, LibraryMedia, MediaType, MimeType, NotificationEvent, PlayMethod, PlaylistItem,
+    PodcastEpisode, PodcastFeed, PodcastFeedEpisode, Progress, Seconds, Series, UserPermissions,
+    UserType,
+};
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
+use bytes::Bytes;
 use serde::Serialize;
 
 #[derive(Default, Debug, Clone)]
 pub struct LibraryItemParams {
     pub limit: usize,
     pub page: usize,
-    pub sort: Option<String>,
+    pub sort: Option<Sort>,
     pub desc: bool,
-    pub filter: LibraryItemFilter,
+    pub filter: Option<Filter>,
+    /// Replace books of same series with single item describing series
+    pub collapse_series: bool,
+    pub minified: bool,
+    /// Additional data included into items
+    pub include: Vec<LibraryItemInclude>,
 }
 
-#[derive(Default, Debug, Clone)]
-pub struct LibraryItemFilter {
-    pub authors: Vec<Id<Author>>,
-    pub series: Vec<Id<Series>>,
-    pub tags: Vec<String>,
-    pub genres: Vec<String>,
-    pub progress: Option<Progress>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LibraryItemInclude {
+    /// Open RSS feed of item, as `LibraryItemMinified::rss_feed`
+    RssFeed,
+    /// Number of unfinished podcast episodes, as `LibraryItemMinified::num_episodes_incomplete`
+    NumEpisodesIncomplete,
+}
+
+impl LibraryItemInclude {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RssFeed => "rssfeed",
+            Self::NumEpisodesIncomplete => "numEpisodesIncomplete",
+        }
+    }
+}
+
+/// Order of library items.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sort {
+    Title,
+    AuthorName,
+    /// Author name in "Last, First" form
+    AuthorNameLastFirst,
+    PublishedYear,
+    AddedAt,
+    UpdatedAt,
+    Size,
+    Duration,
+    NumTracks,
+    /// File birth time
+    CreatedAt,
+    /// File modification time
+    ModifiedAt,
+    Progress,
+    Random,
+    /// Position in series, only with `Filter::Series`
+    Sequence,
+    /// Field not listed here, by its server name. Series are sorted by `name`, `numBooks`,
+    /// `totalDuration`, `addedAt` or `lastBookAdded`.
+    Other(String),
+}
+
+impl Sort {
+    /// Sort of given server name, inverse of `as_str`.
+    pub fn parse(sort: &str) -> Self {
+        [
+            Self::Title,
+            Self::AuthorName,
+            Self::AuthorNameLastFirst,
+            Self::PublishedYear,
+            Self::AddedAt,
+            Self::UpdatedAt,
+            Self::Size,
+            Self::Duration,
+            Self::NumTracks,
+            Self::CreatedAt,
+            Self::ModifiedAt,
+            Self::Progress,
+            Self::Random,
+            Self::Sequence,
+        ]
+        .into_iter()
+        .find(|known| known.as_str() == sort)
+        .unwrap_or_else(|| Self::Other(sort.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Title => "media.metadata.title",
+            Self::AuthorName => "media.metadata.authorName",
+            Self::AuthorNameLastFirst => "media.metadata.authorNameLF",
+            Self::PublishedYear => "media.metadata.publishedYear",
+            Self::AddedAt => "addedAt",
+            Self::UpdatedAt => "updatedAt",
+            Self::Size => "size",
+            Self::Duration => "media.duration",
+            Self::NumTracks => "media.numTracks",
+            Self::CreatedAt => "birthtimeMs",
+            Self::ModifiedAt => "mtimeMs",
+            Self::Progress => "progress",
+            Self::Random => "random",
+            Self::Sequence => "sequence",
+            Self::Other(sort) => sort,
+        }
+    }
+}
+
+/// Condition library items should satisfy. Server supports single filter per request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Authors(Id<Author>),
+    Series(Id<Series>),
+    /// Books not belonging to any series
+    NoSeries,
+    Tags(String),
+    Genres(String),
+    Narrators(String),
+    Publishers(String),
+    Languages(String),
+    Progress(Progress),
+    /// Books with given metadata field missing
+    Missing(MissingField),
+    Tracks(TrackCount),
+    Ebooks(EbookFilter),
+    Abridged,
+    /// Items with missing or invalid files
+    Issues,
+    /// Items with open RSS feed
+    FeedOpen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingField {
+    Asin,
+    Isbn,
+    Subtitle,
+    Authors,
+    PublishedYear,
+    Series,
+    Description,
+    Genres,
+    Tags,
+    Narrators,
+    Publisher,
+    Language,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackCount {
+    Single,
+    Multi,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EbookFilter {
+    Ebook,
+    NoEbook,
+    Supplementary,
+    NoSupplementary,
+}
+
+impl Filter {
+    /// Value of `filter` query parameter, like `genres.<base64 of genre>`.
+    pub fn encode(&self) -> String {
+        let (group, value) = match self {
+            Self::Authors(id) => ("authors", id.as_str()),
+            Self::Series(id) => ("series", id.as_str()),
+            Self::NoSeries => ("series", "no-series"),
+            Self::Tags(tag) => ("tags", tag.as_str()),
+            Self::Genres(genre) => ("genres", genre.as_str()),
+            Self::Narrators(narrator) => ("narrators", narrator.as_str()),
+            Self::Publishers(publisher) => ("publishers", publisher.as_str()),
+            Self::Languages(language) => ("languages", language.as_str()),
+            Self::Progress(progress) => ("progress", progress.as_str()),
+            Self::Missing(field) => ("missing", field.as_str()),
+            Self::Tracks(tracks) => ("tracks", tracks.as_str()),
+            Self::Ebooks(ebooks) => ("ebooks", ebooks.as_str()),
+            Self::Abridged => ("abridged", "abridged"),
+            Self::Issues => return "issues".into(),
+            Self::FeedOpen => return "feed-open".into(),
+        };
+        format!("{group}.{b64value}", b64value = STANDARD.encode(value))
+    }
+
+    /// Filter encoded with `encode`.
+    pub fn decode(filter: &str) -> Option<Self> {
+        match filter {
+            "issues" => return Some(Self::Issues),
+            "feed-open" => return Some(Self::FeedOpen),
+            _ => {}
+        }
+        let (group, b64value) = filter.split_once('.')?;
+        let value = String::from_utf8(STANDARD.decode(b64value).ok()?).ok()?;
+        let filter = match group {
+            "authors" => Self::Authors(Id::new(value)),
+            "series" if value == "no-series" => Self::NoSeries,
+            "series" => Self::Series(Id::new(value)),
+            "tags" => Self::Tags(value),
+            "genres" => Self::Genres(value),
+            "narrators" => Self::Narrators(value),
+            "publishers" => Self::Publishers(value),
+            "languages" => Self::Languages(value),
+            "progress" => Self::Progress(Progress::parse(&value)?),
+            "missing" => Self::Missing(MissingField::parse(&value)?),
+            "tracks" => Self::Tracks(TrackCount::parse(&value)?),
+            "ebooks" => Self::Ebooks(EbookFilter::parse(&value)?),
+            "abridged" => Self::Abridged,
+            _ => return None,
+        };
+        Some(filter)
+    }
+}
+
+impl MissingField {
+    pub fn parse(field: &str) -> Option<Self> {
+        [
+            Self::Asin,
+            Self::Isbn,
+            Self::Subtitle,
+            Self::Authors,
+            Self::PublishedYear,
+            Self::Series,
+            Self::Description,
+            Self::Genres,
+            Self::Tags,
+            Self::Narrators,
+            Self::Publisher,
+            Self::Language,
+        ]
+        .into_iter()
+        .find(|known| known.as_str() == field)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asin => "asin",
+            Self::Isbn => "isbn",
+            Self::Subtitle => "subtitle",
+            Self::Authors => "authors",
+            Self::PublishedYear => "publishedYear",
+            Self::Series => "series",
+            Self::Description => "description",
+            Self::Genres => "genres",
+            Self::Tags => "tags",
+            Self::Narrators => "narrators",
+            Self::Publisher => "publisher",
+            Self::Language => "language",
+        }
+    }
+}
+
+impl TrackCount {
+    pub fn parse(tracks: &str) -> Option<Self> {
+        [Self::Single, Self::Multi]
+            .into_iter()
+            .find(|known| known.as_str() == tracks)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Multi => "multi",
+        }
+    }
+}
+
+impl EbookFilter {
+    pub fn parse(ebooks: &str) -> Option<Self> {
+        [
+            Self::Ebook,
+            Self::NoEbook,
+            Self::Supplementary,
+            Self::NoSupplementary,
+        ]
+        .into_iter()
+        .find(|known| known.as_str() == ebooks)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ebook => "ebook",
+            Self::NoEbook => "no-ebook",
+            Self::Supplementary => "supplementary",
+            Self::NoSupplementary => "no-supplementary",
+        }
+    }
 }
 
 impl LibraryItemParams {
@@ -29,46 +306,120 @@ impl LibraryItemParams {
             result.push(("page", self.page.to_string()));
         }
         if let Some(sort) = self.sort {
-            result.push(("sort", sort));
+            result.push(("sort", sort.as_str().to_string()));
         }
         result.push(("desc", self.desc.to_string()));
-
-        for author in &self.filter.authors {
-            Self::add_filter(&mut result, "authors", author.as_str());
-        }
-        for series in &self.filter.series {
-            Self::add_filter(&mut result, "series", series.as_str());
+        if let Some(filter) = self.filter {
+            result.push(("filter", filter.encode()));
         }
-        for tag in &self.filter.tags {
-            Self::add_filter(&mut result, "tags", tag.as_str());
+        if self.collapse_series {
+            result.push(("collapseseries", "1".into()));
         }
-        for genre in &self.filter.genres {
-            Self::add_filter(&mut result, "genres", genre.as_str());
+        if self.minified {
+            result.push(("minified", "1".into()));
         }
-        if let Some(progress) = self.filter.progress {
-            Self::add_filter(&mut result, "progress", progress.as_str());
+        if !self.include.is_empty() {
+            let include: Vec<_> = self
+                .include
+                .iter()
+                .map(|include| include.as_str())
+                .collect();
+            result.push(("include", include.join(",")));
         }
         result
     }
-
-    fn add_filter(query: &mut Vec<(&'static str, String)>, filter: &str, value: &str) {
-        query.push((
-            "filter",
-            format!("{filter}.{b64value}", b64value = STANDARD.encode(value)),
-        ));
-    }
 }
 
-#[derive(Serialize, Debug, Clone, Default)]
+/// Body of `POST /api/items/<ID>/play`. Prefer `PlayLibraryItemParams::builder`, which
+/// fills in device info of this machine.
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayLibraryItemParams {
     pub device_info: DeviceInfoParams,
     pub force_direct_play: bool,
     pub force_transcode: bool,
-    pub supported_mime_types: Vec<String>,
+    /// Types client can play directly, usually `MimeType::supported` of its decoders.
+    /// Server transcodes everything if empty.
+    pub supported_mime_types: Vec<MimeType>,
     pub media_player: Option<String>,
 }
 
+/// Decoders assumed by default, covering common audiobook formats.
+const DEFAULT_DECODERS: &[Codec] = &[
+    Codec::Mp3,
+    Codec::Aac,
+    Codec::Flac,
+    Codec::Vorbis,
+    Codec::Opus,
+];
+
+impl Default for PlayLibraryItemParams {
+    fn default() -> Self {
+        Self {
+            device_info: DeviceInfoParams::default(),
+            force_direct_play: false,
+            force_transcode: false,
+            supported_mime_types: MimeType::supported(DEFAULT_DECODERS),
+            media_player: None,
+        }
+    }
+}
+
+impl PlayLibraryItemParams {
+    /// Params with detected device info, mime types of mp3, aac, flac, vorbis and opus,
+    /// letting server choose between direct play and transcode.
+    pub fn builder() -> PlayLibraryItemParamsBuilder {
+        PlayLibraryItemParamsBuilder {
+            params: Self {
+                device_info: DeviceInfoParams::detect(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayLibraryItemParamsBuilder {
+    params: PlayLibraryItemParams,
+}
+
+impl PlayLibraryItemParamsBuilder {
+    /// Always serve original files, even of types not listed as supported.
+    pub fn direct_play(mut self) -> Self {
+        self.params.force_direct_play = true;
+        self.params.force_transcode = false;
+        self
+    }
+
+    /// Always transcode into HLS stream.
+    pub fn transcode(mut self) -> Self {
+        self.params.force_transcode = true;
+        self.params.force_direct_play = false;
+        self
+    }
+
+    pub fn device(mut self, device_info: DeviceInfoParams) -> Self {
+        self.params.device_info = device_info;
+        self
+    }
+
+    /// Replace default mime types, e.g. with `MimeType::supported` of available decoders.
+    pub fn mime_types(mut self, mime_types: impl IntoIterator<Item = MimeType>) -> Self {
+        self.params.supported_mime_types = mime_types.into_iter().collect();
+        self
+    }
+
+    /// Name of player shown in server's session list.
+    pub fn media_player(mut self, media_player: impl Into<String>) -> Self {
+        self.params.media_player = Some(media_player.into());
+        self
+    }
+
+    pub fn build(self) -> PlayLibraryItemParams {
+        self.params
+    }
+}
+
 #[derive(Serialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceInfoParams {
@@ -79,3 +430,1341 @@ pub struct DeviceInfoParams {
     pub model: Option<String>,
     pub sdk_version: Option<u64>,
 }
+
+impl DeviceInfoParams {
+    /// Device info of this machine. Client is reported as this crate, unless overridden
+    /// with `with_client`.
+    ///
+    /// Device id is derived from machine id, so it stays the same between runs,
+    /// without exposing machine id itself.
+    pub fn detect() -> Self {
+        let (manufacturer, model) = detect_hardware();
+        Self {
+            device_id: detect_machine_id().map(|id| format!("{:016x}", fnv1a(&id))),
+            client_name: Some(env!("CARGO_PKG_NAME").to_string()),
+            client_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            manufacturer,
+            model,
+            sdk_version: None,
+        }
+    }
+
+    /// Report client as `name` of `version`, e.g. `env!("CARGO_PKG_NAME")` and
+    /// `env!("CARGO_PKG_VERSION")` of application.
+    pub fn with_client(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.client_name = Some(name.into());
+        self.client_version = Some(version.into());
+        self
+    }
+}
+
+fn detect_machine_id() -> Option<String> {
+    [
+        "/etc/machine-id",
+        "/var/lib/dbus/machine-id",
+        "/etc/hostname",
+    ]
+    .iter()
+    .filter_map(|path| std::fs::read_to_string(path).ok())
+    .map(|id| id.trim().to_string())
+    .find(|id| !id.is_empty())
+}
+
+/// Vendor and product name from DMI where available, operating system and architecture
+/// otherwise.
+fn detect_hardware() -> (Option<String>, Option<String>) {
+    let read = |path: &str| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let manufacturer =
+        read("/sys/class/dmi/id/sys_vendor").unwrap_or_else(|| std::env::consts::OS.to_string());
+    let model = read("/sys/class/dmi/id/product_name")
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string());
+    (Some(manufacturer), Some(model))
+}
+
+/// Body of `PATCH /api/me/progress/<ID>`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaProgressParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_time: Option<Seconds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Seconds>,
+    /// Fraction of media listened, from 0 to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_finished: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hide_from_continue_listening: Option<bool>,
+    /// Reader position in ebook, such as EPUB CFI or PDF page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ebook_location: Option<String>,
+    /// Fraction of ebook read, from 0 to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ebook_progress: Option<f64>,
+}
+
+impl MediaProgressParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn current_time(mut self, current_time: impl Into<Seconds>) -> Self {
+        self.current_time = Some(current_time.into());
+        self
+    }
+
+    pub fn duration(mut self, duration: impl Into<Seconds>) -> Self {
+        self.duration = Some(duration.into());
+        self
+    }
+
+    pub fn progress(mut self, progress: f64) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn is_finished(mut self, is_finished: bool) -> Self {
+        self.is_finished = Some(is_finished);
+        self
+    }
+
+    pub fn hide_from_continue_listening(mut self, hide: bool) -> Self {
+        self.hide_from_continue_listening = Some(hide);
+        self
+    }
+
+    pub fn ebook_location(mut self, location: impl Into<String>) -> Self {
+        self.ebook_location = Some(location.into());
+        self
+    }
+
+    pub fn ebook_progress(mut self, progress: f64) -> Self {
+        self.ebook_progress = Some(progress);
+        self
+    }
+}
+
+/// Element of `PATCH /api/me/progress/batch/update` body.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchMediaProgressParams {
+    pub library_item_id: Id<LibraryItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode_id: Option<Id<PodcastEpisode>>,
+    #[serde(flatten)]
+    pub progress: MediaProgressParams,
+}
+
+impl BatchMediaProgressParams {
+    pub fn item(id: Id<LibraryItem>, progress: MediaProgressParams) -> Self {
+        Self {
+            library_item_id: id,
+            episode_id: None,
+            progress,
+        }
+    }
+
+    pub fn episode(
+        id: Id<LibraryItem>,
+        episode_id: Id<PodcastEpisode>,
+        progress: MediaProgressParams,
+    ) -> Self {
+        Self {
+            library_item_id: id,
+            episode_id: Some(episode_id),
+            progress,
+        }
+    }
+}
+
+/// Body of `POST /api/session/<ID>/sync`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSessionParams {
+    pub current_time: Seconds,
+    /// Time listened since previous sync
+    pub time_listened: Seconds,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Seconds>,
+}
+
+/// Session played from downloaded files, body of `POST /api/session/local`. Server creates
+/// session with given `id`, or updates it if it exists, and applies position to media
+/// progress unless progress was updated later.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalSessionParams {
+    /// Generated by client, same for all reports of one session
+    pub id: String,
+    pub library_id: Id<Library>,
+    pub library_item_id: Id<LibraryItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode_id: Option<Id<PodcastEpisode>>,
+    pub media_type: MediaType,
+    pub display_title: String,
+    pub display_author: String,
+    pub duration: Seconds,
+    pub play_method: PlayMethod,
+    pub media_player: String,
+    pub device_info: DeviceInfoParams,
+    /// Local date session was started, as `YYYY-MM-DD`
+    pub date: String,
+    pub day_of_week: String,
+    pub time_listening: Seconds,
+    pub start_time: Seconds,
+    pub current_time: Seconds,
+    #[serde(serialize_with = "timestamp")]
+    pub started_at: DateTime<Utc>,
+    #[serde(serialize_with = "timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LocalSessionParams {
+    /// Session starting now at `start_time` of book, or of `episode` of podcast. Device is
+    /// detected as with `DeviceInfoParams::detect`.
+    pub fn new(item: &LibraryItem, episode: Option<&PodcastEpisode>, start_time: Seconds) -> Self {
+        let (media_type, display_title, display_author, duration) = match (&item.media, episode) {
+            (
+                LibraryMedia::Book {
+                    metadata,
+                    audio_files,
+                    ..
+                },
+                _,
+            ) => {
+                let authors: Vec<&str> = metadata
+                    .authors
+                    .iter()
+                    .map(|author| author.name.as_str())
+                    .collect();
+                let duration = audio_files
+                    .iter()
+                    .filter(|file| !file.exclude)
+                    .map(|file| file.duration)
+                    .sum();
+                (
+                    MediaType::Book,
+                    metadata.title.clone().unwrap_or_default(),
+                    authors.join(", "),
+                    duration,
+                )
+            }
+            (LibraryMedia::Podcast { metadata, .. }, episode) => (
+                MediaType::Podcast,
+                episode.map_or_else(
+                    || metadata.title.clone().unwrap_or_default(),
+                    |episode| episode.title.clone(),
+                ),
+                metadata.author.clone().unwrap_or_default(),
+                episode.map_or(0.0, |episode| episode.audio_file.duration),
+            ),
+        };
+        let now = Utc::now();
+        let local = chrono::Local::now();
+        Self {
+            id: local_session_id(&now),
+            library_id: item.library_id.clone(),
+            library_item_id: item.id.clone(),
+            episode_id: episode.map(|episode| episode.id.clone()),
+            media_type,
+            display_title,
+            display_author,
+            duration: Seconds(duration),
+            play_method: PlayMethod::Local,
+            media_player: env!("CARGO_PKG_NAME").to_string(),
+            device_info: DeviceInfoParams::detect(),
+            date: local.format("%Y-%m-%d").to_string(),
+            day_of_week: local.format("%A").to_string(),
+            time_listening: Seconds::ZERO,
+            start_time,
+            current_time: start_time,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Record playback reaching `current_time`, after listening for `time_listened` since
+    /// previous update.
+    pub fn listened(&mut self, current_time: Seconds, time_listened: Seconds) {
+        self.current_time = current_time;
+        self.time_listening = self.time_listening + time_listened;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Body of `POST /api/session/local-all`
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct LocalSessionsParams<'a> {
+    pub sessions: &'a [LocalSessionParams],
+}
+
+/// Id unique to this device, as sessions of all devices share id space on server.
+fn local_session_id(now: &DateTime<Utc>) -> String {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let device = detect_machine_id().map_or(0, |id| fnv1a(&id));
+    format!(
+        "play_local_{device:016x}{:x}{count:x}",
+        now.timestamp_nanos_opt().unwrap_or_default()
+    )
+}
+
+fn timestamp<S: serde::Serializer>(
+    datetime: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(datetime.timestamp_millis())
+}
+
+/// Body of `POST /api/me/item/<ID>/bookmark` and `PATCH /api/me/item/<ID>/bookmark`.
+/// Bookmark to update is identified by its `time`.
+#[derive(Serialize, Debug, Clone)]
+pub struct BookmarkParams {
+    /// Position in seconds
+    pub time: f64,
+    pub title: String,
+}
+
+/// Query of `GET /api/items/<ID>/cover` and `GET /api/authors/<ID>/image`
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CoverParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<CoverFormat>,
+    /// Return original file, ignoring other options
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub raw: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverFormat {
+    Jpeg,
+    Webp,
+}
+
+impl CoverParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn height(mut self, height: u32) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn format(mut self, format: CoverFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+}
+
+/// Body of `POST /api/collections`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCollectionParams {
+    pub library_id: Id<Library>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub books: Vec<Id<LibraryItem>>,
+}
+
+/// Body of `PATCH /api/collections/<ID>`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCollectionParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// New order or set of books
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub books: Option<Vec<Id<LibraryItem>>>,
+}
+
+/// Body of `POST /api/playlists`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePlaylistParams {
+    pub library_id: Id<Library>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub items: Vec<PlaylistItem>,
+}
+
+/// Body of `PATCH /api/playlists/<ID>`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePlaylistParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// New order or set of items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<PlaylistItem>>,
+}
+
+/// Body of `POST /api/playlists/<ID>/batch/add` and `.../batch/remove`
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct PlaylistItemsParams<'a> {
+    pub items: &'a [PlaylistItem],
+}
+
+/// Body of `POST /api/podcasts/feed`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PodcastFeedParams<'a> {
+    pub rss_feed: &'a str,
+}
+
+/// Body of `POST /api/podcasts`. Podcast files are stored in `path`, which has to be
+/// inside of folder.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePodcastParams {
+    pub path: String,
+    pub folder_id: Id<Folder>,
+    pub library_id: Id<Library>,
+    pub media: CreatePodcastMedia,
+    /// Episodes of feed to download right after creation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub episodes_to_download: Vec<PodcastFeedEpisode>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePodcastMedia {
+    pub metadata: CreatePodcastMetadata,
+    pub auto_download_episodes: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePodcastMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub release_date: Option<String>,
+    pub genres: Vec<String>,
+    pub feed_url: Option<String>,
+    pub image_url: Option<String>,
+    pub language: Option<String>,
+    pub explicit: bool,
+    /// `episodic` or `serial`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+}
+
+impl CreatePodcastParams {
+    /// Podcast described by `feed`, as returned by `UserClient::podcast_feed`.
+    pub fn from_feed(
+        feed: &PodcastFeed,
+        library_id: Id<Library>,
+        folder_id: Id<Folder>,
+        path: impl Into<String>,
+    ) -> Self {
+        let metadata = &feed.metadata;
+        let explicit = metadata
+            .explicit
+            .as_deref()
+            .is_some_and(|explicit| matches!(explicit, "true" | "yes"));
+        Self {
+            path: path.into(),
+            folder_id,
+            library_id,
+            media: CreatePodcastMedia {
+                metadata: CreatePodcastMetadata {
+                    title: metadata.title.clone(),
+                    author: metadata.author.clone(),
+                    description: metadata.description.clone(),
+                    release_date: metadata.pub_date.clone(),
+                    genres: metadata.categories.clone(),
+                    feed_url: metadata.feed_url.clone(),
+                    image_url: metadata.image.clone(),
+                    language: metadata.language.clone(),
+                    explicit,
+                    type_: metadata.type_.clone(),
+                },
+                auto_download_episodes: false,
+            },
+            episodes_to_download: vec![],
+        }
+    }
+}
+
+/// Body of `PATCH /api/podcasts/<ID>/episode/<ID>`. Fields left as `None` are not
+/// changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEpisodeParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Publication date as written in RSS feed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pub_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub season: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode: Option<String>,
+    /// `full`, `trailer` or `bonus`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode_type: Option<String>,
+}
+
+/// Body of `POST /api/podcasts/opml/parse`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OpmlParseParams<'a> {
+    pub opml_text: &'a str,
+}
+
+/// Body of `POST /api/podcasts/opml/create`. Podcasts are stored in folder under their
+/// titles.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpmlCreateParams {
+    /// Feed urls, such as `OpmlFeed::feed_url`
+    pub feeds: Vec<String>,
+    pub library_id: Id<Library>,
+    pub folder_id: Id<Folder>,
+    pub auto_download_episodes: bool,
+}
+
+/// Body of `PATCH /api/items/<ID>/media`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMediaParams {
+    pub metadata: UpdateMediaMetadataParams,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chapters: Option<Vec<Chapter>>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMediaMetadataParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<AuthorParams>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub narrators: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<Vec<SeriesParams>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genres: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explicit: Option<bool>,
+}
+
+/// Author of book. Server creates author, if there is none with such name.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Id<Author>>,
+    pub name: String,
+}
+
+/// Series of book. Server creates series, if there is none with such name.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Id<Series>>,
+    pub name: String,
+    /// Position of book in series, e.g. `1` or `2.5`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<String>,
+}
+
+impl UpdateMediaParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.metadata.title = Some(title.into());
+        self
+    }
+
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.metadata.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Replace authors with ones with given names.
+    pub fn authors<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        let authors = names.into_iter().map(|name| AuthorParams {
+            id: None,
+            name: name.into(),
+        });
+        self.metadata.authors = Some(authors.collect());
+        self
+    }
+
+    pub fn narrators<S: Into<String>>(mut self, narrators: impl IntoIterator<Item = S>) -> Self {
+        self.metadata.narrators = Some(narrators.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Replace series with ones with given names and sequences.
+    pub fn series<S: Into<String>>(
+        mut self,
+        series: impl IntoIterator<Item = (S, Option<S>)>,
+    ) -> Self {
+        let series = series.into_iter().map(|(name, sequence)| SeriesParams {
+            id: None,
+            name: name.into(),
+            sequence: sequence.map(Into::into),
+        });
+        self.metadata.series = Some(series.collect());
+        self
+    }
+
+    pub fn genres<S: Into<String>>(mut self, genres: impl IntoIterator<Item = S>) -> Self {
+        self.metadata.genres = Some(genres.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.metadata.description = Some(description.into());
+        self
+    }
+
+    pub fn tags<S: Into<String>>(mut self, tags: impl IntoIterator<Item = S>) -> Self {
+        self.tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn chapters(mut self, chapters: Vec<Chapter>) -> Self {
+        self.chapters = Some(chapters);
+        self
+    }
+}
+
+/// Source of book metadata used for matching.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum MetadataProvider {
+    #[default]
+    Google,
+    OpenLibrary,
+    ITunes,
+    Audible,
+    /// Audible of specific region, e.g. `uk` or `de`
+    AudibleRegion(String),
+    FantLab,
+    /// Provider added with `AdminClient::create_custom_metadata_provider`
+    Custom(Id<CustomMetadataProvider>),
+    /// Provider not listed here, by its server name
+    Other(String),
+}
+
+impl MetadataProvider {
+    pub fn as_str(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            Self::Google => "google".into(),
+            Self::OpenLibrary => "openlibrary".into(),
+            Self::ITunes => "itunes".into(),
+            Self::Audible => "audible".into(),
+            Self::AudibleRegion(region) => format!("audible.{region}").into(),
+            Self::FantLab => "fantlab".into(),
+            Self::Custom(id) => format!("custom-{id}").into(),
+            Self::Other(name) => name.as_str().into(),
+        }
+    }
+}
+
+impl Serialize for MetadataProvider {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_str())
+    }
+}
+
+/// Body of `POST /api/custom-metadata-providers`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCustomMetadataProviderParams {
+    pub name: String,
+    /// Base url of provider API
+    pub url: String,
+    pub media_type: MediaType,
+    /// Value of `Authorization` header sent to provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_header_value: Option<String>,
+}
+
+/// Query of `GET /api/search/books` and `GET /api/search/covers`
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BookSearchParams {
+    pub provider: MetadataProvider,
+    /// Title, or ISBN or ASIN of book. Providers recognize and search by identifiers.
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+impl BookSearchParams {
+    pub fn by_title(provider: MetadataProvider, title: impl Into<String>) -> Self {
+        Self {
+            provider,
+            title: title.into(),
+            author: None,
+        }
+    }
+
+    /// Search by ISBN, or ASIN for Audible providers.
+    pub fn by_id(provider: MetadataProvider, id: impl Into<String>) -> Self {
+        Self::by_title(provider, id)
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+}
+
+/// Body of `POST /api/items/<ID>/chapters`
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ChaptersParams<'a> {
+    pub chapters: &'a [Chapter],
+}
+
+/// Position of audio file in `update_item_tracks`.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackOrder {
+    pub ino: String,
+    /// Leave file out of tracks, without deleting it
+    pub exclude: bool,
+}
+
+impl From<&AudioFile> for TrackOrder {
+    fn from(file: &AudioFile) -> Self {
+        Self {
+            ino: file.ino.clone(),
+            exclude: file.exclude,
+        }
+    }
+}
+
+/// Body of `PATCH /api/items/<ID>/tracks`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TracksParams<'a> {
+    pub ordered_file_data: &'a [TrackOrder],
+}
+
+/// Body of `POST /api/items/<ID>/match`
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchParams {
+    pub provider: MetadataProvider,
+    /// Search by, instead of current title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asin: Option<String>,
+    #[serde(flatten)]
+    pub options: MatchOptions,
+}
+
+/// How match result is applied. By default only missing details are filled.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchOptions {
+    pub override_cover: bool,
+    pub override_details: bool,
+}
+
+/// Body of `POST /api/items/batch/get` and `POST /api/items/batch/delete`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchItemsParams<'a> {
+    pub library_item_ids: &'a [Id<LibraryItem>],
+}
+
+/// Element of `POST /api/items/batch/update` body: media changes of single item.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateParams {
+    pub id: Id<LibraryItem>,
+    pub media_payload: UpdateMediaParams,
+}
+
+impl BatchUpdateParams {
+    pub fn new(id: Id<LibraryItem>, media_payload: UpdateMediaParams) -> Self {
+        Self { id, media_payload }
+    }
+}
+
+/// Body of `POST /api/items/batch/quickmatch`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchQuickMatchParams<'a> {
+    pub library_item_ids: &'a [Id<LibraryItem>],
+    pub options: BatchQuickMatchOptions<'a>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchQuickMatchOptions<'a> {
+    pub provider: &'a MetadataProvider,
+    #[serde(flatten)]
+    pub options: &'a MatchOptions,
+}
+
+/// Body of `PATCH /api/authors/<ID>`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAuthorParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asin: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Local path or url of image. Empty string removes image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>,
+}
+
+/// Body of `POST /api/authors/<ID>/match`
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchAuthorParams {
+    /// Name to search by
+    #[serde(rename = "q", skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asin: Option<String>,
+    /// Audible region, e.g. `us`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// Body of `POST /api/users`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateUserParams {
+    pub username: String,
+    pub password: String,
+    #[serde(rename = "type")]
+    pub type_: UserType,
+    pub is_active: bool,
+    /// Server defaults are used if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<UserPermissions>,
+    pub libraries_accessible: Vec<Id<Library>>,
+    pub item_tags_selected: Vec<String>,
+}
+
+impl CreateUserParams {
+    /// Active user with default permissions.
+    pub fn new(username: impl Into<String>, password: impl Into<String>, type_: UserType) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+            type_,
+            is_active: true,
+            permissions: None,
+            libraries_accessible: vec![],
+            item_tags_selected: vec![],
+        }
+    }
+}
+
+/// Body of `PATCH /api/users/<ID>`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUserParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<UserType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<UserPermissions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub libraries_accessible: Option<Vec<Id<Library>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_tags_selected: Option<Vec<String>>,
+}
+
+/// Body of `PATCH /api/settings`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateServerSettingsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanner_find_covers: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanner_cover_provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanner_parse_subtitle: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanner_prefer_matched_metadata: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanner_disable_watcher: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_cover_with_item: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store_metadata_with_item: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_login_requests: Option<u32>,
+    /// Milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_login_window: Option<u64>,
+    /// Cron expression. `Some(None)` disables automatic backups.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "backup_schedule"
+    )]
+    pub backup_schedule: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backups_to_keep: Option<u32>,
+    /// Gigabytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_backup_size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger_daily_logs_to_keep: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger_scanner_logs_to_keep: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sorting_ignore_prefix: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sorting_prefixes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chromecast_enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<u32>,
+}
+
+/// Body of `POST /api/share/mediaitem`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateShareParams {
+    /// Last part of share link, unique among shares
+    pub slug: String,
+    /// Share never expires if `None`
+    #[serde(serialize_with = "expires_at")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub media_item_type: String,
+    /// Id of book, as opposed to its library item
+    pub media_item_id: String,
+    /// Whether listeners are allowed to download book files
+    pub is_downloadable: bool,
+}
+
+impl CreateShareParams {
+    /// Share of book with given id, never expiring.
+    pub fn new(media_item_id: impl Into<String>, slug: impl Into<String>) -> Self {
+        Self {
+            slug: slug.into(),
+            expires_at: None,
+            media_item_type: "book".to_string(),
+            media_item_id: media_item_id.into(),
+            is_downloadable: false,
+        }
+    }
+
+    /// Share of book of library item, if server sent its book id.
+    pub fn for_item(item: &LibraryItem, slug: impl Into<String>) -> Option<Self> {
+        match &item.media {
+            LibraryMedia::Book { id: Some(id), .. } => Some(Self::new(id.clone(), slug)),
+            _ => None,
+        }
+    }
+
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn downloadable(mut self) -> Self {
+        self.is_downloadable = true;
+        self
+    }
+}
+
+/// Expiration is sent as milliseconds since epoch, with `0` meaning never.
+fn expires_at<S: serde::Serializer>(
+    expires_at: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match expires_at {
+        Some(expires_at) => serializer.serialize_i64(expires_at.timestamp_millis()),
+        None => serializer.serialize_i64(0),
+    }
+}
+
+/// Disabled backup schedule is sent as `false`.
+fn backup_schedule<S: serde::Serializer>(
+    schedule: &Option<Option<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match schedule {
+        Some(Some(cron)) => serializer.serialize_str(cron),
+        _ => serializer.serialize_bool(false),
+    }
+}
+
+/// Entity RSS feed is opened for
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedEntity {
+    Item(Id<LibraryItem>),
+    Collection(Id<Collection>),
+    Series(Id<Series>),
+}
+
+impl FeedEntity {
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            Self::Item(_) => "item",
+            Self::Collection(_) => "collection",
+            Self::Series(_) => "series",
+        }
+    }
+
+    pub fn id_str(&self) -> &str {
+        match self {
+            Self::Item(id) => id.as_str(),
+            Self::Collection(id) => id.as_str(),
+            Self::Series(id) => id.as_str(),
+        }
+    }
+}
+
+/// Options of opened RSS feed. Serialized as its `metadataDetails`.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenFeedParams {
+    /// Part of feed url, must be unique among open feeds
+    #[serde(skip)]
+    pub slug: String,
+    pub prevent_indexing: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_email: Option<String>,
+}
+
+impl OpenFeedParams {
+    pub fn new(slug: impl Into<String>) -> Self {
+        Self {
+            slug: slug.into(),
+            prevent_indexing: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Body of `POST /api/feeds/<TYPE>/<ID>/open`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OpenFeedBody<'a> {
+    pub server_address: &'a str,
+    pub slug: &'a str,
+    pub metadata_details: &'a OpenFeedParams,
+}
+
+/// Body of `PATCH /api/notifications`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNotificationSettingsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apprise_api_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_failed_attempts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_notification_queue: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification_delay: Option<u64>,
+}
+
+/// Body of `POST /api/notifications`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNotificationParams {
+    /// Only send notifications of events in this library
+    pub library_id: Option<Id<Library>>,
+    pub event_name: NotificationEvent,
+    /// Apprise urls
+    pub urls: Vec<String>,
+    pub title_template: String,
+    pub body_template: String,
+    pub enabled: bool,
+}
+
+/// Change of path in library folder, reported to server file watcher.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchedPathChange {
+    Added(String),
+    Removed(String),
+    Renamed { from: String, to: String },
+}
+
+/// Body of `POST /api/watcher/update`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WatcherUpdateParams<'a> {
+    pub library_id: &'a Id<Library>,
+    pub path: &'a str,
+    #[serde(rename = "type")]
+    pub type_: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<&'a str>,
+}
+
+impl<'a> WatcherUpdateParams<'a> {
+    pub fn new(library_id: &'a Id<Library>, change: &'a WatchedPathChange) -> Self {
+        let (path, type_, old_path) = match change {
+            WatchedPathChange::Added(path) => (path, "add", None),
+            WatchedPathChange::Removed(path) => (path, "unlink", None),
+            WatchedPathChange::Renamed { from, to } => (to, "rename", Some(from.as_str())),
+        };
+        Self {
+            library_id,
+            path,
+            type_,
+            old_path,
+        }
+    }
+}
+
+/// Body of `PATCH /api/notifications/<ID>`. Fields left as `None` are not changed.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNotificationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_id: Option<Option<Id<Library>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_name: Option<NotificationEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub urls: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+/// Query of `POST /api/tools/item/<ID>/embed-metadata`
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedMetadataParams {
+    /// Replace chapters already embedded into files
+    #[serde(skip_serializing_if = "std::ops::Not::not", serialize_with = "flag")]
+    pub force_embed_chapters: bool,
+    /// Keep copy of original files in server metadata directory
+    #[serde(skip_serializing_if = "std::ops::Not::not", serialize_with = "flag")]
+    pub backup: bool,
+}
+
+/// Query of `POST /api/tools/item/<ID>/encode-m4b`. Unset fields keep server defaults.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct EncodeM4bParams {
+    /// Bitrate such as `128k`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<String>,
+    /// Codec such as `aac` or `copy`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u32>,
+}
+
+/// Server expects boolean query flags as `1`.
+fn flag<S: serde::Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u8(*value as u8)
+}
+
+/// Multipart form of `POST /api/upload`. Files are stored in
+/// `<folder>/<author>/<series>/<title>`.
+#[derive(Clone)]
+pub struct UploadParams {
+    pub title: String,
+    pub author: Option<String>,
+    pub series: Option<String>,
+    pub library: Id<Library>,
+    pub folder: Id<Folder>,
+    pub files: Vec<UploadFile>,
+    pub on_progress: Option<Arc<dyn Fn(UploadProgress) + Send + Sync>>,
+}
+
+/// File to upload, under given name.
+#[derive(Debug, Clone)]
+pub struct UploadFile {
+    pub name: String,
+    pub source: UploadSource,
+}
+
+#[derive(Debug, Clone)]
+pub enum UploadSource {
+    Path(PathBuf),
+    Bytes(Bytes),
+}
+
+/// Amount of file data sent, reported as upload proceeds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UploadProgress {
+    pub sent: u64,
+    pub total: u64,
+}
+
+impl UploadParams {
+    pub fn new(library: Id<Library>, folder: Id<Folder>, title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            author: None,
+            series: None,
+            library,
+            folder,
+            files: vec![],
+            on_progress: None,
+        }
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn series(mut self, series: impl Into<String>) -> Self {
+        self.series = Some(series.into());
+        self
+    }
+
+    /// Add file from disk, uploaded under its own name.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.files.push(UploadFile {
+            name,
+            source: UploadSource::Path(path),
+        });
+        self
+    }
+
+    pub fn file_bytes(mut self, name: impl Into<String>, data: impl Into<Bytes>) -> Self {
+        self.files.push(UploadFile {
+            name: name.into(),
+            source: UploadSource::Bytes(data.into()),
+        });
+        self
+    }
+
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(UploadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+}
+
+impl std::fmt::Debug for UploadParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UploadParams")
+            .field("title", &self.title)
+            .field("author", &self.author)
+            .field("series", &self.series)
+            .field("library", &self.library)
+            .field("folder", &self.folder)
+            .field("files", &self.files)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+/// Body of `POST /api/tags/rename`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RenameTagParams<'a> {
+    pub tag: &'a str,
+    pub new_tag: &'a str,
+}
+
+/// Body of `POST /api/genres/rename`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RenameGenreParams<'a> {
+    pub genre: &'a str,
+    pub new_genre: &'a str,
+}
+
+/// Body of `POST /api/items/<ID>/cover`, downloading cover from url
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct CoverUrlParams<'a> {
+    pub url: &'a str,
+}
+
+/// Body of `PATCH /api/items/<ID>/cover`
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct CoverPathParams<'a> {
+    pub cover: &'a str,
+}
+
+/// Body of requests referring to single entity by id
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct IdParams<'a> {
+    pub id: &'a str,
+}
@@ -0,0 +1,223 @@
+//! Offline-first progress: local playback positions reconciled with server progress, and
+//! queued while server is unreachable.
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{APIError, FusedError, RequestError};
+use crate::params::{BatchMediaProgressParams, MediaProgressParams};
+use crate::schema::{Id, LibraryItem, MediaProgress, PodcastEpisode, Seconds};
+use crate::UserClient;
+
+type ProgressKey = (Id<LibraryItem>, Option<Id<PodcastEpisode>>);
+
+/// Position recorded by player, possibly while offline.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalProgress {
+    pub library_item_id: Id<LibraryItem>,
+    pub episode_id: Option<Id<PodcastEpisode>>,
+    pub current_time: Seconds,
+    pub duration: Seconds,
+    pub is_finished: bool,
+    /// Time position was recorded, by local clock
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which side of progress is kept by `reconcile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Local,
+    Server,
+}
+
+/// Outcome of `ProgressSync::flush`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlushReport {
+    /// Local positions sent to server
+    pub uploaded: Vec<LocalProgress>,
+    /// Server progress which was newer than queued local position, to be applied to player
+    pub superseded: Vec<MediaProgress>,
+}
+
+/// Queue of local positions, sent to server once it is reachable.
+///
+/// Queue is kept in memory. Persist `pending` before exit and restore it with
+/// `with_pending` to keep positions recorded offline across restarts.
+pub struct ProgressSync {
+    client: UserClient,
+    /// Difference of timestamps treated as simultaneous update
+    skew_tolerance: TimeDelta,
+    pending: Mutex<BTreeMap<ProgressKey, LocalProgress>>,
+}
+
+impl LocalProgress {
+    pub fn new(
+        library_item_id: Id<LibraryItem>,
+        current_time: impl Into<Seconds>,
+        duration: impl Into<Seconds>,
+    ) -> Self {
+        Self {
+            library_item_id,
+            episode_id: None,
+            current_time: current_time.into(),
+            duration: duration.into(),
+            is_finished: false,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Fraction listened, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        if self.is_finished {
+            return 1.0;
+        }
+        if self.duration.as_f64() <= 0.0 {
+            return 0.0;
+        }
+        (self.current_time.as_f64() / self.duration.as_f64()).clamp(0.0, 1.0)
+    }
+
+    fn key(&self) -> ProgressKey {
+        (self.library_item_id.clone(), self.episode_id.clone())
+    }
+
+    fn params(&self) -> BatchMediaProgressParams {
+        BatchMediaProgressParams {
+            library_item_id: self.library_item_id.clone(),
+            episode_id: self.episode_id.clone(),
+            progress: MediaProgressParams::new()
+                .current_time(self.current_time)
+                .duration(self.duration)
+                .progress(self.progress())
+                .is_finished(self.is_finished),
+        }
+    }
+}
+
+impl From<&MediaProgress> for LocalProgress {
+    fn from(progress: &MediaProgress) -> Self {
+        Self {
+            library_item_id: progress.library_item_id.clone(),
+            episode_id: progress.episode_id.clone(),
+            current_time: progress.current_time,
+            duration: progress.duration,
+            is_finished: progress.is_finished,
+            updated_at: progress.last_update,
+        }
+    }
+}
+
+/// Last writer wins. Updates less than `skew_tolerance` apart are considered simultaneous,
+/// and the one further along is kept, as clocks of devices can't be trusted to be closer.
+pub fn reconcile(
+    local: &LocalProgress,
+    server: &MediaProgress,
+    skew_tolerance: TimeDelta,
+) -> Resolution {
+    let difference = local.updated_at - server.last_update;
+    if difference > skew_tolerance {
+        Resolution::Local
+    } else if difference < -skew_tolerance {
+        Resolution::Server
+    } else if (local.is_finished, local.current_time) > (server.is_finished, server.current_time) {
+        Resolution::Local
+    } else {
+        Resolution::Server
+    }
+}
+
+impl ProgressSync {
+    pub fn new(client: UserClient) -> Self {
+        Self {
+            client,
+            skew_tolerance: TimeDelta::seconds(30),
+            pending: Mutex::default(),
+        }
+    }
+
+    pub fn with_skew_tolerance(mut self, skew_tolerance: Duration) -> Self {
+        self.skew_tolerance = TimeDelta::from_std(skew_tolerance).unwrap_or(TimeDelta::max_value());
+        self
+    }
+
+    /// Restore queue saved from `pending`.
+    pub fn with_pending(self, pending: impl IntoIterator<Item = LocalProgress>) -> Self {
+        pending
+            .into_iter()
+            .for_each(|progress| self.record(progress));
+        self
+    }
+
+    /// Queue position, replacing older position of the same item.
+    pub fn record(&self, progress: LocalProgress) {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.get(&progress.key()) {
+            Some(queued) if queued.updated_at > progress.updated_at => {}
+            _ => {
+                pending.insert(progress.key(), progress);
+            }
+        }
+    }
+
+    /// Positions not sent yet.
+    pub fn pending(&self) -> Vec<LocalProgress> {
+        self.pending.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Send queued positions which are newer than server progress. Older ones are dropped
+    /// and server progress is returned instead.
+    ///
+    /// Queue is kept if server can't be reached. Positions recorded during flush stay
+    /// queued.
+    pub async fn flush(&self) -> Result<FlushReport, FusedError<RequestError>> {
+        let pending = self.pending();
+        if pending.is_empty() {
+            return Ok(FlushReport::default());
+        }
+        let user = self.client.me().await?;
+
+        let mut report = FlushReport::default();
+        for local in pending.iter() {
+            let server = user.progress_for(&local.library_item_id, local.episode_id.as_ref());
+            match server {
+                Some(server)
+                    if reconcile(local, server, self.skew_tolerance) == Resolution::Server =>
+                {
+                    report.superseded.push(server.clone())
+                }
+                _ => report.uploaded.push(local.clone()),
+            }
+        }
+        if !report.uploaded.is_empty() {
+            let updates: Vec<_> = report.uploaded.iter().map(LocalProgress::params).collect();
+            self.client.batch_update_media_progress(&updates).await?;
+        }
+
+        let mut queue = self.pending.lock().unwrap();
+        for flushed in &pending {
+            if queue.get(&flushed.key()) == Some(flushed) {
+                queue.remove(&flushed.key());
+            }
+        }
+        Ok(report)
+    }
+
+    /// Flush queue, retrying every `retry_interval` while server can't be reached.
+    pub async fn flush_when_online(
+        &self,
+        retry_interval: Duration,
+    ) -> Result<FlushReport, FusedError<RequestError>> {
+        loop {
+            match self.flush().await {
+                Err(FusedError::APIError(APIError::NetworkError { .. })) => {
+                    tokio::time::sleep(retry_interval).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
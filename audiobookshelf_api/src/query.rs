@@ -0,0 +1,124 @@
+//! Saved searches of library items, stored as compact strings.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::InvalidQuery;
+use crate::params::{Filter, LibraryItemParams, Sort};
+
+/// Sort and filter of library items, without pagination.
+///
+/// Written as query string of server, such as
+/// `sort=media.duration&desc=1&filter=progress.bm90LWZpbmlzaGVk`, which is also its
+/// serialized form, so it can be persisted and shared between devices as is. Server
+/// supports single filter, so narrower searches need filtering on client.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub sort: Option<Sort>,
+    pub desc: bool,
+    pub filter: Option<Filter>,
+    pub collapse_series: bool,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sort(mut self, sort: Sort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn desc(mut self) -> Self {
+        self.desc = true;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn collapse_series(mut self) -> Self {
+        self.collapse_series = true;
+        self
+    }
+
+    /// Params fetching page of `limit` items matching query.
+    pub fn params(&self, limit: usize, page: usize) -> LibraryItemParams {
+        LibraryItemParams {
+            limit,
+            page,
+            sort: self.sort.clone(),
+            desc: self.desc,
+            filter: self.filter.clone(),
+            collapse_series: self.collapse_series,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Query> for LibraryItemParams {
+    /// Params fetching all matching items at once.
+    fn from(query: Query) -> Self {
+        query.params(0, 0)
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(sort) = &self.sort {
+            query.append_pair("sort", sort.as_str());
+        }
+        if self.desc {
+            query.append_pair("desc", "1");
+        }
+        if let Some(filter) = &self.filter {
+            query.append_pair("filter", &filter.encode());
+        }
+        if self.collapse_series {
+            query.append_pair("collapseseries", "1");
+        }
+        f.write_str(&query.finish())
+    }
+}
+
+impl FromStr for Query {
+    type Err = InvalidQuery;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidQuery(text.to_string());
+        let flag = |value: &str| match value {
+            "1" | "true" => Ok(true),
+            "0" | "false" => Ok(false),
+            _ => Err(invalid()),
+        };
+        let mut query = Query::default();
+        for (key, value) in url::form_urlencoded::parse(text.as_bytes()) {
+            match key.as_ref() {
+                "sort" => query.sort = Some(Sort::parse(&value)),
+                "desc" => query.desc = flag(&value)?,
+                "filter" => query.filter = Some(Filter::decode(&value).ok_or_else(invalid)?),
+                "collapseseries" => query.collapse_series = flag(&value)?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(query)
+    }
+}
+
+impl Serialize for Query {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Query {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,197 @@
+//! Client side limiting of request rate, so bulk operations don't trip server rate limits,
+//! and of bandwidth, so background downloads don't saturate the link.
+use std::future::Future;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::Stream;
+use tokio::time::{Instant, Sleep};
+
+use crate::transport::{Transport, TransportFuture};
+
+/// Token bucket, allowing `burst` requests at once, refilled at `requests_per_second`.
+///
+/// Shared by all clients created from the same `ClientConfig`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests are waiting for their turn.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// # Panics
+    /// If `requests_per_second` is not positive.
+    pub fn new(requests_per_second: f64, burst: NonZeroU32) -> Self {
+        assert!(
+            requests_per_second > 0.0,
+            "Request rate must be positive, got {requests_per_second}"
+        );
+        let burst = f64::from(burst.get());
+        Self {
+            requests_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst as u32
+    }
+
+    /// Wait until request can be sent. Requests are let through in order of calls.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = Instant::now();
+            let refilled = (now - bucket.updated).as_secs_f64() * self.requests_per_second;
+            bucket.tokens = (bucket.tokens + refilled).min(self.burst);
+            bucket.updated = now;
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Transport waiting for `limiter` before every request.
+#[derive(Debug)]
+pub(crate) struct RateLimited {
+    pub inner: Arc<dyn Transport>,
+    pub limiter: Arc<RateLimiter>,
+}
+
+impl Transport for RateLimited {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            self.limiter.acquire().await;
+            self.inner.execute(request).await
+        })
+    }
+}
+
+/// Limit of bytes received per second, allowing a second worth of bytes at once.
+///
+/// Set with `StreamOptions::throttle` and `DownloadManager::with_throttle`. Streams and
+/// downloads sharing the same throttle share its bandwidth.
+#[derive(Debug)]
+pub struct Throttle {
+    bytes_per_second: f64,
+    /// Bytes available, negative when received data outpaced the limit
+    bucket: Mutex<Bucket>,
+}
+
+impl Throttle {
+    pub fn new(bytes_per_second: NonZeroU64) -> Self {
+        let bytes_per_second = bytes_per_second.get() as f64;
+        Self {
+            bytes_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: bytes_per_second,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second as u64
+    }
+
+    /// Account for `bytes` received, returning time to wait before receiving more.
+    pub fn consume(&self, bytes: u64) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let refilled = (now - bucket.updated).as_secs_f64() * self.bytes_per_second;
+        bucket.tokens = (bucket.tokens + refilled).min(self.bytes_per_second);
+        bucket.updated = now;
+        bucket.tokens -= bytes as f64;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.bytes_per_second)
+        }
+    }
+}
+
+/// Delay between polls of byte stream, keeping it within throttle.
+pub(crate) struct Pacer {
+    throttle: Arc<Throttle>,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl Pacer {
+    pub fn new(throttle: Arc<Throttle>) -> Self {
+        Self {
+            throttle,
+            delay: None,
+        }
+    }
+
+    /// Ready once more bytes can be received.
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(delay) = &mut self.delay {
+            ready!(delay.as_mut().poll(cx));
+            self.delay = None;
+        }
+        Poll::Ready(())
+    }
+
+    pub fn received(&mut self, bytes: usize) {
+        let wait = self.throttle.consume(bytes as u64);
+        if !wait.is_zero() {
+            self.delay = Some(Box::pin(tokio::time::sleep(wait)));
+        }
+    }
+}
+
+/// Byte stream, received no faster than throttle allows.
+pub(crate) struct Throttled<S> {
+    inner: S,
+    pacer: Pacer,
+}
+
+impl<S> Throttled<S> {
+    pub fn new(inner: S, throttle: Arc<Throttle>) -> Self {
+        Self {
+            inner,
+            pacer: Pacer::new(throttle),
+        }
+    }
+}
+
+impl<S, E> Stream for Throttled<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        ready!(self.pacer.poll_ready(cx));
+        let item = ready!(Pin::new(&mut self.inner).poll_next(cx));
+        if let Some(Ok(bytes)) = &item {
+            self.pacer.received(bytes.len());
+        }
+        Poll::Ready(item)
+    }
+}
@@ -1,56 +1,197 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Deserializer, Serialize};
-use serde_repr::Deserialize_repr;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::errors::InvalidId;
+
+/// JSON fields not known to this crate, kept with `extra-fields` feature enabled.
+#[cfg(feature = "extra-fields")]
+pub type Extra = serde_json::Map<String, serde_json::Value>;
+
+/// Timestamps are sent as milliseconds since epoch.
+mod timestamp {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn deserialize<'d, D: Deserializer<'d>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let timestamp = i64::deserialize(deserializer)?;
+        DateTime::from_timestamp_millis(timestamp)
+            .ok_or(serde::de::Error::custom("DateTime out of range"))
+    }
+
+    pub fn serialize<S: Serializer>(
+        datetime: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        datetime.timestamp_millis().serialize(serializer)
+    }
+}
+
+mod timestamp_option {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn deserialize<'d, D: Deserializer<'d>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        let timestamp = Option::<i64>::deserialize(deserializer)?;
+        if let Some(timestamp) = timestamp {
+            let datetime = DateTime::from_timestamp_millis(timestamp)
+                .ok_or(serde::de::Error::custom("DateTime out of range"))?;
+            Ok(Some(datetime))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        datetime: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        datetime
+            .map(|datetime| datetime.timestamp_millis())
+            .serialize(serializer)
+    }
+}
 
-fn deserialize_timestamp<'d, D: Deserializer<'d>>(
-    deserializer: D,
-) -> Result<DateTime<Utc>, D::Error> {
-    let timestamp = i64::deserialize(deserializer)?;
-    DateTime::from_timestamp_millis(timestamp)
-        .ok_or(serde::de::Error::custom("DateTime out of range"))
+/// Backup schedule is cron expression, or `false` then automatic backups are disabled.
+mod backup_schedule {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn deserialize<'d, D: Deserializer<'d>>(
+        deserializer: D,
+    ) -> Result<Option<String>, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(cron) => Ok(Some(cron)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        schedule: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match schedule {
+            Some(cron) => cron.serialize(serializer),
+            None => false.serialize(serializer),
+        }
+    }
+}
+
+/// Response to `GET /status`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ServerStatus {
+    pub app: String,
+    /// Version, such as `2.12.3`
+    pub server_version: String,
+    /// False until root user is created
+    pub is_init: bool,
+    pub language: Option<String>,
+    #[serde(default)]
+    pub auth_methods: Vec<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-fn deserialize_timestamp_option<'d, D: Deserializer<'d>>(
-    deserializer: D,
-) -> Result<Option<DateTime<Utc>>, D::Error> {
-    let timestamp = Option::<i64>::deserialize(deserializer)?;
-    if let Some(timestamp) = timestamp {
-        let datetime = DateTime::from_timestamp_millis(timestamp)
-            .ok_or(serde::de::Error::custom("DateTime out of range"))?;
-        Ok(Some(datetime))
-    } else {
-        Ok(None)
+impl ServerStatus {
+    /// Whether server version is `major.minor.patch` or later.
+    pub fn version_at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        let mut parts = self
+            .server_version
+            .split(['.', '-'])
+            .map(|part| part.parse::<u32>().unwrap_or(0));
+        let version = (
+            parts.next().unwrap_or(0),
+            parts.next().unwrap_or(0),
+            parts.next().unwrap_or(0),
+        );
+        version >= (major, minor, patch)
     }
 }
 
+/// Response to `GET /ping`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Ping {
+    pub success: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AuthRequest {
     pub username: String,
     pub password: String,
 }
 
 /// Response to `AuthRequest`
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AuthResponse {
     pub user: UserData,
     pub user_default_library_id: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct UserData {
     pub id: Id<UserData>,
     pub username: String,
     #[serde(rename = "type")]
     pub type_: String,
     pub token: String,
+    /// Short lived token, returned on login by servers supporting refresh tokens
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
     pub media_progress: Vec<MediaProgress>,
+    #[serde(default)]
+    pub bookmarks: Vec<AudioBookmark>,
     pub permissions: UserPermissions,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Position in book marked by user. Identified by item and time.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AudioBookmark {
+    pub library_item_id: Id<LibraryItem>,
+    pub title: String,
+    /// Position in seconds
+    pub time: f64,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UserPermissions {
     pub download: bool,
@@ -60,29 +201,251 @@ pub struct UserPermissions {
     pub access_all_libraries: bool,
     pub access_all_tags: bool,
     pub access_explicit_content: bool,
+    #[serde(default)]
+    pub create_ereader: bool,
+    /// Tags of `User::item_tags_selected` are denied, instead of allowed
+    #[serde(default)]
+    pub selected_tags_not_accessible: bool,
+}
+
+/// Right of user, checked by server before acting on request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    Download,
+    Update,
+    Delete,
+    Upload,
+    AccessAllLibraries,
+    AccessAllTags,
+    AccessExplicitContent,
+    CreateEreader,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Download => "download",
+            Permission::Update => "update",
+            Permission::Delete => "delete",
+            Permission::Upload => "upload",
+            Permission::AccessAllLibraries => "accessAllLibraries",
+            Permission::AccessAllTags => "accessAllTags",
+            Permission::AccessExplicitContent => "accessExplicitContent",
+            Permission::CreateEreader => "createEreader",
+        }
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl UserPermissions {
+    pub fn allows(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::Download => self.download,
+            Permission::Update => self.update,
+            Permission::Delete => self.delete,
+            Permission::Upload => self.upload,
+            Permission::AccessAllLibraries => self.access_all_libraries,
+            Permission::AccessAllTags => self.access_all_tags,
+            Permission::AccessExplicitContent => self.access_explicit_content,
+            Permission::CreateEreader => self.create_ereader,
+        }
+    }
+
+    pub fn can_download(&self) -> bool {
+        self.download
+    }
+
+    pub fn can_update(&self) -> bool {
+        self.update
+    }
+
+    pub fn can_delete(&self) -> bool {
+        self.delete
+    }
+
+    pub fn can_upload(&self) -> bool {
+        self.upload
+    }
+}
+
+/// User account, as seen by administrator
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct User {
+    pub id: Id<UserData>,
+    pub username: String,
+    pub email: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: UserType,
+    pub is_active: bool,
+    #[serde(default)]
+    pub is_locked: bool,
+    #[serde(default, with = "timestamp_option")]
+    pub last_seen: Option<DateTime<Utc>>,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    pub permissions: UserPermissions,
+    /// Libraries user can access, unless `permissions.access_all_libraries` is set
+    #[serde(default)]
+    pub libraries_accessible: Vec<Id<Library>>,
+    #[serde(default)]
+    pub item_tags_selected: Vec<String>,
+    #[serde(default)]
+    pub media_progress: Vec<MediaProgress>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum UserType {
+    Root,
+    Admin,
+    User,
+    Guest,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Response to `GET /api/users`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Users {
+    pub users: Vec<User>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/users` and `PATCH /api/users/<ID>`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct UserResponse {
+    pub user: User,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/users/<ID>/listening-sessions`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ListeningSessions {
+    pub total: usize,
+    pub num_pages: usize,
+    pub page: usize,
+    pub items_per_page: usize,
+    pub sessions: Vec<PlaybackSession>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct MediaProgress {
     pub id: Id<MediaProgress>,
     pub library_item_id: Id<LibraryItem>,
-    pub episode_id: Option<Id<Episode>>,
-    pub duration: f64,
+    pub episode_id: Option<Id<PodcastEpisode>>,
+    pub duration: Seconds,
     pub progress: f64,
-    pub current_time: f64,
+    pub current_time: Seconds,
     pub is_finished: bool,
     pub hide_from_continue_listening: bool,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub last_update: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub started_at: DateTime<Utc>,
     #[serde(default)]
-    #[serde(deserialize_with = "deserialize_timestamp_option")]
+    #[serde(with = "timestamp_option")]
     pub finished_at: Option<DateTime<Utc>>,
+    /// Reader position in ebook, such as EPUB CFI or PDF page
+    #[serde(default)]
+    pub ebook_location: Option<String>,
+    /// Fraction of ebook read, from 0 to 1
+    #[serde(default)]
+    pub ebook_progress: Option<f64>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Position or duration in seconds, which is the unit used by server for media time.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[serde(transparent)]
+pub struct Seconds(pub f64);
+
+impl Seconds {
+    pub const ZERO: Seconds = Seconds(0.0);
+
+    pub fn from_millis(millis: u64) -> Self {
+        Self(millis as f64 / 1000.0)
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.0
+    }
+
+    /// Negative values are clamped to zero.
+    pub fn to_duration(self) -> Duration {
+        Duration::from_secs_f64(self.0.max(0.0))
+    }
+}
+
+impl From<f64> for Seconds {
+    fn from(seconds: f64) -> Self {
+        Self(seconds)
+    }
+}
+
+impl From<Duration> for Seconds {
+    fn from(duration: Duration) -> Self {
+        Self(duration.as_secs_f64())
+    }
+}
+
+impl From<Seconds> for Duration {
+    fn from(seconds: Seconds) -> Self {
+        seconds.to_duration()
+    }
+}
+
+impl Add for Seconds {
+    type Output = Seconds;
+
+    fn add(self, other: Seconds) -> Seconds {
+        Seconds(self.0 + other.0)
+    }
+}
+
+impl Sub for Seconds {
+    type Output = Seconds;
+
+    fn sub(self, other: Seconds) -> Seconds {
+        Seconds(self.0 - other.0)
+    }
+}
+
+impl Display for Seconds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}s", self.0)
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// Identifier of server entity of type `T`.
+#[derive(Deserialize, Serialize)]
 #[serde(transparent)]
 pub struct Id<T> {
     pub id: String,
@@ -91,21 +454,122 @@ pub struct Id<T> {
 }
 
 impl<T> Id<T> {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Check that `id` can be used as part of url: non-empty, without whitespace and
+    /// url delimiters.
+    pub fn parse(id: &str) -> Result<Self, InvalidId> {
+        let is_valid = !id.is_empty()
+            && !id
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || "/?#%".contains(c));
+        if is_valid {
+            Ok(Self::new(id))
+        } else {
+            Err(InvalidId(id.to_string()))
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         self.id.as_str()
     }
+
+    pub fn into_string(self) -> String {
+        self.id
+    }
+}
+
+// Implemented by hand, since derive would require `T` to implement traits as well.
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id.clone())
+    }
+}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<T> Debug for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Id").field(&self.id).finish()
+    }
+}
+
+impl<T> Display for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl<T> From<&str> for Id<T> {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl<T> From<String> for Id<T> {
+    fn from(id: String) -> Self {
+        Self::new(id)
+    }
+}
+
+impl<T> FromStr for Id<T> {
+    type Err = InvalidId;
+
+    fn from_str(id: &str) -> Result<Self, InvalidId> {
+        Self::parse(id)
+    }
+}
+
+impl<T> AsRef<str> for Id<T> {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
 }
 
 /// Response to `GET /api/libraries`
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Libraries {
     pub libraries: Vec<Library>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 /// Response to `GET /api/libraries/<ID>`
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Library {
     pub id: Id<Library>,
     pub name: String,
@@ -114,98 +578,1258 @@ pub struct Library {
     pub icon: String,
     pub media_type: MediaType,
     pub provider: String,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub created_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub last_update: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LibraryWithFilters {
     pub library: Library,
     pub filterdata: LibraryFilterData,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LibraryFilterData {
     pub authors: Vec<Author>,
     pub genres: Vec<String>,
     pub series: Vec<Series>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub narrators: Vec<String>,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    /// Decades books were published in, such as `1990`
+    #[serde(default)]
+    pub published_decades: Vec<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// Narrator, as listed by `GET /api/libraries/<ID>/narrators`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct Series {
-    pub id: Id<Series>,
+#[non_exhaustive]
+pub struct Narrator {
+    /// Encoded name, used in urls
+    pub id: String,
     pub name: String,
+    pub num_books: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// Response to `GET /api/libraries/<ID>/narrators`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct Author {
-    pub id: Id<Author>,
-    pub name: String,
+#[non_exhaustive]
+pub struct Narrators {
+    pub narrators: Vec<Narrator>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// Element of response to `GET /api/libraries/<ID>/personalized`, such as "Continue
+/// listening" or "Recently added".
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub enum MediaType {
-    Book,
-    Podcast,
+#[non_exhaustive]
+pub struct Shelf {
+    /// Kind of shelf, such as `continue-listening` or `recent-series`
+    pub id: String,
+    /// English label
+    pub label: String,
+    /// Key of label translation in web client
+    #[serde(default)]
+    pub label_string_key: Option<String>,
+    #[serde(flatten)]
+    pub entities: ShelfEntities,
+    /// Number of matching entities, which may exceed number of returned ones
+    #[serde(default)]
+    pub total: usize,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// Entities of shelf, by shelf type.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
-pub struct Folder {
-    pub id: Id<Folder>,
-    pub full_path: String,
-    pub library_id: String,
-    #[serde(deserialize_with = "deserialize_timestamp")]
-    pub added_at: DateTime<Utc>,
+#[non_exhaustive]
+pub enum ShelfEntities {
+    Book {
+        entities: Vec<ShelfItem>,
+    },
+    Podcast {
+        entities: Vec<ShelfItem>,
+    },
+    /// Podcasts with their most recent episode
+    Episode {
+        entities: Vec<ShelfItem>,
+    },
+    Series {
+        entities: Vec<ShelfSeries>,
+    },
+    Authors {
+        entities: Vec<ShelfAuthor>,
+    },
+    /// Shelf type not known to this crate
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct PaginatedResponse<T> {
-    pub results: Vec<T>,
-    pub total: usize,
-    pub limit: usize,
-    pub page: usize,
+#[non_exhaustive]
+pub struct ShelfItem {
+    #[serde(flatten)]
+    pub item: LibraryItemMinified,
+    /// Episode of podcast, on `Episode` shelves
+    #[serde(default)]
+    pub recent_episode: Option<PodcastEpisode>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// Series with its books, in order of sequence.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct LibraryItem {
-    pub id: Id<LibraryItem>,
-    pub library_id: Id<Library>,
-    pub folder_id: Id<Folder>,
-    pub path: String,
-    pub rel_path: String,
-    pub is_file: bool,
-    #[serde(deserialize_with = "deserialize_timestamp")]
-    pub mtime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
-    pub ctime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
-    pub birthtime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
-    pub added_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
-    pub updated_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp_option")]
-    pub last_scan: Option<DateTime<Utc>>,
-    pub scan_version: Option<String>,
+#[non_exhaustive]
+pub struct ShelfSeries {
+    pub id: Id<Series>,
+    pub name: String,
+    #[serde(default)]
+    pub books: Vec<LibraryItemMinified>,
+    #[serde(default)]
+    pub in_progress: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ShelfAuthor {
+    pub id: Id<Author>,
+    pub name: String,
+    #[serde(default)]
+    pub image_path: Option<String>,
+    #[serde(default)]
+    pub num_books: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/libraries/<ID>/stats`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryStats {
+    pub total_items: usize,
+    pub total_authors: usize,
+    pub total_genres: usize,
+    pub total_duration: Seconds,
+    /// Bytes
+    pub total_size: u64,
+    pub num_audio_tracks: usize,
+    /// Largest items by size, in descending order
+    pub largest_items: Vec<ItemSizeStat>,
+    /// Longest items by duration, in descending order
+    pub longest_items: Vec<ItemDurationStat>,
+    pub authors_with_count: Vec<AuthorCount>,
+    pub genres_with_count: Vec<GenreCount>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ItemSizeStat {
+    pub id: Id<LibraryItem>,
+    pub title: String,
+    /// Bytes
+    pub size: u64,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ItemDurationStat {
+    pub id: Id<LibraryItem>,
+    pub title: String,
+    pub duration: Seconds,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Author and number of their books
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuthorCount {
+    pub id: Id<Author>,
+    pub name: String,
+    pub count: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Genre and number of items having it
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct GenreCount {
+    pub genre: String,
+    pub count: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Metadata source implementing audiobookshelf custom provider API, used for matching
+/// as `MetadataProvider::Custom`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CustomMetadataProvider {
+    pub id: Id<CustomMetadataProvider>,
+    pub name: String,
+    pub media_type: MediaType,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Value of `Authorization` header sent to provider
+    #[serde(default)]
+    pub auth_header_value: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/custom-metadata-providers`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CustomMetadataProviders {
+    pub providers: Vec<CustomMetadataProvider>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/custom-metadata-providers`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CustomMetadataProviderResponse {
+    pub provider: CustomMetadataProvider,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/me/stats/year/<YEAR>`, listening summary of user for year
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct YearStats {
+    pub total_listening_sessions: usize,
+    pub total_listening_time: Seconds,
+    pub total_book_listening_time: Seconds,
+    pub total_podcast_listening_time: Seconds,
+    /// In descending order of listening time
+    #[serde(default)]
+    pub top_authors: Vec<NameListeningTime>,
+    #[serde(default)]
+    pub top_genres: Vec<GenreListeningTime>,
+    #[serde(default)]
+    pub top_narrators: Vec<NameListeningTime>,
+    #[serde(default)]
+    pub most_listened_narrator: Option<NameListeningTime>,
+    #[serde(default)]
+    pub most_listened_month: Option<MonthListeningTime>,
+    pub num_books_finished: usize,
+    /// Books with any listening during year
+    pub num_books_listened: usize,
+    #[serde(default)]
+    pub longest_audiobook_finished: Option<FinishedBookStat>,
+    /// Some of listened books having cover, for collage
+    #[serde(default)]
+    pub books_with_covers: Vec<Id<LibraryItem>>,
+    #[serde(default)]
+    pub finished_books_with_covers: Vec<Id<LibraryItem>>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/stats/year/<YEAR>`, summary of all users and libraries for year
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ServerYearStats {
+    pub num_listening_sessions: usize,
+    pub num_books_added: usize,
+    pub num_authors_added: usize,
+    /// Bytes
+    pub total_books_added_size: u64,
+    pub total_books_added_duration: Seconds,
+    #[serde(default)]
+    pub total_listening_time: Seconds,
+    /// Some of added books having cover, for collage
+    #[serde(default)]
+    pub books_with_covers: Vec<Id<LibraryItem>>,
+    /// In descending order of listening time
+    #[serde(default)]
+    pub top_authors: Vec<NameListeningTime>,
+    #[serde(default)]
+    pub top_genres: Vec<GenreListeningTime>,
+    #[serde(default)]
+    pub top_narrators: Vec<NameListeningTime>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Author or narrator, and time spent listening to their books
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NameListeningTime {
+    pub name: String,
+    pub time: Seconds,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct GenreListeningTime {
+    pub genre: String,
+    pub time: Seconds,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct MonthListeningTime {
+    /// Starting from 0 for January
+    pub month: u32,
+    pub time: Seconds,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct FinishedBookStat {
+    /// Id of book, as opposed to its library item
+    pub id: String,
+    pub title: String,
+    pub duration: Seconds,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Series {
+    pub id: Id<Series>,
+    pub name: String,
+    /// Position of book in series, such as `1` or `2.5`, when listed in book metadata
+    #[serde(default)]
+    pub sequence: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Author {
+    pub id: Id<Author>,
+    pub name: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `PATCH /api/items/<ID>/media`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryItemUpdate {
+    /// Whether anything was changed
+    pub updated: bool,
+    pub library_item: LibraryItem,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Element of response to `GET /api/libraries/<ID>/series`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibrarySeries {
+    pub id: Id<Series>,
+    pub name: String,
+    pub name_ignore_prefix: Option<String>,
+    #[serde(with = "timestamp")]
+    pub added_at: DateTime<Utc>,
+    /// Sum of durations of all books in seconds
+    #[serde(default)]
+    pub total_duration: f64,
+    pub books: Vec<LibraryItemMinified>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/libraries/<ID>/series/<ID>?include=progress`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SeriesDetails {
+    pub id: Id<Series>,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(with = "timestamp")]
+    pub added_at: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub updated_at: DateTime<Utc>,
+    pub progress: Option<SeriesProgress>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Progress of user through series
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SeriesProgress {
+    /// Books of series which user started
+    pub library_item_ids: Vec<Id<LibraryItem>>,
+    pub library_item_ids_finished: Vec<Id<LibraryItem>>,
+    pub is_finished: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/authors/<ID>?include=items,series`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuthorExpanded {
+    pub id: Id<Author>,
+    pub asin: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub image_path: Option<String>,
+    #[serde(with = "timestamp")]
+    pub added_at: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub library_items: Vec<LibraryItemMinified>,
+    #[serde(default)]
+    pub series: Vec<AuthorSeries>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Series with books of single author
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuthorSeries {
+    pub id: Id<Series>,
+    pub name: String,
+    pub items: Vec<LibraryItemMinified>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `PATCH /api/authors/<ID>` and `POST /api/authors/<ID>/match`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuthorUpdate {
+    pub author: AuthorExpanded,
+    #[serde(default)]
+    pub updated: bool,
+    /// Author was renamed to name of existing one, and merged into it
+    #[serde(default)]
+    pub merged: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum MediaType {
+    Book,
+    Podcast,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Folder {
+    pub id: Id<Folder>,
+    pub full_path: String,
+    pub library_id: String,
+    #[serde(with = "timestamp")]
+    pub added_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/collections`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Collections {
+    pub collections: Vec<CollectionExpanded>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Collection {
+    pub id: Id<Collection>,
+    pub library_id: Id<Library>,
+    pub user_id: Option<Id<UserData>>,
+    pub name: String,
+    pub description: Option<String>,
+    pub books: Vec<Id<LibraryItem>>,
+    #[serde(with = "timestamp")]
+    pub last_update: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Collection with books included
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CollectionExpanded {
+    pub id: Id<Collection>,
+    pub library_id: Id<Library>,
+    pub user_id: Option<Id<UserData>>,
+    pub name: String,
+    pub description: Option<String>,
+    pub books: Vec<LibraryItem>,
+    #[serde(with = "timestamp")]
+    pub last_update: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/playlists`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Playlists {
+    pub playlists: Vec<PlaylistExpanded>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Playlist {
+    pub id: Id<Playlist>,
+    pub library_id: Id<Library>,
+    pub user_id: Id<UserData>,
+    pub name: String,
+    pub description: Option<String>,
+    pub cover_path: Option<String>,
+    pub items: Vec<PlaylistItem>,
+    #[serde(with = "timestamp")]
+    pub last_update: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Book, or podcast episode, in playlist
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItem {
+    pub library_item_id: Id<LibraryItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub episode_id: Option<Id<PodcastEpisode>>,
+}
+
+/// Playlist with items included
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PlaylistExpanded {
+    pub id: Id<Playlist>,
+    pub library_id: Id<Library>,
+    pub user_id: Id<UserData>,
+    pub name: String,
+    pub description: Option<String>,
+    pub cover_path: Option<String>,
+    pub items: Vec<PlaylistItemExpanded>,
+    #[serde(with = "timestamp")]
+    pub last_update: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PlaylistItemExpanded {
+    pub library_item_id: Id<LibraryItem>,
+    #[serde(default)]
+    pub episode_id: Option<Id<PodcastEpisode>>,
+    pub library_item: LibraryItemMinified,
+    #[serde(default)]
+    pub episode: Option<PodcastEpisode>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// RSS feed of book, collection or series
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Feed {
+    pub id: Id<Feed>,
+    pub slug: Option<String>,
+    pub entity_type: String,
+    /// Id of library item, collection or series
+    pub entity_id: String,
+    pub feed_url: String,
+    pub meta: FeedMeta,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct FeedMeta {
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub prevent_indexing: bool,
+    pub owner_name: Option<String>,
+    pub owner_email: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/feeds`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Feeds {
+    pub feeds: Vec<Feed>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/feeds/<TYPE>/<ID>/open`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct FeedResponse {
+    pub feed: Feed,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/notifications`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NotificationsResponse {
+    pub data: NotificationData,
+    pub settings: NotificationSettings,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/notifications`, `PATCH /api/notifications/<ID>` and
+/// `DELETE /api/notifications/<ID>`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NotificationSettingsResponse {
+    pub settings: NotificationSettings,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Events notifications can be sent on
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NotificationData {
+    pub events: Vec<NotificationEventInfo>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NotificationEventInfo {
+    pub name: NotificationEvent,
+    #[serde(default)]
+    pub requires_library: bool,
+    pub description: String,
+    /// Variables available in title and body templates
+    #[serde(default)]
+    pub variables: Vec<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum NotificationEvent {
+    OnPodcastEpisodeDownloaded,
+    OnBackupCompleted,
+    OnBackupFailed,
+    #[serde(rename = "onRSSFeedFailed")]
+    OnRssFeedFailed,
+    #[serde(rename = "onRSSFeedDisabled")]
+    OnRssFeedDisabled,
+    OnTest,
+    /// Event added in newer server version
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NotificationSettings {
+    /// Either `api` or `cli`
+    pub apprise_type: String,
+    pub apprise_api_url: Option<String>,
+    pub notifications: Vec<Notification>,
+    pub max_failed_attempts: u32,
+    pub max_notification_queue: u32,
+    /// Delay between notifications in milliseconds
+    pub notification_delay: u64,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Notification {
+    pub id: Id<Notification>,
+    pub library_id: Option<Id<Library>>,
+    pub event_name: NotificationEvent,
+    /// Apprise urls
+    pub urls: Vec<String>,
+    pub title_template: String,
+    pub body_template: String,
+    pub enabled: bool,
+    #[serde(default, with = "timestamp_option")]
+    pub last_fired_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_attempt_failed: bool,
+    #[serde(default)]
+    pub num_consecutive_failed_attempts: u32,
+    #[serde(default)]
+    pub num_times_fired: u32,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/tags`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Tags {
+    pub tags: Vec<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/genres`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Genres {
+    pub genres: Vec<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Server wide configuration.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ServerSettings {
+    pub id: String,
+    pub scanner_find_covers: bool,
+    /// Metadata provider used to search covers, such as `google` or `audible`
+    pub scanner_cover_provider: String,
+    pub scanner_parse_subtitle: bool,
+    pub scanner_prefer_matched_metadata: bool,
+    pub scanner_disable_watcher: bool,
+    pub store_cover_with_item: bool,
+    pub store_metadata_with_item: bool,
+    pub metadata_file_format: String,
+    /// Login attempts allowed within `rate_limit_login_window`
+    pub rate_limit_login_requests: u32,
+    /// Milliseconds
+    pub rate_limit_login_window: u64,
+    /// Cron expression, `None` if automatic backups are disabled
+    #[serde(with = "backup_schedule", default)]
+    pub backup_schedule: Option<String>,
+    pub backups_to_keep: u32,
+    /// Gigabytes
+    pub max_backup_size: f64,
+    pub logger_daily_logs_to_keep: u32,
+    pub logger_scanner_logs_to_keep: u32,
+    #[serde(default)]
+    pub sorting_ignore_prefix: bool,
+    #[serde(default)]
+    pub sorting_prefixes: Vec<String>,
+    #[serde(default)]
+    pub chromecast_enabled: bool,
+    pub date_format: String,
+    #[serde(default)]
+    pub time_format: Option<String>,
+    pub language: String,
+    pub log_level: u32,
+    pub version: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/authorize`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuthorizeResponse {
+    pub user: UserData,
+    pub user_default_library_id: Option<String>,
+    pub server_settings: ServerSettings,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `PATCH /api/settings`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ServerSettingsResponse {
+    pub server_settings: ServerSettings,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Background job running on server, such as metadata embedding or M4B encoding.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Task {
+    pub id: Id<Task>,
+    /// Kind of job, e.g. `embed-metadata` or `encode-m4b`
+    pub action: String,
+    /// Action specific data, usually including `libraryItemId`
+    #[serde(default)]
+    pub data: serde_json::Value,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub show_success: bool,
+    #[serde(default)]
+    pub is_failed: bool,
+    #[serde(default)]
+    pub is_finished: bool,
+    #[serde(with = "timestamp_option", default)]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(with = "timestamp_option", default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Task {
+    /// Item task operates on, if any.
+    pub fn library_item_id(&self) -> Option<Id<LibraryItem>> {
+        let id = self.data.get("libraryItemId")?.as_str()?;
+        Some(Id::new(id))
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.is_finished
+    }
+}
+
+/// Response to `GET /api/tasks`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Tasks {
+    pub tasks: Vec<Task>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Severity of server log entry, same as `log_level` of server settings.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct LogLevel(pub u32);
+
+impl LogLevel {
+    pub const TRACE: LogLevel = LogLevel(0);
+    pub const DEBUG: LogLevel = LogLevel(1);
+    pub const INFO: LogLevel = LogLevel(2);
+    pub const WARN: LogLevel = LogLevel(3);
+    pub const ERROR: LogLevel = LogLevel(4);
+    pub const FATAL: LogLevel = LogLevel(5);
+    /// Messages server always logs, such as startup notes
+    pub const NOTE: LogLevel = LogLevel(6);
+}
+
+/// Entry of server log.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LogEntry {
+    /// Server local time, formatted as `YYYY-MM-DD HH:mm:ss.SSS`
+    pub timestamp: String,
+    /// Source file and line, such as `Server.js:120`
+    pub source: String,
+    pub message: String,
+    pub level_name: String,
+    pub level: LogLevel,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/logger-data`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LoggerData {
+    pub current_daily_logs: Vec<LogEntry>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Outcome of rescanning library item.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+#[non_exhaustive]
+pub enum ScanResult {
+    /// Item was not found on disk during scan
+    Nothing,
+    Added,
+    Updated,
+    Removed,
+    /// Files on disk match stored item
+    UpToDate,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Response to `POST /api/items/<ID>/scan`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ScanResponse {
+    pub result: ScanResult,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Directory on server filesystem, candidate for library folder.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct FilesystemDirectory {
+    /// Full path, with `/` as separator even on Windows servers
+    pub path: String,
+    /// Last component of path
+    pub dirname: String,
+    /// Depth of directory, starting from 0 for top level
+    pub level: u32,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/filesystem`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct FilesystemDirectories {
+    /// Whether server uses `/` as path separator, i.e. is not running on Windows
+    pub posix_separator: bool,
+    pub directories: Vec<FilesystemDirectory>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/items/batch/get`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryItemsBatch {
+    pub library_items: Vec<LibraryItem>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/items/batch/update`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BatchUpdate {
+    /// Number of items changed
+    pub updates: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to renaming or deleting tag or genre
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TagsUpdate {
+    /// Tag or genre was renamed to existing one, and merged into it
+    #[serde(default, alias = "tagMerged", alias = "genreMerged")]
+    pub merged: bool,
+    pub num_items_updated: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/libraries/<ID>/search`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibrarySearchResult {
+    #[serde(default)]
+    pub book: Vec<SearchItemMatch>,
+    #[serde(default)]
+    pub podcast: Vec<SearchItemMatch>,
+    #[serde(default)]
+    pub authors: Vec<SearchAuthorMatch>,
+    #[serde(default)]
+    pub series: Vec<SearchSeriesMatch>,
+    #[serde(default)]
+    pub tags: Vec<SearchTagMatch>,
+    #[serde(default)]
+    pub genres: Vec<SearchTagMatch>,
+    #[serde(default)]
+    pub narrators: Vec<SearchNarratorMatch>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SearchItemMatch {
+    pub library_item: LibraryItem,
+    /// Field query matched, e.g. `title` or `subtitle`
+    pub match_key: Option<String>,
+    pub match_text: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SearchAuthorMatch {
+    pub id: Id<Author>,
+    pub name: String,
+    #[serde(default)]
+    pub num_books: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SearchSeriesMatch {
+    pub series: Series,
+    pub books: Vec<LibraryItem>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SearchTagMatch {
+    pub name: String,
+    pub num_items: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SearchNarratorMatch {
+    pub name: String,
+    pub num_books: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PaginatedResponse<T> {
+    pub results: Vec<T>,
+    pub total: usize,
+    pub limit: usize,
+    pub page: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryItem {
+    pub id: Id<LibraryItem>,
+    pub library_id: Id<Library>,
+    pub folder_id: Id<Folder>,
+    pub path: String,
+    pub rel_path: String,
+    pub is_file: bool,
+    #[serde(with = "timestamp")]
+    pub mtime_ms: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub ctime_ms: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub birthtime_ms: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub added_at: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(with = "timestamp_option")]
+    pub last_scan: Option<DateTime<Utc>>,
+    pub scan_version: Option<String>,
     pub is_missing: bool,
     pub is_invalid: bool,
     #[serde(flatten)]
     pub media: LibraryMedia,
     pub library_files: Vec<LibraryFile>,
+    /// Public share of book, sent to admins only
+    #[serde(default)]
+    pub media_item_share: Option<MediaItemShare>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LibraryItemMinified {
     pub id: Id<LibraryItem>,
     pub library_id: Id<Library>,
@@ -213,34 +1837,65 @@ pub struct LibraryItemMinified {
     pub path: String,
     pub rel_path: String,
     pub is_file: bool,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub mtime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub ctime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub birthtime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub added_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub updated_at: DateTime<Utc>,
     pub is_missing: bool,
     pub is_invalid: bool,
     #[serde(flatten)]
     pub media: LibraryMediaMinified,
+    /// Series this item stands for, if requested with `collapse_series`
+    #[serde(default)]
+    pub collapsed_series: Option<CollapsedSeries>,
+    #[serde(default)]
+    pub rss_feed: Option<Feed>,
+    #[serde(default)]
+    pub num_episodes_incomplete: Option<usize>,
+}
+
+/// Series standing in for its books in collapsed library listing
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CollapsedSeries {
+    pub id: Id<Series>,
+    pub name: String,
+    pub name_ignore_prefix: Option<String>,
+    pub num_books: usize,
+    #[serde(default)]
+    pub library_item_ids: Vec<Id<LibraryItem>>,
+    /// Sequences of books in series, e.g. `1-3, 5`
+    pub series_sequence_list: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(tag = "mediaType", content = "media")]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum LibraryMedia {
     #[serde(rename_all = "camelCase")]
     Book {
+        /// Id of book itself, as opposed to its library item. Sent by newer servers.
+        #[serde(default)]
+        id: Option<String>,
         library_item_id: Id<LibraryItem>,
         metadata: BookMetadata,
         cover_path: Option<String>,
         tags: Vec<String>,
         audio_files: Vec<AudioFile>,
         chapters: Vec<Chapter>,
+        #[serde(default)]
+        ebook_file: Option<Box<EbookFile>>,
     },
     #[serde(rename_all = "camelCase")]
     Podcast {
@@ -257,15 +1912,18 @@ pub enum LibraryMedia {
     },
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(tag = "mediaType", content = "media")]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum LibraryMediaMinified {
+    #[serde(rename_all = "camelCase")]
     Book {
         metadata: BookMetadataMinified,
         cover_path: Option<String>,
         tags: Vec<String>,
     },
+    #[serde(rename_all = "camelCase")]
     Podcast {
         metadata: PodcastMetadataMinified,
         cover_path: Option<String>,
@@ -278,8 +1936,9 @@ pub enum LibraryMediaMinified {
     },
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PodcastEpisode {
     pub library_item_id: Id<LibraryItem>,
     pub id: Id<PodcastEpisode>,
@@ -292,16 +1951,20 @@ pub struct PodcastEpisode {
     pub description: String,
     pub pub_date: String,
     pub audio_file: AudioFile,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub published_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub added_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub updated_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PodcastMetadata {
     pub title: Option<String>,
     pub author: Option<String>,
@@ -317,10 +1980,184 @@ pub struct PodcastMetadata {
     pub language: Option<String>,
     #[serde(rename = "type")]
     pub type_: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Element of response to `GET /api/search/podcast`, describing podcast found on iTunes
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PodcastSearchResult {
+    /// iTunes collection id
+    pub id: i64,
+    pub artist_id: Option<i64>,
+    pub title: String,
+    pub artist_name: Option<String>,
+    pub description: Option<String>,
+    pub description_plain: Option<String>,
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    pub cover: Option<String>,
+    pub track_count: Option<u32>,
+    pub feed_url: Option<String>,
+    pub page_url: Option<String>,
+    #[serde(default)]
+    pub explicit: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/podcasts/feed`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PodcastFeedResponse {
+    pub podcast: PodcastFeed,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Public link to book, allowing to listen to it without account.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct MediaItemShare {
+    pub id: Id<MediaItemShare>,
+    /// Only `book` is supported by server
+    pub media_item_type: String,
+    /// Id of book, as opposed to its library item
+    pub media_item_id: String,
+    /// Last part of share link
+    pub slug: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub is_downloadable: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/podcasts/<ID>/match-episodes`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct EpisodesMatch {
+    pub num_episodes_updated: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Podcast subscription listed in OPML file
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct OpmlFeed {
+    pub title: Option<String>,
+    pub feed_url: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST /api/podcasts/opml/parse`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct OpmlFeeds {
+    pub feeds: Vec<OpmlFeed>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Podcast parsed from RSS feed, not yet added to library
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PodcastFeed {
+    pub metadata: PodcastFeedMetadata,
+    #[serde(default)]
+    pub episodes: Vec<PodcastFeedEpisode>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PodcastFeedMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub description_plain: Option<String>,
+    pub language: Option<String>,
+    pub explicit: Option<String>,
+    pub pub_date: Option<String>,
+    pub link: Option<String>,
+    /// Cover url
+    pub image: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    pub feed_url: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Episode of podcast feed. Sent back as is to download it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PodcastFeedEpisode {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub description: Option<String>,
+    pub description_plain: Option<String>,
+    pub pub_date: Option<String>,
+    pub episode_type: Option<String>,
+    pub season: Option<String>,
+    pub episode: Option<String>,
+    pub author: Option<String>,
+    /// Duration as written in feed, e.g. `01:02:03`
+    pub duration: Option<String>,
+    pub explicit: Option<String>,
+    #[serde(default, with = "timestamp_option")]
+    pub published_at: Option<DateTime<Utc>>,
+    pub guid: Option<String>,
+    pub enclosure: Option<PodcastEnclosure>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Audio file of podcast episode
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PodcastEnclosure {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub length: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PodcastMetadataMinified {
     pub title_ignore_prefix: String,
     pub title: Option<String>,
@@ -337,17 +2174,21 @@ pub struct PodcastMetadataMinified {
     pub language: Option<String>,
     #[serde(rename = "type")]
     pub type_: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AudioFile {
     pub index: usize,
     pub ino: String,
     pub metadata: FileMetadata,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub added_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub updated_at: DateTime<Utc>,
     pub track_num_from_meta: Option<u32>,
     pub disc_num_from_meta: Option<u32>,
@@ -360,26 +2201,30 @@ pub struct AudioFile {
     pub duration: f64,
     pub bit_rate: u32,
     pub language: Option<String>,
-    pub codec: String,
+    pub codec: Codec,
     pub time_base: String,
     pub channels: u32,
     pub channel_layout: String,
     pub chapters: Vec<Chapter>,
     pub embedded_cover_art: Option<String>,
-    pub mime_type: String,
+    pub mime_type: MimeType,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Chapter {
     pub id: usize,
-    pub start: f64,
-    pub end: f64,
+    pub start: Seconds,
+    pub end: Seconds,
     pub title: String,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct BookMetadata {
     pub title: Option<String>,
     pub subtitle: Option<String>,
@@ -395,10 +2240,14 @@ pub struct BookMetadata {
     pub asin: Option<String>,
     pub language: Option<String>,
     pub explicit: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct BookMetadataMinified {
     pub title: Option<String>,
     pub subtitle: Option<String>,
@@ -417,42 +2266,75 @@ pub struct BookMetadataMinified {
     pub asin: Option<String>,
     pub language: Option<String>,
     pub explicit: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LibraryFile {
     pub ino: String,
     pub metadata: FileMetadata,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub added_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub updated_at: DateTime<Utc>,
     pub file_type: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Ebook file of book, such as EPUB or PDF.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct EbookFile {
+    pub ino: String,
+    pub metadata: FileMetadata,
+    /// File extension, such as `epub` or `pdf`
+    pub ebook_format: String,
+    #[serde(with = "timestamp")]
+    pub added_at: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub updated_at: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct FileMetadata {
     pub filename: String,
     pub ext: String,
     pub path: String,
     pub rel_path: String,
     pub size: usize,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub mtime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub ctime_ms: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub birthtime_ms: DateTime<Utc>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct Episode {}
+/// Binary response, such as cover image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
 pub enum Progress {
     Finished,
     NotStarted,
@@ -460,58 +2342,286 @@ pub enum Progress {
     InProgress,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PlaybackSession {
     pub id: Id<PlaybackSession>,
     pub user_id: Id<UserData>,
     pub library_id: Id<Library>,
     pub library_item_id: Id<LibraryItem>,
-    pub episode_id: Option<Id<Episode>>,
+    pub episode_id: Option<Id<PodcastEpisode>>,
     #[serde(flatten)]
     pub playback_media: PlaybackMedia,
     pub display_title: String,
     pub display_author: String,
-    pub cover_path: String,
-    pub duration: f64,
+    /// Path of cover on server, `None` if item has no cover
+    pub cover_path: Option<String>,
+    pub duration: Seconds,
     pub play_method: PlayMethod,
+    /// Name of player reported by client, such as `html5`
     pub media_player: String,
+    /// Device session was opened from, as reported on opening
     pub device_info: DeviceInfo,
     pub server_version: String,
+    /// Date session was started, as `YYYY-MM-DD`
     pub date: String,
     pub day_of_week: String,
-    pub time_listening: f64,
-    pub start_time: f64,
-    pub current_time: f64,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    /// Total time listened during session
+    pub time_listening: Seconds,
+    /// Position session was started at
+    pub start_time: Seconds,
+    pub current_time: Seconds,
+    #[serde(with = "timestamp")]
     pub started_at: DateTime<Utc>,
-    #[serde(deserialize_with = "deserialize_timestamp")]
+    #[serde(with = "timestamp")]
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+impl PlaybackSession {
+    /// Chapters of played book or episode.
+    pub fn chapters(&self) -> &[Chapter] {
+        match &self.playback_media {
+            PlaybackMedia::Book { chapters, .. } | PlaybackMedia::Podcast { chapters, .. } => {
+                chapters
+            }
+            PlaybackMedia::Unknown => &[],
+        }
+    }
+}
+
+/// Response to `POST /api/session/local-all`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LocalSessionResults {
+    pub results: Vec<LocalSessionResult>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Outcome of single session reported with `UserClient::sync_local_sessions`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LocalSessionResult {
+    pub id: String,
+    pub success: bool,
+    /// Whether media progress was updated, as opposed to being newer on server
+    #[serde(default)]
+    pub progress_synced: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Session returned on starting playback, with tracks to play.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct PlaybackSessionExtended {
     #[serde(flatten)]
     pub playback_session: PlaybackSession,
+    /// Empty for video podcast episodes, which are played from `video_track`
     pub audio_tracks: Vec<AudioTrack>,
+    #[serde(default)]
+    pub video_track: Option<VideoTrack>,
+    /// Played item, included by server on starting playback
+    #[serde(default)]
+    pub library_item: Option<Box<LibraryItem>>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct AudioTrack {
+#[non_exhaustive]
+pub struct VideoTrack {
     pub index: usize,
-    pub start_offset: f64,
-    pub duration: f64,
+    pub duration: Seconds,
     pub title: String,
     pub content_url: String,
     pub mime_type: String,
+    pub codec: Option<String>,
+    pub metadata: Option<FileMetadata>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AudioTrack {
+    pub index: usize,
+    pub start_offset: Seconds,
+    pub duration: Seconds,
+    pub title: String,
+    pub content_url: String,
+    pub mime_type: MimeType,
     pub metadata: Option<FileMetadata>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Mime type of audio file, as reported by server and listed in
+/// `PlayLibraryItemParams::supported_mime_types`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum MimeType {
+    /// `audio/mpeg`, used for mp3
+    Mpeg,
+    /// `audio/mp4`, used for m4a and m4b
+    Mp4,
+    Aac,
+    /// `audio/ogg`, used for both vorbis and opus
+    Ogg,
+    Flac,
+    Webm,
+    Wav,
+    /// Mime type not listed here
+    Other(String),
+}
+
+/// Audio codec, by its `ffprobe` name.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Codec {
+    Mp3,
+    Aac,
+    Alac,
+    Vorbis,
+    Opus,
+    Flac,
+    /// Signed 16 bit little endian PCM, usual content of wav
+    PcmS16le,
+    /// Codec not listed here
+    Other(String),
+}
+
+impl MimeType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mpeg => "audio/mpeg",
+            Self::Mp4 => "audio/mp4",
+            Self::Aac => "audio/aac",
+            Self::Ogg => "audio/ogg",
+            Self::Flac => "audio/flac",
+            Self::Webm => "audio/webm",
+            Self::Wav => "audio/wav",
+            Self::Other(mime_type) => mime_type,
+        }
+    }
+
+    /// Mime types files decodable with `decoders` can be served as, for
+    /// `PlayLibraryItemParams::supported_mime_types`. Server transcodes files of other
+    /// types.
+    pub fn supported(decoders: &[Codec]) -> Vec<MimeType> {
+        let mut supported: Vec<MimeType> = vec![];
+        for mime_type in decoders.iter().flat_map(Codec::mime_types) {
+            if !supported.contains(mime_type) {
+                supported.push(mime_type.clone());
+            }
+        }
+        supported
+    }
+}
+
+impl Codec {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Aac => "aac",
+            Self::Alac => "alac",
+            Self::Vorbis => "vorbis",
+            Self::Opus => "opus",
+            Self::Flac => "flac",
+            Self::PcmS16le => "pcm_s16le",
+            Self::Other(codec) => codec,
+        }
+    }
+
+    /// Containers this codec is served in.
+    pub fn mime_types(&self) -> &'static [MimeType] {
+        match self {
+            Self::Mp3 => &[MimeType::Mpeg],
+            Self::Aac => &[MimeType::Mp4, MimeType::Aac],
+            Self::Alac => &[MimeType::Mp4],
+            Self::Vorbis => &[MimeType::Ogg, MimeType::Webm],
+            Self::Opus => &[MimeType::Ogg, MimeType::Webm],
+            Self::Flac => &[MimeType::Flac],
+            Self::PcmS16le => &[MimeType::Wav],
+            Self::Other(_) => &[],
+        }
+    }
+}
+
+impl From<String> for MimeType {
+    fn from(mime_type: String) -> Self {
+        match mime_type.as_str() {
+            "audio/mpeg" => Self::Mpeg,
+            "audio/mp4" => Self::Mp4,
+            "audio/aac" => Self::Aac,
+            "audio/ogg" => Self::Ogg,
+            "audio/flac" => Self::Flac,
+            "audio/webm" => Self::Webm,
+            "audio/wav" => Self::Wav,
+            _ => Self::Other(mime_type),
+        }
+    }
+}
+
+impl From<MimeType> for String {
+    fn from(mime_type: MimeType) -> Self {
+        match mime_type {
+            MimeType::Other(mime_type) => mime_type,
+            mime_type => mime_type.as_str().to_string(),
+        }
+    }
+}
+
+impl Display for MimeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for Codec {
+    fn from(codec: String) -> Self {
+        match codec.as_str() {
+            "mp3" => Self::Mp3,
+            "aac" => Self::Aac,
+            "alac" => Self::Alac,
+            "vorbis" => Self::Vorbis,
+            "opus" => Self::Opus,
+            "flac" => Self::Flac,
+            "pcm_s16le" => Self::PcmS16le,
+            _ => Self::Other(codec),
+        }
+    }
+}
+
+impl From<Codec> for String {
+    fn from(codec: Codec) -> Self {
+        match codec {
+            Codec::Other(codec) => codec,
+            codec => codec.as_str().to_string(),
+        }
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+impl Display for Codec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(tag = "mediaType")]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum PlaybackMedia {
     #[serde(rename_all = "camelCase")]
     Book {
@@ -519,20 +2629,40 @@ pub enum PlaybackMedia {
         chapters: Vec<Chapter>,
     },
     #[serde(rename_all = "camelCase")]
-    Podcast { media_metadata: PodcastMetadata },
+    Podcast {
+        media_metadata: PodcastMetadata,
+        /// Chapters of played episode
+        #[serde(default)]
+        chapters: Vec<Chapter>,
+    },
+    /// Media type not known to this crate
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Deserialize_repr, Debug, Clone, PartialEq)]
+#[derive(Deserialize_repr, Serialize_repr, Debug, Clone, PartialEq)]
 #[repr(u8)]
+#[non_exhaustive]
 pub enum PlayMethod {
+    /// Original files are played, fetched from track `content_url`
     DirectPlay = 0,
     DirectStream = 1,
+    /// Server transcodes media into HLS stream
     Transcode = 2,
+    /// Played from files downloaded to device
     Local = 3,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+impl PlayMethod {
+    /// Whether tracks refer to original files, rather than transcoded stream.
+    pub fn is_direct(&self) -> bool {
+        matches!(self, Self::DirectPlay | Self::DirectStream)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DeviceInfo {
     pub id: Id<DeviceInfo>,
     pub user_id: Id<UserData>,
@@ -549,9 +2679,328 @@ pub struct DeviceInfo {
     pub sdk_version: Option<String>,
     pub client_name: Option<String>,
     pub client_version: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Payload of `user_item_progress_updated` event
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct MediaProgressUpdate {
+    pub id: Id<MediaProgress>,
+    pub session_id: Option<Id<PlaybackSession>>,
+    pub device_description: Option<String>,
+    pub data: MediaProgress,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/podcasts/<ID>/checknew`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NewEpisodes {
+    #[serde(default)]
+    pub episodes: Vec<PodcastFeedEpisode>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/podcasts/<ID>/downloads`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct EpisodeDownloads {
+    pub downloads: Vec<EpisodeDownload>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/libraries/<ID>/episode-downloads`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct EpisodeDownloadQueue {
+    pub current_download: Option<EpisodeDownload>,
+    pub queue: Vec<EpisodeDownload>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct EpisodeDownload {
+    pub id: String,
+    pub episode_display_title: Option<String>,
+    pub url: Option<String>,
+    pub library_item_id: Id<LibraryItem>,
+    pub library_id: Option<Id<Library>>,
+    pub podcast_title: Option<String>,
+    #[serde(default)]
+    pub is_finished: bool,
+    #[serde(default)]
+    pub failed: bool,
+    #[serde(default)]
+    #[serde(with = "timestamp_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[serde(with = "timestamp_option")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    #[serde(with = "timestamp_option")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Backup {
+    pub id: String,
+    pub filename: Option<String>,
+    pub path: Option<String>,
+    pub full_path: Option<String>,
+    pub file_size: Option<u64>,
+    pub server_version: Option<String>,
+    #[serde(default)]
+    #[serde(with = "timestamp_option")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Element of response to `GET /api/search/books`, candidate book metadata from provider
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BookMatch {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub author: Option<String>,
+    pub narrator: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<String>,
+    pub description: Option<String>,
+    /// Cover url
+    pub cover: Option<String>,
+    pub isbn: Option<String>,
+    pub asin: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub series: Vec<BookMatchSeries>,
+    pub language: Option<String>,
+    /// Duration in minutes
+    pub duration: Option<f64>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BookMatchSeries {
+    pub series: String,
+    pub sequence: Option<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/search/covers`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CoverSearchResult {
+    /// Urls of found covers
+    pub results: Vec<String>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `GET /api/search/chapters`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum ChapterSearchResult {
+    Found(AudnexusChapters),
+    NotFound { error: String },
+}
+
+/// Chapters of audible book, as provided by Audnexus
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AudnexusChapters {
+    pub asin: String,
+    #[serde(default)]
+    pub brand_intro_duration_ms: u64,
+    #[serde(default)]
+    pub brand_outro_duration_ms: u64,
+    pub chapters: Vec<AudnexusChapter>,
+    #[serde(default)]
+    pub is_accurate: bool,
+    pub runtime_length_ms: u64,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AudnexusChapter {
+    pub length_ms: u64,
+    pub start_offset_ms: u64,
+    pub title: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl AudnexusChapters {
+    /// Chapters in form accepted by `update_item_chapters`.
+    pub fn to_chapters(&self) -> Vec<Chapter> {
+        self.chapters
+            .iter()
+            .enumerate()
+            .map(|(id, chapter)| Chapter {
+                id,
+                start: Seconds::from_millis(chapter.start_offset_ms),
+                end: Seconds::from_millis(chapter.start_offset_ms + chapter.length_ms),
+                title: chapter.title.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Response to `POST /api/items/<ID>/chapters`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ChaptersUpdate {
+    pub success: bool,
+    pub updated: bool,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Response to `POST` and `PATCH` of `/api/items/<ID>/cover`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CoverUpdate {
+    pub success: bool,
+    /// Path of cover on server
+    pub cover: String,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Payload of `batch_quickmatch_complete` event
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BatchQuickMatchResult {
+    pub success: bool,
+    /// Number of updated items
+    pub updates: usize,
+    /// Number of items no match was found for
+    pub unmatched: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Payload of `scan_start`, `scan_progress` and `scan_complete` events
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryScan {
+    /// Id of scanned library
+    pub id: Id<Library>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Name of scanned library
+    pub name: String,
+    #[serde(default)]
+    pub progress: Option<LibraryScanProgress>,
+    #[serde(default)]
+    pub results: Option<LibraryScanResults>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryScanProgress {
+    pub total: usize,
+    pub done: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct LibraryScanResults {
+    pub added: usize,
+    pub updated: usize,
+    pub missing: usize,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Payload of `user_stream_update` event, sent then user starts or updates playback session
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct OnlineUser {
+    pub id: Id<UserData>,
+    pub username: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub session: Option<PlaybackSession>,
+    #[serde(default, with = "timestamp_option")]
+    pub last_seen: Option<DateTime<Utc>>,
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 impl Progress {
+    pub fn parse(progress: &str) -> Option<Self> {
+        [
+            Self::Finished,
+            Self::NotStarted,
+            Self::NotFinished,
+            Self::InProgress,
+        ]
+        .into_iter()
+        .find(|known| known.as_str() == progress)
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Finished => "finished",
@@ -562,7 +3011,29 @@ impl Progress {
     }
 }
 
+impl MediaProgress {
+    /// Part of media listened, from 0 to 100.
+    pub fn percent_complete(&self) -> f64 {
+        (self.progress * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Time left until the end of media.
+    pub fn remaining(&self) -> Seconds {
+        if self.is_finished {
+            return Seconds::ZERO;
+        }
+        Seconds((self.duration - self.current_time).0.max(0.0))
+    }
+
+    /// Started, but not finished.
+    pub fn is_in_progress(&self) -> bool {
+        !self.is_finished && self.current_time > Seconds::ZERO
+    }
+}
+
 impl UserData {
+    /// Item listened most recently, which is not finished and not hidden from
+    /// "Continue listening" shelf.
     pub fn currently_listening(&self) -> Option<Id<LibraryItem>> {
         self.media_progress
             .iter()
@@ -570,4 +3041,35 @@ impl UserData {
             .max_by_key(|progress| progress.last_update)
             .map(|progress| progress.library_item_id.clone())
     }
+
+    /// Progress updated most recently, including finished items.
+    pub fn most_recent_progress(&self) -> Option<&MediaProgress> {
+        self.media_progress
+            .iter()
+            .max_by_key(|progress| progress.last_update)
+    }
+
+    /// Progress of item, or of podcast episode if `episode_id` is given.
+    pub fn progress_for(
+        &self,
+        library_item_id: &Id<LibraryItem>,
+        episode_id: Option<&Id<PodcastEpisode>>,
+    ) -> Option<&MediaProgress> {
+        self.media_progress.iter().find(|progress| {
+            &progress.library_item_id == library_item_id
+                && progress.episode_id.as_ref() == episode_id
+        })
+    }
+
+    pub fn finished(&self) -> impl Iterator<Item = &MediaProgress> {
+        self.media_progress
+            .iter()
+            .filter(|progress| progress.is_finished)
+    }
+
+    pub fn in_progress(&self) -> impl Iterator<Item = &MediaProgress> {
+        self.media_progress
+            .iter()
+            .filter(|progress| progress.is_in_progress())
+    }
 }
@@ -0,0 +1,215 @@
+//! Several servers used from one process, such as separate servers for books and
+//! podcasts, or home and remote instances.
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Display};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use futures_util::future;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{InvalidId, UrlError};
+use crate::schema::Id;
+use crate::{ClientConfig, Credentials, UserClient};
+
+/// Clients of servers, identified by names chosen by user.
+#[derive(Clone, Default)]
+pub struct ServerRegistry {
+    servers: BTreeMap<String, UserClient>,
+}
+
+/// Address and credentials of registered server, for persisting registry between runs.
+///
+/// Only tokens are kept, passwords are never saved.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedServer {
+    pub name: String,
+    pub url: Url,
+    pub token: String,
+    /// Refresh token, for servers supporting them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+/// Id of entity of type `T` on one of registered servers.
+///
+/// Written as `<server>/<id>`, which is also its serialized form.
+pub struct Tagged<T> {
+    pub server: String,
+    pub id: Id<T>,
+}
+
+impl ServerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry of saved servers. `configure` is applied to config of each server, e.g.
+    /// to set timeouts or retries.
+    pub fn restore(
+        saved: impl IntoIterator<Item = SavedServer>,
+        configure: impl Fn(ClientConfig) -> ClientConfig,
+    ) -> Result<Self, UrlError> {
+        let mut registry = Self::new();
+        for server in saved {
+            let config = configure(ClientConfig::new(server.url)?);
+            let mut client = UserClient::from_token(config, server.token);
+            if let Some(refresh_token) = server.refresh_token {
+                client = client.with_credentials(Credentials::RefreshToken(refresh_token));
+            }
+            registry.insert(server.name, client);
+        }
+        Ok(registry)
+    }
+
+    /// Register `client` under `name`, returning client previously registered with it.
+    pub fn insert(&mut self, name: impl Into<String>, client: UserClient) -> Option<UserClient> {
+        self.servers.insert(name.into(), client)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<UserClient> {
+        self.servers.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UserClient> {
+        self.servers.get(name)
+    }
+
+    /// Client of server `id` belongs to.
+    pub fn client_for<T>(&self, id: &Tagged<T>) -> Option<&UserClient> {
+        self.get(&id.server)
+    }
+
+    /// Names of registered servers, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.servers.keys().map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &UserClient)> {
+        self.servers
+            .iter()
+            .map(|(name, client)| (name.as_str(), client))
+    }
+
+    pub fn len(&self) -> usize {
+        self.servers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.servers.is_empty()
+    }
+
+    /// Run `func` against every server concurrently, e.g. to search all of them.
+    /// Results are paired with server names, in order of `names`.
+    pub async fn gather<'a, F, Fut>(&'a self, func: F) -> Vec<(&'a str, Fut::Output)>
+    where
+        F: Fn(&'a UserClient) -> Fut,
+        Fut: Future,
+    {
+        let requests = self.iter().map(|(name, client)| {
+            let request = func(client);
+            async move { (name, request.await) }
+        });
+        future::join_all(requests).await
+    }
+
+    /// Current credentials of registered servers, to be passed to `restore`.
+    pub fn saved(&self) -> Vec<SavedServer> {
+        self.iter()
+            .map(|(name, client)| {
+                let refresh_token = match &*client.credentials.read().unwrap() {
+                    Some(Credentials::RefreshToken(refresh_token)) => Some(refresh_token.clone()),
+                    _ => None,
+                };
+                SavedServer {
+                    name: name.to_string(),
+                    url: client.config.root_url().clone(),
+                    token: client.token(),
+                    refresh_token,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Debug for ServerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.iter()
+                    .map(|(name, client)| (name, client.config.root_url().as_str())),
+            )
+            .finish()
+    }
+}
+
+impl<T> Tagged<T> {
+    pub fn new(server: impl Into<String>, id: Id<T>) -> Self {
+        Self {
+            server: server.into(),
+            id,
+        }
+    }
+}
+
+impl<T> Clone for Tagged<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.server.clone(), self.id.clone())
+    }
+}
+
+impl<T> PartialEq for Tagged<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.server == other.server && self.id == other.id
+    }
+}
+
+impl<T> Eq for Tagged<T> {}
+
+impl<T> Hash for Tagged<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.server.hash(state);
+        self.id.hash(state);
+    }
+}
+
+impl<T> Debug for Tagged<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tagged({self})")
+    }
+}
+
+impl<T> Display for Tagged<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.server, self.id)
+    }
+}
+
+impl<T> FromStr for Tagged<T> {
+    type Err = InvalidId;
+
+    /// Split at last `/`, as ids never contain it, while server names may.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (server, id) = text
+            .rsplit_once('/')
+            .filter(|(server, _)| !server.is_empty())
+            .ok_or_else(|| InvalidId(text.to_string()))?;
+        Ok(Self::new(server, Id::parse(id)?))
+    }
+}
+
+impl<T> Serialize for Tagged<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Tagged<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,84 @@
+//! Keeping playback session alive by reporting position periodically.
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::params::SyncSessionParams;
+use crate::schema::{Id, PlaybackSession, Seconds};
+use crate::UserClient;
+
+/// Background task syncing session position. Session is synced one last time and closed
+/// once heartbeat is stopped or dropped.
+pub struct SessionHeartbeat {
+    stop: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl SessionHeartbeat {
+    /// Stop syncing and wait until session is closed.
+    pub async fn stop(mut self) {
+        self.stop.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for SessionHeartbeat {
+    fn drop(&mut self) {
+        self.stop.cancel();
+    }
+}
+
+impl UserClient {
+    /// Spawn task syncing session every `interval`, with position given by
+    /// `position_source`. Time listened is counted while position advances.
+    ///
+    /// Failed syncs are skipped, to be retried on next tick.
+    pub fn start_session_heartbeat(
+        &self,
+        session_id: Id<PlaybackSession>,
+        interval: Duration,
+        position_source: impl Fn() -> Seconds + Send + 'static,
+    ) -> SessionHeartbeat {
+        let stop = CancellationToken::new();
+        let client = self.clone();
+        let task = tokio::spawn({
+            let stop = stop.clone();
+            async move {
+                let mut ticks = tokio::time::interval(interval);
+                ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                let mut last_position = position_source();
+                let mut last_sync = Instant::now();
+                loop {
+                    let stopped = tokio::select! {
+                        _ = ticks.tick() => false,
+                        _ = stop.cancelled() => true,
+                    };
+                    let position = position_source();
+                    let advanced = (position - last_position).as_f64().max(0.0);
+                    let elapsed = last_sync.elapsed().as_secs_f64();
+                    let params = SyncSessionParams {
+                        current_time: position,
+                        time_listened: Seconds(advanced.min(elapsed)),
+                        duration: None,
+                    };
+                    if client.sync_session(&session_id, &params).await.is_ok() {
+                        last_position = position;
+                        last_sync = Instant::now();
+                    }
+                    if stopped {
+                        let _ = client.close_session(&session_id).await;
+                        return;
+                    }
+                }
+            }
+        });
+        SessionHeartbeat {
+            stop,
+            task: Some(task),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Metrics of requests and transferred data, emitted via `metrics` facade. Install any
+//! `metrics` recorder, such as `metrics-exporter-prometheus`, to collect them.
+//!
+//! Endpoints are labeled by url path with segments containing digits, such as ids,
+//! replaced with `:id`, so the number of series stays bounded.
+use std::future::Future;
+use std::time::Instant;
+
+use crate::errors::{FusedError, ResponseError};
+use crate::Url;
+
+/// Counter of finished requests, labeled by `method`, `endpoint` and `status`. Status is
+/// `error` for requests which got no response.
+pub const REQUESTS: &str = "audiobookshelf_requests_total";
+/// Histogram of request latency in seconds, including retries, labeled by `method` and
+/// `endpoint`.
+pub const REQUEST_DURATION: &str = "audiobookshelf_request_duration_seconds";
+/// Counter of retried attempts, labeled by `method` and `endpoint`.
+pub const RETRIES: &str = "audiobookshelf_retries_total";
+/// Counter of bytes received by audio streams and file downloads, labeled by `kind`,
+/// either `stream` or `download`.
+pub const BYTES_RECEIVED: &str = "audiobookshelf_bytes_received_total";
+
+/// Method and endpoint of request.
+pub(crate) struct Labels {
+    method: String,
+    endpoint: String,
+}
+
+impl Labels {
+    pub fn of(request: &reqwest::Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            endpoint: endpoint(request.url()),
+        }
+    }
+}
+
+/// Run request `future`, recording its outcome and latency.
+pub(crate) async fn instrument<F>(labels: Labels, future: F) -> F::Output
+where
+    F: Future<Output = Result<reqwest::Response, FusedError<ResponseError>>>,
+{
+    let Labels { method, endpoint } = labels;
+    let start = Instant::now();
+    let result = future.await;
+    let status = match &result {
+        Ok(response) => response.status().as_u16().to_string(),
+        Err(FusedError::DomainError(error)) => error.status.as_u16().to_string(),
+        Err(FusedError::APIError(_)) => "error".to_string(),
+    };
+    metrics::histogram!(REQUEST_DURATION, "method" => method.clone(), "endpoint" => endpoint.clone())
+        .record(start.elapsed().as_secs_f64());
+    metrics::counter!(REQUESTS, "method" => method, "endpoint" => endpoint, "status" => status)
+        .increment(1);
+    result
+}
+
+pub(crate) fn retry(request: &reqwest::Request) {
+    let Labels { method, endpoint } = Labels::of(request);
+    metrics::counter!(RETRIES, "method" => method, "endpoint" => endpoint).increment(1);
+}
+
+pub(crate) fn bytes_received(kind: &'static str, bytes: usize) {
+    metrics::counter!(BYTES_RECEIVED, "kind" => kind).increment(bytes as u64);
+}
+
+/// Path of `url` with ids replaced by `:id`.
+pub(crate) fn endpoint(url: &Url) -> String {
+    let Some(segments) = url.path_segments() else {
+        return url.path().to_string();
+    };
+    segments
+        .map(|segment| {
+            if segment.bytes().any(|byte| byte.is_ascii_digit()) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .fold(String::new(), |path, segment| path + "/" + segment)
+}
@@ -0,0 +1,110 @@
+//! Instrumentation of requests with `tracing` spans.
+use std::future::Future;
+use std::time::Instant;
+
+use serde_json::Value;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+use crate::errors::{FusedError, ResponseError};
+use crate::Url;
+
+/// JSON fields holding secrets, which are never logged.
+const SECRET_FIELDS: &[&str] = &["token", "accessToken", "refreshToken", "password", "apiKey"];
+
+/// Span covering single request, with status, latency and response size recorded later.
+pub(crate) fn request_span(request: &reqwest::Request) -> Span {
+    let span = tracing::debug_span!(
+        "request",
+        method = %request.method(),
+        url = %redact_url(request.url()),
+        status = Empty,
+        latency_ms = Empty,
+        size = Empty,
+    );
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        let body = String::from_utf8_lossy(body);
+        span.in_scope(|| tracing::trace!(body = %redact(&body), "request body"));
+    }
+    span
+}
+
+/// Run request `future` inside `span`, recording outcome once finished.
+pub(crate) async fn instrument<F>(span: Span, future: F) -> F::Output
+where
+    F: Future<Output = Result<reqwest::Response, FusedError<ResponseError>>>,
+{
+    let start = Instant::now();
+    let result = future.instrument(span.clone()).await;
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    match &result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            if let Some(size) = response.content_length() {
+                span.record("size", size);
+            }
+            span.in_scope(|| tracing::debug!("request finished"));
+        }
+        Err(FusedError::DomainError(error)) => {
+            span.record("status", error.status.as_u16());
+            span.in_scope(|| tracing::debug!(%error, "request failed"));
+        }
+        Err(FusedError::APIError(error)) => {
+            span.in_scope(|| tracing::debug!(%error, "request failed"));
+        }
+    }
+    result
+}
+
+/// Log response body at trace level.
+pub(crate) fn response_body(url: &Url, body: &str) {
+    tracing::trace!(url = %redact_url(url), size = body.len(), body = %redact(body), "response body");
+}
+
+/// Replace values of secret fields in JSON `body`. Bodies which are not JSON are left as is.
+pub(crate) fn redact(body: &str) -> String {
+    fn redact_value(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if SECRET_FIELDS.contains(&key.as_str()) {
+                        *value = Value::String("<redacted>".to_string());
+                    } else {
+                        redact_value(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(redact_value),
+            _ => {}
+        }
+    }
+
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+/// Url with `token` query parameter hidden.
+fn redact_url(url: &Url) -> Url {
+    if !url.query_pairs().any(|(key, _)| key == "token") {
+        return url.clone();
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = if key == "token" {
+                "<redacted>".to_string()
+            } else {
+                value.into_owned()
+            };
+            (key.into_owned(), value)
+        })
+        .collect();
+    let mut url = url.clone();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    url
+}
@@ -0,0 +1,204 @@
+//! Sending of built requests, replaceable to run client against canned responses.
+use std::fmt::Debug;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use reqwest::{Method, StatusCode};
+
+use crate::errors::RequestInfo;
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<reqwest::Response, reqwest::Error>> + Send + 'a>>;
+
+/// Executes requests made by `UserClient`. Implemented by `reqwest::Client`, which is used
+/// by default.
+pub trait Transport: Debug + Send + Sync {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_>;
+}
+
+impl Transport for reqwest::Client {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(reqwest::Client::execute(self, request))
+    }
+}
+
+/// Transport answering with canned responses, matched by method and url path.
+/// Requests without matching response are answered with 404. Successful responses honor
+/// `Range` header of form `bytes=<start>-<end>`.
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use audiobookshelf_api::{ClientConfig, Method, Url, UserClient};
+/// # use audiobookshelf_api::transport::MockTransport;
+/// let transport = MockTransport::new()
+///     .respond_fixture(Method::GET, "/api/me", "tests/fixtures/me.json")
+///     .unwrap();
+/// let config = ClientConfig::new(Url::parse("http://localhost").unwrap())
+///     .unwrap()
+///     .with_transport(Arc::new(transport));
+/// let client = UserClient::from_token(config, "token".to_string());
+/// ```
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: Mutex<Vec<MockResponse>>,
+    requests: Mutex<Vec<RequestInfo>>,
+    bodies: Mutex<Vec<Option<String>>>,
+}
+
+#[derive(Debug, Clone)]
+struct MockResponse {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    body: String,
+    /// Removed once used, uncovering earlier responses for the same path
+    once: bool,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests to `path` with `status` and `body`. Later responses for the same
+    /// path take precedence.
+    pub fn respond(
+        mut self,
+        method: Method,
+        path: &str,
+        status: StatusCode,
+        body: impl Into<String>,
+    ) -> Self {
+        self.responses.get_mut().unwrap().push(MockResponse {
+            method,
+            path: path.to_string(),
+            status,
+            body: body.into(),
+            once: false,
+        });
+        self
+    }
+
+    /// Answer next request to `path` with `status` and `body`, and later ones with
+    /// responses given before.
+    pub fn respond_once(
+        mut self,
+        method: Method,
+        path: &str,
+        status: StatusCode,
+        body: impl Into<String>,
+    ) -> Self {
+        self.responses.get_mut().unwrap().push(MockResponse {
+            method,
+            path: path.to_string(),
+            status,
+            body: body.into(),
+            once: true,
+        });
+        self
+    }
+
+    /// Answer requests to `path` with successful JSON response.
+    pub fn respond_json(self, method: Method, path: &str, body: impl Into<String>) -> Self {
+        self.respond(method, path, StatusCode::OK, body)
+    }
+
+    /// Answer requests to `path` with successful response, read from `fixture` file.
+    pub fn respond_fixture(
+        self,
+        method: Method,
+        path: &str,
+        fixture: impl AsRef<Path>,
+    ) -> std::io::Result<Self> {
+        let body = std::fs::read_to_string(fixture)?;
+        Ok(self.respond_json(method, path, body))
+    }
+
+    /// Requests received so far, in order.
+    pub fn requests(&self) -> Vec<RequestInfo> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Bodies of requests received so far, in order. `None` for requests without body,
+    /// or with streamed one.
+    pub fn request_bodies(&self) -> Vec<Option<String>> {
+        self.bodies.lock().unwrap().clone()
+    }
+
+    fn find(&self, method: &Method, path: &str) -> Option<MockResponse> {
+        let mut responses = self.responses.lock().unwrap();
+        let index = responses
+            .iter()
+            .rposition(|response| response.method == *method && response.path == path)?;
+        if responses[index].once {
+            Some(responses.remove(index))
+        } else {
+            Some(responses[index].clone())
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(RequestInfo::of(&request));
+        self.bodies.lock().unwrap().push(
+            request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+        );
+        let (status, body) = match self.find(request.method(), request.url().path()) {
+            Some(response) => (response.status, response.body),
+            None => (
+                StatusCode::NOT_FOUND,
+                r#"{"error":"No mock response"}"#.to_string(),
+            ),
+        };
+        let range = request
+            .headers()
+            .get(reqwest::header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(byte_range);
+        let mut response =
+            http::Response::builder().header(reqwest::header::CONTENT_TYPE, "application/json");
+        let body = match range {
+            Some((start, end)) if status.is_success() => {
+                let end = end.map_or(body.len(), |end| (end + 1).min(body.len()));
+                match body.as_bytes().get(start..end) {
+                    Some(part) if start < end => {
+                        response = response.status(StatusCode::PARTIAL_CONTENT).header(
+                            reqwest::header::CONTENT_RANGE,
+                            format!("bytes {start}-{}/{}", end - 1, body.len()),
+                        );
+                        part.to_vec()
+                    }
+                    _ => {
+                        response = response.status(StatusCode::RANGE_NOT_SATISFIABLE);
+                        vec![]
+                    }
+                }
+            }
+            _ => {
+                response = response.status(status);
+                body.into_bytes()
+            }
+        };
+        let response = response.body(body).unwrap();
+        Box::pin(std::future::ready(Ok(reqwest::Response::from(response))))
+    }
+}
+
+/// Start and optional end of `bytes=<start>-<end>` range, both inclusive.
+fn byte_range(value: &str) -> Option<(usize, Option<usize>)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let end = match end {
+        "" => None,
+        end => Some(end.parse().ok()?),
+    };
+    Some((start.parse().ok()?, end))
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use audiobookshelf_api::credentials::{CredentialStore, EncryptedFileStore, StoredToken};
+use audiobookshelf_api::errors::{CredentialError, FusedError};
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Method, Url, UserClient};
+
+fn store_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("audiobookshelf_api_credentials_{name}"));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn server() -> Url {
+    Url::parse("http://localhost:13378/").unwrap()
+}
+
+fn token(token: &str) -> StoredToken {
+    StoredToken {
+        token: token.to_string(),
+        refresh_token: None,
+    }
+}
+
+#[test]
+fn encrypted_file_roundtrip() {
+    let path = store_path("roundtrip");
+    let store = EncryptedFileStore::open(&path, "secret").unwrap();
+    store.save(&server(), "root", &token("abc")).unwrap();
+    assert!(!std::fs::read_to_string(&path)
+        .unwrap_or_default()
+        .contains("abc"));
+
+    let reopened = EncryptedFileStore::open(&path, "secret").unwrap();
+    assert_eq!(
+        reopened.load(&server(), "root").unwrap(),
+        Some(token("abc"))
+    );
+    assert_eq!(reopened.load(&server(), "guest").unwrap(), None);
+    reopened.delete(&server(), "root").unwrap();
+    assert_eq!(reopened.load(&server(), "root").unwrap(), None);
+
+    let wrong = EncryptedFileStore::open(&path, "guess").unwrap();
+    assert!(matches!(
+        wrong.load(&server(), "root"),
+        Err(CredentialError::Decrypt)
+    ));
+}
+
+#[tokio::test]
+async fn saves_token_after_login() {
+    let me = std::fs::read_to_string("tests/fixtures/me.json").unwrap();
+    let login = format!(r#"{{"user":{me},"userDefaultLibraryId":"lib_1"}}"#);
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond_json(Method::POST, "/login", login)
+            .respond_json(Method::GET, "/api/me", me),
+    );
+    let config = ClientConfig::new(server())
+        .unwrap()
+        .with_transport(transport.clone());
+    let store: Arc<dyn CredentialStore> =
+        Arc::new(EncryptedFileStore::open(store_path("login"), "secret").unwrap());
+
+    let result =
+        UserClient::auth_with_store(config.clone(), "root".to_string(), None, store.clone()).await;
+    assert!(matches!(result, Err(FusedError::DomainError(_))));
+
+    let client = UserClient::auth_with_store(
+        config.clone(),
+        "root".to_string(),
+        Some("password".to_string()),
+        store.clone(),
+    )
+    .await
+    .unwrap();
+    let saved = store.load(&server(), "root").unwrap().unwrap();
+    assert_eq!(saved.token, client.token());
+
+    // Saved token is used without password
+    UserClient::auth_with_store(config, "root".to_string(), None, store)
+        .await
+        .unwrap();
+    let paths: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect();
+    assert_eq!(paths, ["/login", "/api/me"]);
+}
@@ -0,0 +1,205 @@
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use audiobookshelf_api::downloads::{ChunkedDownloader, DownloadManager};
+use audiobookshelf_api::errors::DownloadError;
+use audiobookshelf_api::rate_limit::Throttle;
+use audiobookshelf_api::schema::{AudioFile, Id, LibraryItem};
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Method, Url, UserClient};
+
+const CONTENT: &str = "0123456789abcdef";
+const PATH: &str = "/api/items/li_1/file/1234/download";
+
+fn downloader(content: &str) -> (ChunkedDownloader, Arc<MockTransport>) {
+    let transport = Arc::new(MockTransport::new().respond_json(Method::GET, PATH, content));
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(transport.clone());
+    let client = UserClient::from_token(config, "token".to_string());
+    let downloader = ChunkedDownloader::new(client).with_chunk_size(4);
+    (downloader, transport)
+}
+
+fn audio_file() -> AudioFile {
+    serde_json::from_value(serde_json::json!({
+        "index": 1,
+        "ino": "1234",
+        "metadata": {
+            "filename": "book.m4b",
+            "ext": ".m4b",
+            "path": "/audiobooks/book.m4b",
+            "relPath": "book.m4b",
+            "size": CONTENT.len(),
+            "mtimeMs": 1700000000000u64,
+            "ctimeMs": 1700000000000u64,
+            "birthtimeMs": 1700000000000u64,
+        },
+        "addedAt": 1700000000000u64,
+        "updatedAt": 1700000000000u64,
+        "manuallyVerified": false,
+        "exclude": false,
+        "format": "MP4A",
+        "duration": 1.0,
+        "bitRate": 64000,
+        "codec": "aac",
+        "timeBase": "1/44100",
+        "channels": 2,
+        "channelLayout": "stereo",
+        "chapters": [],
+        "mimeType": "audio/mp4",
+    }))
+    .unwrap()
+}
+
+fn target(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("audiobookshelf_api_downloads_{name}"));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory.join("book.m4b")
+}
+
+#[tokio::test]
+async fn downloads_file_in_parallel_chunks() {
+    let (downloader, transport) = downloader(CONTENT);
+    let target = target("parallel");
+    let id: Id<LibraryItem> = Id::new("li_1");
+
+    let size = downloader
+        .with_parallel_chunks(3)
+        .download(&id, &audio_file(), &target, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(size, 16);
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), CONTENT);
+    assert_eq!(transport.requests().len(), 4);
+    assert!(!target.with_extension("m4b.part.json").exists());
+}
+
+#[tokio::test]
+async fn resumes_interrupted_download() {
+    let target = target("resume");
+    let id: Id<LibraryItem> = Id::new("li_1");
+
+    // Third chunk comes out short, stopping download after two chunks
+    let (interrupted, _) = downloader(&CONTENT[..10]);
+    let error = interrupted
+        .with_parallel_chunks(1)
+        .download(&id, &audio_file(), &target, |_| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DownloadError::SizeMismatch { .. }));
+
+    let (downloader, transport) = downloader(CONTENT);
+    let resumed = std::sync::Mutex::new(vec![]);
+    downloader
+        .download(&id, &audio_file(), &target, |bytes| {
+            resumed.lock().unwrap().push(bytes)
+        })
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), CONTENT);
+    assert_eq!(transport.requests().len(), 2);
+    assert_eq!(resumed.into_inner().unwrap()[0], 8);
+}
+
+#[tokio::test]
+async fn redownloads_changed_file() {
+    let (downloader, transport) = downloader(CONTENT);
+    let target = target("changed");
+    let id: Id<LibraryItem> = Id::new("li_1");
+
+    // File of the same size, but not downloaded from this version
+    std::fs::write(&target, "fedcba9876543210").unwrap();
+    downloader
+        .download(&id, &audio_file(), &target, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), CONTENT);
+    assert_eq!(transport.requests().len(), 4);
+
+    downloader
+        .download(&id, &audio_file(), &target, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 4);
+
+    let mut changed = audio_file();
+    changed.metadata.mtime_ms += chrono::Duration::seconds(1);
+    downloader
+        .download(&id, &changed, &target, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 8);
+}
+
+#[test]
+fn throttle_delays_after_burst() {
+    let throttle = Throttle::new(NonZeroU64::new(10).unwrap());
+    assert_eq!(throttle.consume(10), Duration::ZERO);
+    let wait = throttle.consume(5);
+    assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+}
+
+/// Manager downloading item with audio files at given relative paths, each containing
+/// its own path.
+fn manager(name: &str, paths: &[&str]) -> DownloadManager {
+    let item = std::fs::read_to_string("tests/fixtures/server/2.17.2/library_item.json").unwrap();
+    let mut item: serde_json::Value = serde_json::from_str(&item).unwrap();
+    let template = item["media"]["audioFiles"][0].clone();
+    let mut transport = MockTransport::new();
+    let mut files = vec![];
+    for (index, path) in paths.iter().enumerate() {
+        let ino = format!("{index}");
+        let mut file = template.clone();
+        file["ino"] = ino.clone().into();
+        file["metadata"]["relPath"] = (*path).into();
+        file["metadata"]["size"] = path.len().into();
+        files.push(file);
+        transport = transport.respond_json(
+            Method::GET,
+            &format!("/api/items/li_8gch9ve09orgn4fdz8/file/{ino}/download"),
+            *path,
+        );
+    }
+    item["media"]["audioFiles"] = files.into();
+    let transport = transport.respond_json(
+        Method::GET,
+        "/api/items/li_8gch9ve09orgn4fdz8",
+        item.to_string(),
+    );
+
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(Arc::new(transport));
+    let client = UserClient::from_token(config, "token".to_string());
+    let root = std::env::temp_dir().join(format!("audiobookshelf_api_manager_{name}"));
+    let _ = std::fs::remove_dir_all(&root);
+    DownloadManager::new(client, root)
+}
+
+#[tokio::test]
+async fn keeps_files_of_different_discs_apart() {
+    let manager = manager("discs", &["CD1/01.mp3", "CD2/01.mp3"]);
+    let id = Id::new("li_8gch9ve09orgn4fdz8");
+
+    let item = manager.download(&id).await.unwrap();
+    for file in &item.files {
+        let content = std::fs::read_to_string(manager.root().join(&file.path)).unwrap();
+        assert!(file.path.ends_with(&content));
+    }
+    assert_eq!(item.files.len(), 2);
+    assert_ne!(item.files[0].path, item.files[1].path);
+}
+
+#[tokio::test]
+async fn rejects_paths_outside_of_item() {
+    let manager = manager("escape", &["../../escaped.mp3"]);
+    let id = Id::new("li_8gch9ve09orgn4fdz8");
+
+    let error = manager.download(&id).await.unwrap_err();
+    assert!(matches!(error, DownloadError::UnsafePath(_)));
+    assert!(!manager.root().join("escaped.mp3").exists());
+}
@@ -0,0 +1,85 @@
+use audiobookshelf_api::endpoints::Endpoints;
+use audiobookshelf_api::errors::UrlError;
+
+#[test]
+fn joins_root_without_subpath() {
+    for root in ["http://localhost:13378", "http://localhost:13378/"] {
+        let endpoints = Endpoints::parse(root).unwrap();
+        assert_eq!(
+            endpoints.api(["libraries", "lib_1", "items"]).as_str(),
+            "http://localhost:13378/api/libraries/lib_1/items"
+        );
+        assert_eq!(
+            endpoints.url(["login"]).as_str(),
+            "http://localhost:13378/login"
+        );
+    }
+}
+
+#[test]
+fn joins_root_with_subpath() {
+    for root in [
+        "https://host/audiobookshelf",
+        "https://host/audiobookshelf/",
+        "https://host/audiobookshelf/?query#fragment",
+    ] {
+        let endpoints = Endpoints::parse(root).unwrap();
+        assert_eq!(endpoints.root().as_str(), "https://host/audiobookshelf/");
+        assert_eq!(
+            endpoints.api(["me"]).as_str(),
+            "https://host/audiobookshelf/api/me"
+        );
+        assert_eq!(
+            endpoints.url(["socket.io", ""]).as_str(),
+            "https://host/audiobookshelf/socket.io/"
+        );
+    }
+}
+
+#[test]
+fn encodes_segments() {
+    let endpoints = Endpoints::parse("http://localhost/").unwrap();
+    assert_eq!(
+        endpoints.api(["items", "../me", "a/b?c#d"]).as_str(),
+        "http://localhost/api/items/..%2Fme/a%2Fb%3Fc%23d"
+    );
+}
+
+#[test]
+fn resolves_server_paths() {
+    let endpoints = Endpoints::parse("https://host/abs").unwrap();
+    for path in [
+        "/api/items/li_1/file/1",
+        "/abs/api/items/li_1/file/1",
+        "api/items/li_1/file/1",
+    ] {
+        assert_eq!(
+            endpoints.resolve(path).unwrap().as_str(),
+            "https://host/abs/api/items/li_1/file/1"
+        );
+    }
+    assert_eq!(
+        endpoints.resolve("https://cdn/file.mp3").unwrap().as_str(),
+        "https://cdn/file.mp3"
+    );
+    assert!(matches!(
+        endpoints.resolve("http://[invalid"),
+        Err(UrlError::Parse(_))
+    ));
+}
+
+#[test]
+fn rejects_unsupported_urls() {
+    assert!(matches!(
+        Endpoints::parse("mailto:root@localhost"),
+        Err(UrlError::UnsupportedScheme(_))
+    ));
+    assert!(matches!(
+        Endpoints::parse("localhost:13378"),
+        Err(UrlError::UnsupportedScheme(_))
+    ));
+    assert!(matches!(
+        Endpoints::parse("not a url"),
+        Err(UrlError::Parse(_))
+    ));
+}
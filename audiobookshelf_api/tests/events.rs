@@ -0,0 +1,171 @@
+#![cfg(feature = "events")]
+//! Payloads of server events, stored as `tests/fixtures/events/<name>.json`.
+use audiobookshelf_api::events::AbsEvent;
+use audiobookshelf_api::schema::Id;
+use serde_json::{json, Value};
+
+fn fixture(path: &str) -> Value {
+    let text = std::fs::read_to_string(format!("tests/fixtures/{path}")).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+fn event(name: &str, path: &str) -> AbsEvent {
+    AbsEvent::from_raw(name.to_string(), fixture(path))
+}
+
+#[test]
+fn parses_item_events() {
+    for name in ["item_added", "item_updated", "item_removed"] {
+        let item = match event(name, "server/2.17.2/library_item.json") {
+            AbsEvent::ItemAdded(item) if name == "item_added" => item,
+            AbsEvent::ItemUpdated(item) if name == "item_updated" => item,
+            AbsEvent::ItemRemoved(item) if name == "item_removed" => item,
+            other => panic!("Unexpected event for {name}: {other:?}"),
+        };
+        assert!(!item.id.as_str().is_empty());
+    }
+}
+
+#[test]
+fn parses_user_updated() {
+    let AbsEvent::UserUpdated(user) = event("user_updated", "me.json") else {
+        panic!("Expected user update");
+    };
+    assert_eq!(user.username, "root");
+}
+
+#[test]
+fn parses_progress_updated() {
+    let AbsEvent::MediaProgressUpdated(update) =
+        event("user_item_progress_updated", "events/progress_updated.json")
+    else {
+        panic!("Expected progress update");
+    };
+    assert_eq!(update.id, Id::new("li_8gch9ve09orgn4fdz8"));
+    assert_eq!(update.session_id, Some(Id::new("play_c786zm3qtjz6bd5q3n")));
+    assert_eq!(
+        update.device_description.as_deref(),
+        Some("Pixel 7 - Android 14")
+    );
+    assert_eq!(
+        update.data.library_item_id,
+        Id::new("li_8gch9ve09orgn4fdz8")
+    );
+    assert!(!update.data.is_finished);
+}
+
+#[test]
+fn parses_episode_download_events() {
+    let AbsEvent::EpisodeDownloadQueued(download) =
+        event("episode_download_queued", "events/episode_download.json")
+    else {
+        panic!("Expected queued download");
+    };
+    assert_eq!(download.id, "epdl_o8sn4xb2xo7ofvw6d3");
+    assert_eq!(download.library_item_id, Id::new("li_bufnnmp4y5o2gbbxfm"));
+    assert!(download.started_at.is_none());
+
+    let AbsEvent::EpisodeDownloadFinished(finished) =
+        event("episode_download_finished", "events/episode_download.json")
+    else {
+        panic!("Expected finished download");
+    };
+    assert_eq!(finished, download);
+}
+
+#[test]
+fn parses_backup_completed() {
+    let AbsEvent::BackupCompleted(backup) = event("backup_completed", "events/backup.json") else {
+        panic!("Expected backup");
+    };
+    assert_eq!(backup.id, "2022-11-14T0130");
+    assert_eq!(backup.file_size, Some(7776983));
+    assert!(backup.created_at.is_some());
+}
+
+#[test]
+fn parses_session_events() {
+    let AbsEvent::SessionUpdated(user) = event("user_stream_update", "events/stream_update.json")
+    else {
+        panic!("Expected session update");
+    };
+    assert_eq!(user.username, "root");
+    assert!(user.session.is_none());
+
+    let mut payload = fixture("events/stream_update.json");
+    let sessions = fixture("server/2.17.2/listening_sessions.json");
+    payload["session"] = sessions["sessions"][0].clone();
+    let AbsEvent::SessionUpdated(user) =
+        AbsEvent::from_raw("user_stream_update".to_string(), payload)
+    else {
+        panic!("Expected session update");
+    };
+    let session = user.session.expect("Expected session");
+    assert_eq!(
+        session.id.as_str(),
+        sessions["sessions"][0]["id"].as_str().unwrap()
+    );
+
+    let closed = AbsEvent::from_raw(
+        "user_session_closed".to_string(),
+        json!("play_c786zm3qtjz6bd5q3n"),
+    );
+    assert!(matches!(
+        closed,
+        AbsEvent::SessionClosed(id) if id == Id::new("play_c786zm3qtjz6bd5q3n")
+    ));
+}
+
+#[test]
+fn parses_scan_events() {
+    for name in ["scan_start", "scan_progress", "scan_complete"] {
+        let scan = match event(name, "events/scan.json") {
+            AbsEvent::ScanStarted(scan) if name == "scan_start" => scan,
+            AbsEvent::ScanProgress(scan) if name == "scan_progress" => scan,
+            AbsEvent::ScanCompleted(scan) if name == "scan_complete" => scan,
+            other => panic!("Unexpected event for {name}: {other:?}"),
+        };
+        assert_eq!(scan.name, "Audiobooks");
+        assert_eq!(scan.progress.unwrap().done, 42);
+        assert_eq!(scan.results.unwrap().updated, 5);
+    }
+}
+
+#[test]
+fn parses_quickmatch_completed() {
+    let AbsEvent::BatchQuickMatchCompleted(result) =
+        event("batch_quickmatch_complete", "events/quickmatch.json")
+    else {
+        panic!("Expected quickmatch result");
+    };
+    assert!(result.success);
+    assert_eq!(result.updates, 3);
+    assert_eq!(result.unmatched, 1);
+}
+
+#[test]
+fn keeps_unknown_events_raw() {
+    let payload = json!({"id": "li_8gch9ve09orgn4fdz8"});
+    let AbsEvent::Raw(name, data) = AbsEvent::from_raw("item_moved".to_string(), payload.clone())
+    else {
+        panic!("Expected raw event");
+    };
+    assert_eq!(name, "item_moved");
+    assert_eq!(data, payload);
+}
+
+#[test]
+fn keeps_mismatched_payloads_raw() {
+    // Known event, but payload of another one
+    let payload = fixture("events/quickmatch.json");
+    let AbsEvent::Raw(name, data) =
+        AbsEvent::from_raw("backup_completed".to_string(), payload.clone())
+    else {
+        panic!("Expected raw event");
+    };
+    assert_eq!(name, "backup_completed");
+    assert_eq!(data, payload);
+
+    let event = AbsEvent::from_raw("user_session_closed".to_string(), json!({"id": 1}));
+    assert!(matches!(event, AbsEvent::Raw(..)));
+}
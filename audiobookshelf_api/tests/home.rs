@@ -0,0 +1,70 @@
+use audiobookshelf_api::home::{HomeEntry, HomeScreen};
+use audiobookshelf_api::schema::{Id, MediaProgress, Shelf, UserData};
+
+fn progress(id: &str, finished: bool, progress: f64, last_update: i64) -> MediaProgress {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "libraryItemId": id,
+        "episodeId": null,
+        "duration": 1000.0,
+        "progress": progress,
+        "currentTime": progress * 1000.0,
+        "isFinished": finished,
+        "hideFromContinueListening": false,
+        "lastUpdate": last_update,
+        "startedAt": 1668120083771i64,
+        "finishedAt": null,
+    }))
+    .unwrap()
+}
+
+fn home_screen(media_progress: Vec<MediaProgress>) -> HomeScreen {
+    let shelves = std::fs::read_to_string("tests/fixtures/personalized.json").unwrap();
+    let shelves: Vec<Shelf> = serde_json::from_str(&shelves).unwrap();
+    let user = std::fs::read_to_string("tests/fixtures/me.json").unwrap();
+    let mut user: UserData = serde_json::from_str(&user).unwrap();
+    user.media_progress = media_progress;
+    HomeScreen::new(shelves, &user)
+}
+
+#[test]
+fn merges_progress_into_shelves() {
+    let home = home_screen(vec![
+        progress("li_first", true, 1.0, 1668120000000),
+        progress("li_second", false, 0.25, 1668120246620),
+    ]);
+    assert_eq!(home.shelves.len(), 4);
+
+    let HomeEntry::Item(entry) = &home.shelves[0].entries[0] else {
+        panic!("Expected item entry");
+    };
+    assert_eq!(entry.item.id, Id::new("li_second"));
+    assert_eq!(entry.fraction(), 0.25);
+
+    let HomeEntry::Series(series) = &home.shelves[1].entries[0] else {
+        panic!("Expected series entry");
+    };
+    assert_eq!(series.finished, 1);
+    assert_eq!(series.next_book().unwrap().id, Id::new("li_second"));
+
+    assert!(matches!(home.shelves[2].entries[0], HomeEntry::Author(_)));
+    assert!(home.shelves[3].entries.is_empty());
+}
+
+#[test]
+fn up_next_follows_finished_books() {
+    let home = home_screen(vec![
+        progress("li_first", true, 1.0, 1668120000000),
+        progress("li_second", true, 1.0, 1668120246620),
+    ]);
+    assert_eq!(home.up_next.len(), 1);
+    assert_eq!(home.up_next[0].item.id, Id::new("li_third"));
+    assert_eq!(home.up_next[0].series_name, "Sword of Truth");
+    assert!(home.up_next[0].progress.is_none());
+}
+
+#[test]
+fn no_up_next_without_finished_books() {
+    let home = home_screen(vec![progress("li_second", false, 0.5, 1668120246620)]);
+    assert!(home.up_next.is_empty());
+}
@@ -0,0 +1,543 @@
+use std::sync::Arc;
+
+use audiobookshelf_api::errors::{APIError, FusedError, RequestError};
+use audiobookshelf_api::params::{
+    CreateShareParams, LibraryItemParams, LocalSessionParams, MetadataProvider, UploadFile,
+    UploadSource,
+};
+use audiobookshelf_api::progress_sync::{LocalProgress, ProgressSync};
+use audiobookshelf_api::retry::RetryPolicy;
+use audiobookshelf_api::schema::{
+    Id, LibraryItem, LogLevel, MediaType, Permission, ScanResult, Seconds, UserPermissions,
+};
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Credentials, Method, StatusCode, Url, UserClient};
+use bytes::Bytes;
+use futures_util::StreamExt;
+
+fn client(transport: MockTransport) -> (UserClient, Arc<MockTransport>) {
+    let transport = Arc::new(transport);
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(transport.clone());
+    (
+        UserClient::from_token(config, "token".to_string()),
+        transport,
+    )
+}
+
+#[tokio::test]
+async fn parses_fixtures() {
+    let transport = MockTransport::new()
+        .respond_fixture(Method::GET, "/api/me", "tests/fixtures/me.json")
+        .unwrap()
+        .respond_fixture(
+            Method::GET,
+            "/api/libraries",
+            "tests/fixtures/libraries.json",
+        )
+        .unwrap();
+    let (client, transport) = client(transport);
+
+    let user = client.me().await.unwrap();
+    assert_eq!(user.username, "root");
+    assert_eq!(user.media_progress.len(), 1);
+
+    let libraries = client.libraries().await.unwrap();
+    assert_eq!(libraries[0].media_type, MediaType::Book);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].url.path(), "/api/me");
+}
+
+#[tokio::test]
+async fn classifies_errors() {
+    let transport = MockTransport::new().respond(
+        Method::GET,
+        "/api/me",
+        StatusCode::FORBIDDEN,
+        r#"{"error":"Forbidden","message":"Not allowed"}"#,
+    );
+    let (client, _) = client(transport);
+
+    match client.me().await {
+        Err(FusedError::DomainError(RequestError::Forbidden(error))) => {
+            assert_eq!(error.error.message.as_deref(), Some("Not allowed"));
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+    assert!(matches!(
+        client.libraries().await,
+        Err(FusedError::DomainError(RequestError::NotFound(_)))
+    ));
+}
+
+#[tokio::test]
+async fn denies_write_without_permission() {
+    let (client, transport) = client(MockTransport::new());
+    let permissions = UserPermissions {
+        download: true,
+        update: false,
+        delete: false,
+        upload: false,
+        access_all_libraries: true,
+        access_all_tags: true,
+        access_explicit_content: true,
+        create_ereader: false,
+        selected_tags_not_accessible: false,
+    };
+    let client = client.with_permissions(permissions);
+
+    let result = client
+        .batch_delete_library_items(&[Id::new("li_1")], false)
+        .await;
+    match result {
+        Err(FusedError::DomainError(
+            error @ RequestError::PermissionDenied(Permission::Delete),
+        )) => {
+            assert_eq!(error.status(), StatusCode::FORBIDDEN);
+            assert!(error.response().is_none());
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+    assert!(transport.requests().is_empty());
+}
+
+/// Login response for user from `me.json`, optionally with refresh token.
+fn login_response(refresh_token: Option<&str>) -> String {
+    let me = std::fs::read_to_string("tests/fixtures/me.json").unwrap();
+    let mut user: serde_json::Value = serde_json::from_str(&me).unwrap();
+    if let Some(refresh_token) = refresh_token {
+        user["refreshToken"] = refresh_token.into();
+    }
+    serde_json::json!({"user": user, "userDefaultLibraryId": "lib_1"}).to_string()
+}
+
+fn paths(transport: &MockTransport) -> Vec<String> {
+    transport
+        .requests()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn reauthenticates_rejected_request() {
+    let transport = MockTransport::new()
+        .respond_fixture(Method::GET, "/api/me", "tests/fixtures/me.json")
+        .unwrap()
+        .respond_once(Method::GET, "/api/me", StatusCode::UNAUTHORIZED, "")
+        .respond_json(Method::POST, "/login", login_response(None));
+    let (client, transport) = client(transport);
+    let client = client.with_credentials(Credentials::Password {
+        username: "root".to_string(),
+        password: "password".to_string(),
+    });
+
+    assert_eq!(client.me().await.unwrap().username, "root");
+    assert_eq!(client.token(), "exJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9");
+    assert_eq!(paths(&transport), ["/api/me", "/login", "/api/me"]);
+}
+
+#[tokio::test]
+async fn falls_back_to_password_then_refresh_token_is_rejected() {
+    let transport = MockTransport::new()
+        .respond_fixture(Method::GET, "/api/me", "tests/fixtures/me.json")
+        .unwrap()
+        .respond_once(Method::GET, "/api/me", StatusCode::UNAUTHORIZED, "")
+        .respond_json(Method::POST, "/login", login_response(Some("refresh_1")))
+        .respond(Method::POST, "/auth/refresh", StatusCode::UNAUTHORIZED, "");
+    let (client, transport) = client(transport);
+    let client = client.with_credentials(Credentials::Password {
+        username: "root".to_string(),
+        password: "password".to_string(),
+    });
+
+    // Password login issues refresh token, which is tried first next time
+    client.me().await.unwrap();
+    client.reauthenticate().await.unwrap();
+    assert_eq!(
+        paths(&transport),
+        ["/api/me", "/login", "/api/me", "/auth/refresh", "/login"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_only_idempotent_requests() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond(Method::GET, "/api/me", StatusCode::SERVICE_UNAVAILABLE, "")
+            .respond(
+                Method::POST,
+                "/api/items/li_1/play",
+                StatusCode::SERVICE_UNAVAILABLE,
+                "",
+            ),
+    );
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(transport.clone())
+        .with_retry(RetryPolicy::default());
+    let client = UserClient::from_token(config, "token".to_string());
+
+    assert!(client.me().await.is_err());
+    assert_eq!(transport.requests().len(), 3);
+
+    // Session may have been opened even though response was lost
+    let result = client
+        .library_item_play(&Id::new("li_1"), &Default::default())
+        .await;
+    assert!(result.is_err());
+    assert_eq!(transport.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn streams_library_items() {
+    let transport = MockTransport::new()
+        .respond_fixture(
+            Method::GET,
+            "/api/libraries/lib_1/items",
+            "tests/fixtures/library_items.json",
+        )
+        .unwrap();
+    let (client, transport) = client(transport);
+
+    let params = LibraryItemParams {
+        limit: 1,
+        ..Default::default()
+    };
+    let items: Vec<_> = client
+        .library_items_stream(&Id::new("lib_1"), params)
+        .collect()
+        .await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        items[0].as_ref().unwrap().id.as_str(),
+        "li_8gch9ve09orgn4fdz8"
+    );
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn streaming_reports_invalid_items() {
+    let transport = MockTransport::new().respond_json(
+        Method::GET,
+        "/api/libraries/lib_1/items",
+        r#"{"results":[{"id":1}],"total":1,"limit":0,"page":0}"#,
+    );
+    let (client, _) = client(transport);
+
+    let items: Vec<_> = client
+        .library_items_stream(&Id::new("lib_1"), LibraryItemParams::default())
+        .collect()
+        .await;
+    assert_eq!(items.len(), 1);
+    assert!(matches!(
+        items[0],
+        Err(FusedError::APIError(APIError::InvalidResponseSchema { .. }))
+    ));
+}
+
+#[tokio::test]
+async fn browses_filesystem() {
+    let transport = MockTransport::new().respond(
+        Method::GET,
+        "/api/filesystem",
+        StatusCode::OK,
+        r#"{"posixSeparator":true,"directories":[{"path":"/audiobooks","dirname":"audiobooks","level":0}]}"#,
+    );
+    let (client, transport) = client(transport);
+
+    let top = client.admin().filesystem(None).await.unwrap();
+    assert!(top.posix_separator);
+    assert_eq!(top.directories[0].dirname, "audiobooks");
+    client
+        .admin()
+        .filesystem(Some(&top.directories[0]))
+        .await
+        .unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].url.query(), None);
+    assert_eq!(requests[1].url.query(), Some("path=%2Faudiobooks&level=1"));
+}
+
+#[tokio::test]
+async fn scans_library_item() {
+    let transport = MockTransport::new().respond(
+        Method::POST,
+        "/api/items/li_1/scan",
+        StatusCode::OK,
+        r#"{"result":"UPTODATE"}"#,
+    );
+    let (client, _) = client(transport);
+
+    let result = client
+        .admin()
+        .scan_library_item(&Id::new("li_1"))
+        .await
+        .unwrap();
+    assert_eq!(result, ScanResult::UpToDate);
+}
+
+#[tokio::test]
+async fn filters_server_logs() {
+    let transport = MockTransport::new().respond(
+        Method::GET,
+        "/api/logger-data",
+        StatusCode::OK,
+        r#"{"currentDailyLogs":[
+            {"timestamp":"2024-05-01 10:00:00.000","source":"Server.js:120","message":"Starting","levelName":"INFO","level":2},
+            {"timestamp":"2024-05-01 10:00:01.000","source":"Watcher.js:40","message":"Failed","levelName":"ERROR","level":4}
+        ]}"#,
+    );
+    let (client, _) = client(transport);
+
+    let logs = client.admin().server_logs(LogLevel::WARN).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].message, "Failed");
+}
+
+#[tokio::test]
+async fn finds_next_in_series() {
+    let library = "/api/libraries/lib_c1u6t4p45c35rf0nzd";
+    let transport = MockTransport::new()
+        .respond_fixture(
+            Method::GET,
+            "/api/items/li_8gch9ve09orgn4fdz8",
+            "tests/fixtures/server/2.17.2/library_item.json",
+        )
+        .unwrap()
+        .respond_fixture(
+            Method::GET,
+            &format!("{library}/items"),
+            "tests/fixtures/server/2.17.2/library_items.json",
+        )
+        .unwrap()
+        .respond_json(
+            Method::GET,
+            &format!("{library}/series/ser_cabkj4jeu8be3rap4g"),
+            r#"{"id":"ser_cabkj4jeu8be3rap4g","name":"Sword of Truth","description":null,
+                "addedAt":1650621073750,"updatedAt":1650621073750,
+                "progress":{"libraryItemIds":["li_8gch9ve09orgn4fdz8"],
+                "libraryItemIdsFinished":["li_8gch9ve09orgn4fdz8"],"isFinished":false}}"#,
+        );
+    let (client, transport) = client(transport);
+
+    let next = client
+        .next_in_series(&Id::new("li_8gch9ve09orgn4fdz8"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(next.id, Id::new("li_ci0dqcsh3gx8ln3pbe"));
+
+    let requests = transport.requests();
+    let items = requests
+        .iter()
+        .find(|request| request.url.path().ends_with("/items"))
+        .unwrap();
+    assert!(items.url.query().unwrap().contains("sort=sequence"));
+}
+
+#[tokio::test]
+async fn uploads_item_cover() {
+    let transport = MockTransport::new().respond_json(
+        Method::POST,
+        "/api/items/li_1/cover",
+        r#"{"success":true,"cover":"/metadata/items/li_1/cover.jpg"}"#,
+    );
+    let (client, _) = client(transport);
+
+    let file = UploadFile {
+        name: "cover.jpg".to_string(),
+        source: UploadSource::Bytes(Bytes::from_static(b"\xff\xd8\xff")),
+    };
+    let cover = client
+        .upload_item_cover(&Id::new("li_1"), file)
+        .await
+        .unwrap();
+    assert_eq!(cover, "/metadata/items/li_1/cover.jpg");
+}
+
+#[tokio::test]
+async fn fetches_author_image() {
+    let transport =
+        MockTransport::new().respond_json(Method::GET, "/api/authors/aut_1/image", "jpeg");
+    let (client, transport) = client(transport);
+
+    let image = client
+        .author_image(&Id::new("aut_1"), Some(200))
+        .await
+        .unwrap();
+    assert_eq!(image.data, b"jpeg");
+    assert_eq!(transport.requests()[0].url.query(), Some("width=200"));
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn caches_author_image() {
+    use audiobookshelf_api::cache::MetadataCache;
+
+    let transport =
+        MockTransport::new().respond_json(Method::GET, "/api/authors/aut_1/image", "jpeg");
+    let (client, transport) = client(transport);
+    let root = std::env::temp_dir().join("audiobookshelf_api_author_image_cache");
+    let _ = std::fs::remove_dir_all(&root);
+    let cache = MetadataCache::new(root);
+
+    let id = Id::new("aut_1");
+    let fetched = cache
+        .fetch_author_image(&client, &id, Some(200))
+        .await
+        .unwrap();
+    let cached = cache
+        .fetch_author_image(&client, &id, Some(200))
+        .await
+        .unwrap();
+    assert_eq!(fetched.value, cached.value);
+    assert_eq!(transport.requests().len(), 1);
+    assert!(cache.author_image(&id, None).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn parses_opml() {
+    let transport = MockTransport::new().respond_json(
+        Method::POST,
+        "/api/podcasts/opml/parse",
+        r#"{"feeds":[{"title":"Example","feedUrl":"https://example.com/feed.xml"}]}"#,
+    );
+    let (client, _) = client(transport);
+
+    let feeds = client.parse_opml("<opml/>").await.unwrap();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].feed_url, "https://example.com/feed.xml");
+}
+
+#[tokio::test]
+async fn matches_podcast_episodes() {
+    let transport = MockTransport::new().respond_json(
+        Method::POST,
+        "/api/podcasts/li_1/match-episodes",
+        r#"{"numEpisodesUpdated":3}"#,
+    );
+    let (client, transport) = client(transport);
+
+    let updated = client
+        .match_podcast_episodes(&Id::new("li_1"), true)
+        .await
+        .unwrap();
+    assert_eq!(updated, 3);
+    assert_eq!(transport.requests()[0].url.query(), Some("override=1"));
+}
+
+#[tokio::test]
+async fn creates_share() {
+    let transport = MockTransport::new().respond_json(
+        Method::POST,
+        "/api/share/mediaitem",
+        r#"{"id":"3f9a","mediaItemType":"book","mediaItemId":"book_8gch9ve09orgn4fdz8",
+            "slug":"wizards","expiresAt":null,"isDownloadable":false,
+            "createdAt":"2024-06-01T10:00:00.000Z","updatedAt":"2024-06-01T10:00:00.000Z"}"#,
+    );
+    let (client, _) = client(transport);
+
+    let item: LibraryItem = serde_json::from_str(
+        &std::fs::read_to_string("tests/fixtures/server/2.17.2/library_item.json").unwrap(),
+    )
+    .unwrap();
+    let params = CreateShareParams::for_item(&item, "wizards").unwrap();
+    assert_eq!(params.media_item_id, "book_8gch9ve09orgn4fdz8");
+
+    let admin = client.admin();
+    let share = admin.create_share(&params).await.unwrap();
+    assert_eq!(
+        admin.share_link(&share).as_str(),
+        "http://localhost:13378/share/wizards"
+    );
+}
+
+#[tokio::test]
+async fn fetches_year_stats() {
+    let transport = MockTransport::new().respond_json(
+        Method::GET,
+        "/api/me/stats/year/2024",
+        r#"{"totalListeningSessions":12,"totalListeningTime":36000.5,
+            "totalBookListeningTime":30000,"totalPodcastListeningTime":6000.5,
+            "topAuthors":[{"name":"Terry Goodkind","time":30000}],
+            "topGenres":[{"genre":"Fantasy","time":30000}],
+            "topNarrators":[{"name":"Sam Tsoutsouvas","time":30000}],
+            "mostListenedNarrator":{"name":"Sam Tsoutsouvas","time":30000},
+            "mostListenedMonth":{"month":2,"time":20000},
+            "numBooksFinished":1,"numBooksListened":2,
+            "longestAudiobookFinished":{"id":"book_1","title":"Wizards First Rule",
+                "duration":12000.9,"finishedAt":"2024-03-10T20:00:00.000Z"},
+            "booksWithCovers":["li_1"],"finishedBooksWithCovers":["li_1"]}"#,
+    );
+    let (client, _) = client(transport);
+
+    let stats = client.year_stats(2024).await.unwrap();
+    assert_eq!(stats.top_genres[0].genre, "Fantasy");
+    assert_eq!(stats.most_listened_month.unwrap().month, 2);
+    assert_eq!(stats.books_with_covers, [Id::new("li_1")]);
+}
+
+#[tokio::test]
+async fn lists_custom_metadata_providers() {
+    let transport = MockTransport::new().respond_json(
+        Method::GET,
+        "/api/custom-metadata-providers",
+        r#"{"providers":[{"id":"4a1b","name":"Local","mediaType":"book",
+            "url":"http://metadata.lan","authHeaderValue":null}]}"#,
+    );
+    let (client, _) = client(transport);
+
+    let providers = client.admin().custom_metadata_providers().await.unwrap();
+    assert_eq!(providers[0].media_type, MediaType::Book);
+    let provider = MetadataProvider::Custom(providers[0].id.clone());
+    assert_eq!(provider.as_str(), "custom-4a1b");
+}
+
+#[tokio::test]
+async fn flushes_newer_local_progress() {
+    let transport = MockTransport::new()
+        .respond_fixture(Method::GET, "/api/me", "tests/fixtures/me.json")
+        .unwrap()
+        .respond_json(Method::PATCH, "/api/me/progress/batch/update", "OK");
+    let (client, transport) = client(transport);
+
+    let server = client.me().await.unwrap().media_progress.remove(0);
+    let mut stale = LocalProgress::from(&server);
+    stale.updated_at = server.last_update - chrono::TimeDelta::hours(1);
+    let fresh = LocalProgress::new(Id::new("li_offline"), 120.0, 3600.0);
+    let sync = ProgressSync::new(client).with_pending([stale, fresh.clone()]);
+
+    let report = sync.flush().await.unwrap();
+    assert_eq!(report.uploaded, [fresh]);
+    assert_eq!(report.superseded, [server]);
+    assert!(sync.pending().is_empty());
+    assert_eq!(transport.requests().last().unwrap().method, Method::PATCH);
+}
+
+#[tokio::test]
+async fn syncs_local_sessions() {
+    let transport = MockTransport::new().respond_json(
+        Method::POST,
+        "/api/session/local-all",
+        r#"{"results":[{"id":"play_local_1","success":true,"progressSynced":true}]}"#,
+    );
+    let (client, transport) = client(transport);
+    let item: LibraryItem = serde_json::from_str(
+        &std::fs::read_to_string("tests/fixtures/server/2.17.2/library_item.json").unwrap(),
+    )
+    .unwrap();
+
+    let mut session = LocalSessionParams::new(&item, None, Seconds(60.0));
+    session.listened(Seconds(90.0), Seconds(30.0));
+    assert_eq!(session.time_listening, Seconds(30.0));
+    assert_eq!(session.library_item_id, item.id);
+
+    let results = client.sync_local_sessions(&[session]).await.unwrap();
+    assert!(results[0].success && results[0].progress_synced);
+    assert_eq!(transport.requests()[0].method, Method::POST);
+}
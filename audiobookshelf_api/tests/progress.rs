@@ -0,0 +1,80 @@
+use audiobookshelf_api::progress_sync::{reconcile, LocalProgress, Resolution};
+use audiobookshelf_api::schema::{Id, MediaProgress, Seconds, UserData};
+use chrono::TimeDelta;
+
+fn user() -> UserData {
+    let user = std::fs::read_to_string("tests/fixtures/me.json").unwrap();
+    let progress = std::fs::read_to_string("tests/fixtures/media_progress.json").unwrap();
+    let mut user: UserData = serde_json::from_str(&user).unwrap();
+    user.media_progress = serde_json::from_str::<Vec<MediaProgress>>(&progress).unwrap();
+    user
+}
+
+#[test]
+fn currently_listening_skips_finished_and_hidden() {
+    assert_eq!(user().currently_listening(), Some(Id::new("li_newer")));
+}
+
+#[test]
+fn currently_listening_without_progress() {
+    let mut user = user();
+    user.media_progress.clear();
+    assert_eq!(user.currently_listening(), None);
+}
+
+#[test]
+fn most_recent_progress_includes_finished() {
+    let user = user();
+    let progress = user.most_recent_progress().unwrap();
+    assert_eq!(progress.library_item_id, Id::new("li_finished"));
+}
+
+#[test]
+fn filters_finished_and_in_progress() {
+    let user = user();
+    let finished: Vec<_> = user.finished().map(|p| p.id.as_str()).collect();
+    let in_progress: Vec<_> = user.in_progress().map(|p| p.id.as_str()).collect();
+    assert_eq!(finished, ["li_finished"]);
+    assert_eq!(in_progress, ["li_older", "li_newer", "li_hidden"]);
+}
+
+#[test]
+fn percent_and_remaining() {
+    let user = user();
+    let progress = user.progress_for(&Id::new("li_older"), None).unwrap();
+    assert_eq!(progress.percent_complete(), 25.0);
+    assert_eq!(progress.remaining(), Seconds(1500.0));
+
+    let finished = user.progress_for(&Id::new("li_finished"), None).unwrap();
+    assert_eq!(finished.remaining(), Seconds::ZERO);
+}
+
+#[test]
+fn reconcile_prefers_later_update_beyond_skew() {
+    let user = user();
+    let server = user.progress_for(&Id::new("li_older"), None).unwrap();
+    let skew = TimeDelta::seconds(30);
+
+    let mut local = LocalProgress::from(server);
+    local.current_time = Seconds(10.0);
+    local.updated_at = server.last_update + TimeDelta::minutes(5);
+    assert_eq!(reconcile(&local, server, skew), Resolution::Local);
+
+    local.updated_at = server.last_update - TimeDelta::minutes(5);
+    assert_eq!(reconcile(&local, server, skew), Resolution::Server);
+}
+
+#[test]
+fn reconcile_prefers_further_position_within_skew() {
+    let user = user();
+    let server = user.progress_for(&Id::new("li_older"), None).unwrap();
+    let skew = TimeDelta::seconds(30);
+
+    let mut local = LocalProgress::from(server);
+    local.updated_at = server.last_update - TimeDelta::seconds(10);
+    local.current_time = server.current_time + Seconds(60.0);
+    assert_eq!(reconcile(&local, server, skew), Resolution::Local);
+
+    local.current_time = server.current_time - Seconds(60.0);
+    assert_eq!(reconcile(&local, server, skew), Resolution::Server);
+}
@@ -0,0 +1,53 @@
+use audiobookshelf_api::errors::InvalidQuery;
+use audiobookshelf_api::params::{Filter, LibraryItemParams, Sort};
+use audiobookshelf_api::query::Query;
+use audiobookshelf_api::schema::Progress;
+
+#[test]
+fn round_trips_through_string() {
+    let query = Query::new()
+        .sort(Sort::Duration)
+        .desc()
+        .filter(Filter::Progress(Progress::NotFinished));
+    let text = query.to_string();
+    assert_eq!(
+        text,
+        "sort=media.duration&desc=1&filter=progress.bm90LWZpbmlzaGVk"
+    );
+    assert_eq!(text.parse::<Query>().unwrap(), query);
+
+    let json = serde_json::to_string(&query).unwrap();
+    assert_eq!(serde_json::from_str::<Query>(&json).unwrap(), query);
+}
+
+#[test]
+fn decodes_every_filter_group() {
+    let filters = [
+        Filter::NoSeries,
+        Filter::Genres("Sci-Fi & Fantasy".into()),
+        Filter::Issues,
+        Filter::FeedOpen,
+        Filter::Abridged,
+    ];
+    for filter in filters {
+        assert_eq!(Filter::decode(&filter.encode()), Some(filter));
+    }
+}
+
+#[test]
+fn converts_into_params() {
+    let query: Query = "sort=custom&collapseseries=1".parse().unwrap();
+    let params = query.params(20, 2);
+    assert_eq!(params.sort, Some(Sort::Other("custom".into())));
+    assert!(params.collapse_series);
+    assert_eq!((params.limit, params.page), (20, 2));
+    assert_eq!(LibraryItemParams::from(query).limit, 0);
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    assert_eq!(
+        "limit=10".parse::<Query>(),
+        Err(InvalidQuery("limit=10".into()))
+    );
+}
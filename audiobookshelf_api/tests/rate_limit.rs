@@ -0,0 +1,93 @@
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use audiobookshelf_api::rate_limit::RateLimiter;
+use audiobookshelf_api::schema::Id;
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Url, UserClient};
+use futures_util::future::join_all;
+use tokio::time::Instant;
+
+/// Client allowing bursts of 3 requests, refilled at 2 requests per second
+fn client() -> (UserClient, Arc<MockTransport>) {
+    let transport = Arc::new(MockTransport::new());
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(transport.clone())
+        .with_rate_limit(RateLimiter::new(2.0, NonZeroU32::new(3).unwrap()));
+    (
+        UserClient::from_token(config, "token".to_string()),
+        transport,
+    )
+}
+
+/// Request items `ids` at once, alternating between `clients`, and return time each
+/// request took.
+async fn request_items(clients: &[UserClient], ids: &[&str]) -> Vec<Duration> {
+    let start = Instant::now();
+    let requests = ids.iter().enumerate().map(|(i, id)| async move {
+        // Items are not mocked, only the time request was let through matters
+        let _ = clients[i % clients.len()].library_item(&Id::new(*id)).await;
+        start.elapsed()
+    });
+    join_all(requests).await
+}
+
+fn paths(transport: &MockTransport) -> Vec<String> {
+    transport
+        .requests()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect()
+}
+
+fn secs(secs: &[f64]) -> Vec<Duration> {
+    secs.iter().copied().map(Duration::from_secs_f64).collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn limits_burst_then_refills() {
+    let (client, transport) = client();
+
+    let elapsed = request_items(
+        std::slice::from_ref(&client),
+        &["li_1", "li_2", "li_3", "li_4", "li_5"],
+    )
+    .await;
+    assert_eq!(elapsed, secs(&[0.0, 0.0, 0.0, 0.5, 1.0]));
+    assert_eq!(
+        paths(&transport),
+        [
+            "/api/items/li_1",
+            "/api/items/li_2",
+            "/api/items/li_3",
+            "/api/items/li_4",
+            "/api/items/li_5",
+        ]
+    );
+
+    // Refilled bucket holds no more than burst
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    let elapsed = request_items(
+        std::slice::from_ref(&client),
+        &["li_6", "li_7", "li_8", "li_9"],
+    )
+    .await;
+    assert_eq!(elapsed, secs(&[0.0, 0.0, 0.0, 0.5]));
+
+    // Waiting request took a token in advance, so a second later only one is left
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let elapsed = request_items(std::slice::from_ref(&client), &["li_10", "li_11"]).await;
+    assert_eq!(elapsed, secs(&[0.0, 0.5]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn clones_share_limiter() {
+    let (client, transport) = client();
+    let clients = [client.clone(), client.clone(), client];
+
+    let elapsed = request_items(&clients, &["li_1", "li_2", "li_3", "li_4", "li_5", "li_6"]).await;
+    assert_eq!(elapsed, secs(&[0.0, 0.0, 0.0, 0.5, 1.0, 1.5]));
+    assert_eq!(transport.requests().len(), 6);
+}
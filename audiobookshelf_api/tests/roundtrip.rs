@@ -0,0 +1,75 @@
+use std::fmt::Debug;
+
+use audiobookshelf_api::params::{DeviceInfoParams, PlayLibraryItemParams};
+use audiobookshelf_api::schema::{
+    Codec, Libraries, LibraryItemMinified, MimeType, PaginatedResponse, Shelf, UserData,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Parse fixture, serialize it back and check that nothing was lost.
+fn roundtrip<T>(fixture: &str)
+where
+    T: DeserializeOwned + Serialize + PartialEq + Debug,
+{
+    let path = format!("tests/fixtures/{fixture}");
+    let json = std::fs::read_to_string(&path).unwrap();
+    let value: T = serde_json::from_str(&json).unwrap();
+    let serialized = serde_json::to_string(&value).unwrap();
+    let parsed: T = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(value, parsed, "{path} changed after round trip");
+}
+
+#[test]
+fn user() {
+    roundtrip::<UserData>("me.json");
+}
+
+#[test]
+fn libraries() {
+    roundtrip::<Libraries>("libraries.json");
+}
+
+#[test]
+fn library_items() {
+    roundtrip::<PaginatedResponse<LibraryItemMinified>>("library_items.json");
+}
+
+#[test]
+fn personalized() {
+    roundtrip::<Vec<Shelf>>("personalized.json");
+}
+
+#[test]
+fn mime_types() {
+    let json = r#"["audio/mp4","audio/x-caf"]"#;
+    let mime_types: Vec<MimeType> = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        mime_types,
+        [MimeType::Mp4, MimeType::Other("audio/x-caf".into())]
+    );
+    assert_eq!(serde_json::to_string(&mime_types).unwrap(), json);
+    assert_eq!(
+        MimeType::supported(&[Codec::Opus, Codec::Mp3, Codec::Vorbis]),
+        [MimeType::Ogg, MimeType::Webm, MimeType::Mpeg]
+    );
+}
+
+#[test]
+fn play_params() {
+    let params = PlayLibraryItemParams::builder()
+        .device(DeviceInfoParams::default())
+        .direct_play()
+        .build();
+    let json = serde_json::to_value(&params).unwrap();
+    assert_eq!(json["forceDirectPlay"], true);
+    assert_eq!(json["supportedMimeTypes"][0], "audio/mpeg");
+
+    let params = PlayLibraryItemParams::builder()
+        .direct_play()
+        .transcode()
+        .mime_types([MimeType::Flac])
+        .build();
+    assert!(params.force_transcode && !params.force_direct_play);
+    assert_eq!(params.supported_mime_types, [MimeType::Flac]);
+}
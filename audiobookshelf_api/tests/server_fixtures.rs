@@ -0,0 +1,110 @@
+//! Responses of different server versions, stored as `tests/fixtures/server/<version>/<name>.json`.
+//!
+//! New versions are recorded from live server with `cargo run --example record_fixtures`.
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+use audiobookshelf_api::schema::{
+    AuthorExpanded, CollectionExpanded, Libraries, LibraryItem, LibraryItemMinified,
+    LibrarySearchResult, LibrarySeries, LibraryStats, LibraryWithFilters, ListeningSessions,
+    Narrators, PaginatedResponse, PlaylistExpanded, ServerStatus, UserData,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const FIXTURES: &str = "tests/fixtures/server";
+
+/// Fixture names, each expected to be present for every server version.
+const NAMES: &[&str] = &[
+    "status",
+    "me",
+    "libraries",
+    "library",
+    "library_items",
+    "library_item",
+    "library_series",
+    "library_stats",
+    "library_narrators",
+    "library_collections",
+    "library_playlists",
+    "library_search",
+    "author",
+    "listening_sessions",
+];
+
+/// Parse fixture, reporting path to invalid field, then check that round trip keeps it intact.
+fn check<T>(path: &Path) -> Result<(), String>
+where
+    T: DeserializeOwned + Serialize + PartialEq + Debug,
+{
+    let json = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let deserializer = &mut serde_json::Deserializer::from_str(&json);
+    let value: T = serde_path_to_error::deserialize(deserializer)
+        .map_err(|error| format!("at {}: {}", error.path(), error.inner()))?;
+    let serialized = serde_json::to_string(&value).map_err(|error| error.to_string())?;
+    let parsed: T = serde_json::from_str(&serialized).map_err(|error| error.to_string())?;
+    if value != parsed {
+        return Err("changed after round trip".to_string());
+    }
+    Ok(())
+}
+
+fn check_fixture(path: &Path) -> Result<(), String> {
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    match name {
+        "status" => check::<ServerStatus>(path),
+        "me" => check::<UserData>(path),
+        "libraries" => check::<Libraries>(path),
+        "library" => check::<LibraryWithFilters>(path),
+        "library_items" => check::<PaginatedResponse<LibraryItemMinified>>(path),
+        "library_item" => check::<LibraryItem>(path),
+        "library_series" => check::<PaginatedResponse<LibrarySeries>>(path),
+        "library_stats" => check::<LibraryStats>(path),
+        "library_narrators" => check::<Narrators>(path),
+        "library_collections" => check::<PaginatedResponse<CollectionExpanded>>(path),
+        "library_playlists" => check::<PaginatedResponse<PlaylistExpanded>>(path),
+        "library_search" => check::<LibrarySearchResult>(path),
+        "author" => check::<AuthorExpanded>(path),
+        "listening_sessions" => check::<ListeningSessions>(path),
+        _ => Err("no schema for fixture".to_string()),
+    }
+}
+
+fn versions() -> Vec<PathBuf> {
+    let mut versions: Vec<_> = std::fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    versions.sort();
+    versions
+}
+
+#[test]
+fn parses_all_versions() {
+    let mut failures = vec![];
+    for version in versions() {
+        for entry in std::fs::read_dir(&version).unwrap() {
+            let path = entry.unwrap().path();
+            if let Err(error) = check_fixture(&path) {
+                failures.push(format!("{}: {error}", path.display()));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn every_version_is_complete() {
+    let versions = versions();
+    assert!(versions.len() >= 2, "Expected fixtures of several versions");
+    for version in versions {
+        for name in NAMES {
+            let path = version.join(format!("{name}.json"));
+            assert!(path.exists(), "{} is missing", path.display());
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use audiobookshelf_api::schema::{Id, LibraryItem};
+use audiobookshelf_api::servers::{SavedServer, ServerRegistry, Tagged};
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Method, Url, UserClient};
+
+fn client(url: &str, token: &str, transport: Arc<MockTransport>) -> UserClient {
+    let config = ClientConfig::new(Url::parse(url).unwrap())
+        .unwrap()
+        .with_transport(transport);
+    UserClient::from_token(config, token.to_string())
+}
+
+#[test]
+fn tagged_id_roundtrip() {
+    let id: Tagged<LibraryItem> = "home/books/li_1".parse().unwrap();
+    assert_eq!(id.server, "home/books");
+    assert_eq!(id.id, Id::new("li_1"));
+    assert_eq!(id.to_string(), "home/books/li_1");
+    assert_eq!(
+        serde_json::to_value(&id).unwrap(),
+        serde_json::json!("home/books/li_1")
+    );
+
+    assert!("li_1".parse::<Tagged<LibraryItem>>().is_err());
+    assert!("home/".parse::<Tagged<LibraryItem>>().is_err());
+}
+
+#[tokio::test]
+async fn dispatches_by_server() {
+    let books =
+        Arc::new(MockTransport::new().respond_json(Method::GET, "/ping", r#"{"success":true}"#));
+    let podcasts = Arc::new(MockTransport::new());
+    let mut registry = ServerRegistry::new();
+    registry.insert("books", client("http://books:13378", "a", books.clone()));
+    registry.insert(
+        "podcasts",
+        client("http://podcasts:13378", "b", podcasts.clone()),
+    );
+
+    let id: Tagged<LibraryItem> = Tagged::new("books", Id::new("li_1"));
+    assert!(registry.client_for(&id).unwrap().ping().await.unwrap());
+    assert_eq!(books.requests().len(), 1);
+    assert!(podcasts.requests().is_empty());
+
+    let pings = registry.gather(|client| client.ping()).await;
+    assert_eq!(pings.len(), 2);
+    assert_eq!(pings[0].0, "books");
+    assert!(pings[1].1.is_err());
+}
+
+#[test]
+fn restores_saved_servers() {
+    let saved = vec![SavedServer {
+        name: "remote".to_string(),
+        url: Url::parse("https://abs.example.com/audiobookshelf/").unwrap(),
+        token: "token".to_string(),
+        refresh_token: Some("refresh".to_string()),
+    }];
+    let registry = ServerRegistry::restore(saved.clone(), |config| config).unwrap();
+    assert_eq!(registry.get("remote").unwrap().token(), "token");
+    assert_eq!(registry.saved(), saved);
+}
@@ -0,0 +1,112 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use audiobookshelf_api::schema::{Id, Seconds};
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Method, StatusCode, Url, UserClient};
+use serde_json::{json, Value};
+
+fn client() -> (UserClient, Arc<MockTransport>) {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond(Method::POST, "/api/session/play_1/sync", StatusCode::OK, "")
+            .respond(
+                Method::POST,
+                "/api/session/play_1/close",
+                StatusCode::OK,
+                "",
+            ),
+    );
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(transport.clone());
+    (
+        UserClient::from_token(config, "token".to_string()),
+        transport,
+    )
+}
+
+/// Actions and bodies of session requests, in order
+fn session_requests(transport: &MockTransport) -> Vec<(String, Option<Value>)> {
+    transport
+        .requests()
+        .into_iter()
+        .zip(transport.request_bodies())
+        .map(|(request, body)| {
+            let action = request.url.path().rsplit('/').next().unwrap().to_string();
+            let body = body.map(|body| serde_json::from_str(&body).unwrap());
+            (action, body)
+        })
+        .collect()
+}
+
+fn sync(current_time: f64, time_listened: f64) -> (String, Option<Value>) {
+    (
+        "sync".to_string(),
+        Some(json!({"currentTime": current_time, "timeListened": time_listened})),
+    )
+}
+
+fn close() -> (String, Option<Value>) {
+    ("close".to_string(), None)
+}
+
+#[tokio::test(start_paused = true)]
+async fn heartbeat_syncs_listened_time() {
+    let (client, transport) = client();
+    let position = Arc::new(Mutex::new(100.0));
+    let heartbeat = client.start_session_heartbeat(Id::new("play_1"), Duration::from_secs(10), {
+        let position = position.clone();
+        move || Seconds(*position.lock().unwrap())
+    });
+
+    // First tick is immediate
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(session_requests(&transport), [sync(100.0, 0.0)]);
+
+    *position.lock().unwrap() = 108.0;
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert_eq!(transport.requests().len(), 1);
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert_eq!(session_requests(&transport)[1], sync(108.0, 8.0));
+
+    // Seeking is not listening: time listened is bounded by time passed
+    *position.lock().unwrap() = 500.0;
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert_eq!(session_requests(&transport)[2], sync(500.0, 10.0));
+
+    *position.lock().unwrap() = 400.0;
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert_eq!(session_requests(&transport)[3], sync(400.0, 0.0));
+
+    // Final sync happens on stop, 1 second after last tick
+    *position.lock().unwrap() = 400.5;
+    heartbeat.stop().await;
+    assert_eq!(
+        session_requests(&transport)[4..],
+        [sync(400.5, 0.5), close()]
+    );
+
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(transport.requests().len(), 6);
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropped_heartbeat_closes_session() {
+    let (client, transport) = client();
+    let heartbeat =
+        client.start_session_heartbeat(Id::new("play_1"), Duration::from_secs(10), || {
+            Seconds(100.0)
+        });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    drop(heartbeat);
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(
+        session_requests(&transport),
+        [sync(100.0, 0.0), sync(100.0, 0.0), close()]
+    );
+
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(transport.requests().len(), 3);
+}
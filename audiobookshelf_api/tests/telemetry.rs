@@ -0,0 +1,53 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::Arc;
+
+use audiobookshelf_api::schema::Id;
+use audiobookshelf_api::telemetry::{REQUESTS, REQUEST_DURATION};
+use audiobookshelf_api::transport::MockTransport;
+use audiobookshelf_api::{ClientConfig, Url, UserClient};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+#[test]
+fn records_requests_by_endpoint() {
+    let transport = Arc::new(MockTransport::new());
+    let config = ClientConfig::new(Url::parse("http://localhost:13378").unwrap())
+        .unwrap()
+        .with_transport(transport);
+    let client = UserClient::from_token(config, "token".to_string());
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    metrics::with_local_recorder(&recorder, || {
+        runtime.block_on(async {
+            let _ = client.library_item(&Id::new("li_8f2c")).await;
+        })
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let (key, _, _, value) = snapshot
+        .iter()
+        .find(|(key, ..)| key.key().name() == REQUESTS)
+        .unwrap();
+    let labels: Vec<(&str, &str)> = key
+        .key()
+        .labels()
+        .map(|label| (label.key(), label.value()))
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            ("method", "GET"),
+            ("endpoint", "/api/items/:id"),
+            ("status", "404")
+        ]
+    );
+    assert_eq!(*value, DebugValue::Counter(1));
+    assert!(snapshot
+        .iter()
+        .any(|(key, ..)| key.key().name() == REQUEST_DURATION));
+}
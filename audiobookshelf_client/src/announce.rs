@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use rodio::Sink;
+use serde::Deserialize;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Spoken status announcements.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AnnounceConfig {
+    /// Announce status then chapter changes during playback
+    pub on_chapter: bool,
+    /// TTS command writing WAV to stdout, e.g. `["espeak-ng", "--stdout"]`.
+    /// Text is appended as last argument, unless `text_on_stdin` is set.
+    pub command: Vec<String>,
+    pub text_on_stdin: bool,
+    /// Audiobook volume multiplier while announcement is playing
+    pub duck: f32,
+}
+
+impl Default for AnnounceConfig {
+    fn default() -> Self {
+        Self {
+            on_chapter: false,
+            command: vec!["espeak-ng".into(), "--stdout".into()],
+            text_on_stdin: false,
+            duck: 0.2,
+        }
+    }
+}
+
+impl AnnounceConfig {
+    /// Render `text` into audio file bytes.
+    pub async fn synthesize(&self, text: &str) -> Result<Vec<u8>> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .context("TTS command is not configured")?;
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        if !self.text_on_stdin {
+            command.arg(text);
+        }
+
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Unable to run TTS command {program}"))?;
+        let mut stdin = child.stdin.take().unwrap();
+        if self.text_on_stdin {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        drop(stdin);
+
+        let output = child.wait_with_output().await?;
+        anyhow::ensure!(
+            output.status.success(),
+            "TTS command failed: {}",
+            output.status
+        );
+        Ok(output.stdout)
+    }
+}
+
+/// Lowers audiobook volume while announcements play. Overlapping announcements duck it
+/// once, and volume is restored after the last one ends.
+#[derive(Clone)]
+pub struct Ducking {
+    sink: Arc<Sink>,
+    duck: f32,
+    /// Number of playing announcements, and volume before the first of them
+    state: Arc<Mutex<(usize, f32)>>,
+}
+
+impl Ducking {
+    pub fn new(sink: Arc<Sink>, duck: f32) -> Self {
+        Self {
+            sink,
+            duck,
+            state: Arc::new(Mutex::new((0, 1.0))),
+        }
+    }
+
+    pub fn start(&self) {
+        let mut state = self.state.lock().unwrap();
+        let (playing, volume) = &mut *state;
+        if *playing == 0 {
+            *volume = self.sink.volume();
+            self.sink.set_volume(*volume * self.duck);
+        }
+        *playing += 1;
+    }
+
+    pub fn end(&self) {
+        let mut state = self.state.lock().unwrap();
+        let (playing, volume) = &mut *state;
+        *playing = playing.saturating_sub(1);
+        if *playing == 0 {
+            self.sink.set_volume(*volume);
+        }
+    }
+}
+
+/// Text like "Chapter 12, 3 hours 5 minutes remaining".
+pub fn status_text(chapter: Option<usize>, remaining: f64) -> String {
+    let minutes = (remaining.max(0.0) / 60.0).round() as u64;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let remaining = match (hours, minutes) {
+        (0, minutes) => plural(minutes, "minute"),
+        (hours, 0) => plural(hours, "hour"),
+        (hours, minutes) => format!("{} {}", plural(hours, "hour"), plural(minutes, "minute")),
+    };
+    match chapter {
+        Some(chapter) => format!("Chapter {}, {remaining} remaining", chapter + 1),
+        None => format!("{remaining} remaining"),
+    }
+}
+
+fn plural(count: u64, unit: &str) -> String {
+    if count == 1 {
+        format!("{count} {unit}")
+    } else {
+        format!("{count} {unit}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_remaining_time() {
+        assert_eq!(status_text(None, 0.0), "0 minutes remaining");
+        assert_eq!(status_text(None, 20.0), "0 minutes remaining");
+        assert_eq!(status_text(None, 60.0), "1 minute remaining");
+        assert_eq!(status_text(None, 45.0 * 60.0), "45 minutes remaining");
+        assert_eq!(status_text(None, 3600.0), "1 hour remaining");
+        assert_eq!(status_text(None, 2.0 * 3600.0), "2 hours remaining");
+        // Rounded to minutes before splitting into hours
+        assert_eq!(status_text(None, 3600.0 - 10.0), "1 hour remaining");
+        assert_eq!(
+            status_text(None, 3600.0 + 60.0),
+            "1 hour 1 minute remaining"
+        );
+        assert_eq!(
+            status_text(Some(11), 3.0 * 3600.0 + 5.0 * 60.0),
+            "Chapter 12, 3 hours 5 minutes remaining"
+        );
+        assert_eq!(status_text(Some(0), -5.0), "Chapter 1, 0 minutes remaining");
+    }
+
+    #[test]
+    fn restores_volume_after_overlapping_announcements() {
+        let (sink, _queue) = Sink::new_idle();
+        let sink = Arc::new(sink);
+        sink.set_volume(0.5);
+        let ducking = Ducking::new(sink.clone(), 0.2);
+
+        ducking.start();
+        assert_eq!(sink.volume(), 0.1);
+        // Second announcement doesn't duck already ducked volume
+        ducking.start();
+        assert_eq!(sink.volume(), 0.1);
+        ducking.end();
+        assert_eq!(sink.volume(), 0.1);
+        ducking.end();
+        assert_eq!(sink.volume(), 0.5);
+
+        // Volume changed between announcements is the one restored
+        sink.set_volume(0.8);
+        ducking.start();
+        ducking.end();
+        assert_eq!(sink.volume(), 0.8);
+    }
+}
@@ -0,0 +1,82 @@
+use audiobookshelf_api::schema::{Id, LibraryItem};
+use audiobookshelf_api::Url;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Audiobookshelf player, controlled over HTTP or from terminal.
+///
+/// Settings are read from config file, then overridden by environment variables and
+/// finally by flags.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(version)]
+pub struct Cli {
+    /// `serve` if omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Config file, `$XDG_CONFIG_HOME/audiobookshelf_client/config.toml` by default
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_CLIENT_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Server address
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_URL")]
+    pub url: Option<Url>,
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_USERNAME")]
+    pub username: Option<String>,
+    /// Needed only for first login, token is saved afterwards
+    #[arg(
+        long,
+        global = true,
+        env = "AUDIOBOOKSHELF_PASSWORD",
+        hide_env_values = true
+    )]
+    pub password: Option<String>,
+    /// File tokens are saved to instead of keyring of OS, encrypted with
+    /// `AUDIOBOOKSHELF_CREDENTIALS_KEY`
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_CREDENTIALS")]
+    pub credentials_file: Option<PathBuf>,
+    /// Address of control server
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_CLIENT_LISTEN")]
+    pub listen: Option<String>,
+    /// Name of audio output device
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_DEVICE")]
+    pub device: Option<String>,
+    /// Initial volume, 1.0 being unchanged
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_VOLUME")]
+    pub volume: Option<f32>,
+    /// Read audio files from disk, for player running on the same machine as server
+    #[arg(long, global = true, env = "AUDIOBOOKSHELF_LOCAL")]
+    pub local: Option<bool>,
+}
+
+#[derive(Subcommand, Debug, Clone, Default)]
+pub enum Command {
+    /// Play current item, controlled over HTTP
+    #[default]
+    Serve,
+    /// List libraries, or items of library
+    List {
+        /// Id or name of library
+        library: Option<String>,
+    },
+    /// Play item until interrupted
+    Play { item: Id<LibraryItem> },
+    /// Continue playing most recent item until interrupted
+    Resume,
+    /// Browse libraries and control playback interactively
+    Tui,
+    /// Download audio files of item
+    Download {
+        item: Id<LibraryItem>,
+        /// Directory to download to
+        #[arg(long, short, default_value = ".")]
+        output: PathBuf,
+    },
+    /// List unfinished items
+    Progress,
+    /// Search books and podcasts
+    Search {
+        query: String,
+        /// Id or name of library, every library is searched if omitted
+        #[arg(long)]
+        library: Option<String>,
+    },
+}
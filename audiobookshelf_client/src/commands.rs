@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use audiobookshelf_api::downloads::{DownloadEvent, DownloadManager};
+use audiobookshelf_api::params::LibraryItemParams;
+use audiobookshelf_api::schema::{
+    Id, Library, LibraryItem, LibraryItemMinified, LibraryMedia, LibraryMediaMinified,
+};
+use audiobookshelf_api::UserClient;
+use std::path::PathBuf;
+
+/// Print libraries, or items of `library` given by id or name.
+pub async fn list(client: &UserClient, library: Option<String>) -> Result<()> {
+    let libraries = client.libraries().await?;
+    let Some(library) = library else {
+        for library in libraries {
+            println!("{}\t{}", library.id, library.name);
+        }
+        return Ok(());
+    };
+    let library = find_library(&libraries, &library)?;
+    let items = client
+        .library_items(&library.id, LibraryItemParams::default())
+        .await?;
+    for item in items {
+        let (title, author) = describe_minified(&item);
+        println!("{}\t{title}\t{author}", item.id);
+    }
+    Ok(())
+}
+
+/// Print items matching `query` in `library`, or in every library.
+pub async fn search(client: &UserClient, query: &str, library: Option<String>) -> Result<()> {
+    let libraries = client.libraries().await?;
+    let libraries = match library {
+        Some(library) => vec![find_library(&libraries, &library)?.clone()],
+        None => libraries,
+    };
+    for library in libraries {
+        let result = client.search_library(&library.id, query, 20).await?;
+        for found in result.book.iter().chain(&result.podcast) {
+            let (title, author) = describe(&found.library_item);
+            println!("{}\t{title}\t{author}", found.library_item.id);
+        }
+    }
+    Ok(())
+}
+
+/// Print unfinished items, most recently listened first.
+pub async fn progress(client: &UserClient) -> Result<()> {
+    let mut progress: Vec<_> = client
+        .me()
+        .await?
+        .media_progress
+        .into_iter()
+        .filter(|progress| !progress.is_finished && !progress.hide_from_continue_listening)
+        .collect();
+    progress.sort_by_key(|progress| std::cmp::Reverse(progress.last_update));
+
+    let ids: Vec<_> = progress
+        .iter()
+        .map(|progress| progress.library_item_id.clone())
+        .collect();
+    let items = client.library_items_batch(&ids).await?;
+    for progress in progress {
+        let title = items
+            .iter()
+            .find(|item| item.id == progress.library_item_id)
+            .map(|item| describe(item).0)
+            .unwrap_or_default();
+        println!(
+            "{}\t{:>3.0}%\t{}/{}\t{title}",
+            progress.library_item_id,
+            progress.progress * 100.0,
+            progress.current_time,
+            progress.duration,
+        );
+    }
+    Ok(())
+}
+
+/// Download audio files of item into `output`, reporting progress.
+pub async fn download(client: &UserClient, item: &Id<LibraryItem>, output: PathBuf) -> Result<()> {
+    let manager = DownloadManager::new(client.clone(), output);
+    let mut events = manager.subscribe();
+    let report = tokio::spawn(async move {
+        while let Ok(event) = events.recv().await {
+            if let DownloadEvent::Progress {
+                downloaded_bytes,
+                total_bytes,
+                ..
+            } = event
+            {
+                eprint!("\r{downloaded_bytes}/{total_bytes} bytes");
+            }
+        }
+    });
+    let manifest = manager.download(item).await;
+    report.abort();
+    eprintln!();
+
+    let manifest = manifest?;
+    for file in manifest.files {
+        println!("{}", manager.root().join(file.path).display());
+    }
+    Ok(())
+}
+
+fn find_library<'a>(libraries: &'a [Library], library: &str) -> Result<&'a Library> {
+    libraries
+        .iter()
+        .find(|candidate| candidate.id.as_str() == library || candidate.name == library)
+        .with_context(|| format!("No library {library}"))
+}
+
+/// Title and author of item.
+pub(crate) fn describe(item: &LibraryItem) -> (String, String) {
+    match &item.media {
+        LibraryMedia::Book { metadata, .. } => (
+            metadata.title.clone().unwrap_or_default(),
+            metadata
+                .authors
+                .iter()
+                .map(|author| author.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        LibraryMedia::Podcast { metadata, .. } => (
+            metadata.title.clone().unwrap_or_default(),
+            metadata.author.clone().unwrap_or_default(),
+        ),
+        _ => (String::new(), String::new()),
+    }
+}
+
+/// Title and author of item, as listed in library.
+pub(crate) fn describe_minified(item: &LibraryItemMinified) -> (String, String) {
+    match &item.media {
+        LibraryMediaMinified::Book { metadata, .. } => (
+            metadata.title.clone().unwrap_or_default(),
+            metadata.author_name.clone(),
+        ),
+        LibraryMediaMinified::Podcast { metadata, .. } => (
+            metadata.title.clone().unwrap_or_default(),
+            metadata.author.clone().unwrap_or_default(),
+        ),
+        _ => (String::new(), String::new()),
+    }
+}
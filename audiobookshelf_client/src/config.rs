@@ -0,0 +1,308 @@
+use crate::announce::AnnounceConfig;
+use crate::cli::Cli;
+use crate::controls::ControlsConfig;
+use crate::relay::RelayConfig;
+use crate::schedule::Alarm;
+use anyhow::{bail, Context, Result};
+use audiobookshelf_api::credentials::{CredentialStore, EncryptedFileStore, KeyringStore};
+use audiobookshelf_api::{ClientConfig, Url, UserClient};
+use serde::Deserialize;
+use std::env::var;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Player configuration loaded from TOML file, with settings of `server` and `player`
+/// overridden by environment variables and flags.
+///
+/// File is `$XDG_CONFIG_HOME/audiobookshelf_client/config.toml`, unless other path is given
+/// with `--config` or `AUDIOBOOKSHELF_CLIENT_CONFIG`. If it does not exist, defaults are used.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub player: PlayerConfig,
+    pub controls: ControlsConfig,
+    pub follow: FollowConfig,
+    pub schedule: Vec<Alarm>,
+    pub announce: AnnounceConfig,
+    pub relay: RelayConfig,
+    /// Accounts player can switch between.
+    /// Account of `server.username` is added first, replacing listed account of that name.
+    pub accounts: Vec<AccountConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub url: Option<Url>,
+    pub username: Option<String>,
+    /// Needed only until token is saved to credential store on first login.
+    pub password: Option<String>,
+    /// File tokens are saved to, encrypted with `AUDIOBOOKSHELF_CREDENTIALS_KEY`. Keyring of
+    /// OS is used if not set, unless the key is given, in which case file defaults to
+    /// `credentials.bin` in config directory.
+    pub credentials_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PlayerConfig {
+    /// Address of control server
+    pub listen: String,
+    /// Name of audio output device, default device of system if not set
+    pub device: Option<String>,
+    /// Initial volume, 1.0 being unchanged
+    pub volume: f32,
+    /// Read audio files from disk, for player running on the same machine as server
+    pub local: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AccountConfig {
+    pub name: String,
+    pub username: String,
+    /// Needed only until token is saved to credential store on first login.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// Follow progress made on other devices while paused.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct FollowConfig {
+    pub enabled: bool,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:3000".to_string(),
+            device: None,
+            volume: 1.0,
+            local: true,
+        }
+    }
+}
+
+impl AccountConfig {
+    pub async fn login(&self, server: &ServerConfig) -> Result<UserClient> {
+        let root_url = server.url.clone().context("Server url is not configured")?;
+        let config = ClientConfig::new(root_url)?;
+        let client = UserClient::auth_with_store(
+            config,
+            self.username.clone(),
+            self.password.clone(),
+            server.credential_store()?,
+        )
+        .await
+        .with_context(|| format!("Unable to log in as {}", self.username))?;
+        Ok(client)
+    }
+}
+
+impl ServerConfig {
+    /// Store of tokens, as described in `credentials_file`.
+    fn credential_store(&self) -> Result<Arc<dyn CredentialStore>> {
+        match (
+            var("AUDIOBOOKSHELF_CREDENTIALS_KEY"),
+            &self.credentials_file,
+        ) {
+            (Ok(passphrase), path) => {
+                let path = path
+                    .clone()
+                    .unwrap_or_else(|| config_dir().join("credentials.bin"));
+                Ok(Arc::new(EncryptedFileStore::open(path, &passphrase)?))
+            }
+            (Err(_), Some(path)) => bail!(
+                "AUDIOBOOKSHELF_CREDENTIALS_KEY is required to use {}",
+                path.display()
+            ),
+            (Err(_), None) => Ok(Arc::new(KeyringStore::new("audiobookshelf_client"))),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/audiobookshelf_client`, falling back to `~/.config`.
+pub fn config_dir() -> PathBuf {
+    let base = match (var("XDG_CONFIG_HOME"), var("HOME")) {
+        (Ok(config_home), _) if !config_home.is_empty() => PathBuf::from(config_home),
+        (_, Ok(home)) => Path::new(&home).join(".config"),
+        _ => PathBuf::from("."),
+    };
+    base.join("audiobookshelf_client")
+}
+
+impl Config {
+    /// Config file, overridden by `cli`, which also holds values of environment variables.
+    pub fn load(cli: &Cli) -> Result<Self> {
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => {
+                let path = config_dir().join("config.toml");
+                if path.exists() {
+                    Self::from_file(path)?
+                } else {
+                    Self::default()
+                }
+            }
+        };
+        config.apply(cli);
+
+        if let Some(username) = config.server.username.clone() {
+            let listed = config
+                .accounts
+                .iter()
+                .position(|account| account.name == username)
+                .map(|index| config.accounts.remove(index));
+            let account = AccountConfig {
+                name: username.clone(),
+                username,
+                password: config
+                    .server
+                    .password
+                    .clone()
+                    .or(listed.and_then(|account| account.password)),
+            };
+            config.accounts.insert(0, account);
+        }
+        Ok(config)
+    }
+
+    fn apply(&mut self, cli: &Cli) {
+        let Cli {
+            command: _,
+            config: _,
+            url,
+            username,
+            password,
+            credentials_file,
+            listen,
+            device,
+            volume,
+            local,
+        } = cli.clone();
+        let server = &mut self.server;
+        server.url = url.or(server.url.take());
+        server.username = username.or(server.username.take());
+        server.password = password.or(server.password.take());
+        server.credentials_file = credentials_file.or(server.credentials_file.take());
+        let player = &mut self.player;
+        player.listen = listen.unwrap_or(std::mem::take(&mut player.listen));
+        player.device = device.or(player.device.take());
+        player.volume = volume.unwrap_or(player.volume);
+        player.local = local.unwrap_or(player.local);
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read config {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Unable to parse config {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn config_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("audiobookshelf_client_config_{name}.toml"));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn load(path: PathBuf) -> Result<Config> {
+        Config::load(&Cli {
+            config: Some(path),
+            ..Default::default()
+        })
+    }
+
+    fn account_names(config: &Config) -> Vec<&str> {
+        config
+            .accounts
+            .iter()
+            .map(|account| account.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn layers_file_env_and_flags() {
+        let path = config_file(
+            "layers",
+            r#"
+            [server]
+            url = "http://file:13378/"
+            [player]
+            listen = "0.0.0.0:4000"
+            device = "file"
+            volume = 0.3
+            "#,
+        );
+        // No other test parses environment, so setting it here does not race
+        std::env::set_var("AUDIOBOOKSHELF_DEVICE", "env");
+        std::env::set_var("AUDIOBOOKSHELF_VOLUME", "0.6");
+        let cli = Cli::try_parse_from([
+            "audiobookshelf_client".as_ref(),
+            "--config".as_ref(),
+            path.as_os_str(),
+            "--device".as_ref(),
+            "flag".as_ref(),
+        ])
+        .unwrap();
+        std::env::remove_var("AUDIOBOOKSHELF_DEVICE");
+        std::env::remove_var("AUDIOBOOKSHELF_VOLUME");
+
+        let config = Config::load(&cli).unwrap();
+        assert_eq!(config.server.url.unwrap().as_str(), "http://file:13378/");
+        assert_eq!(config.player.listen, "0.0.0.0:4000");
+        assert_eq!(config.player.volume, 0.6);
+        assert_eq!(config.player.device.as_deref(), Some("flag"));
+        assert!(config.player.local);
+    }
+
+    #[test]
+    fn adds_server_account_once() {
+        let path = config_file(
+            "accounts",
+            r#"
+            [server]
+            username = "alice"
+            [[accounts]]
+            name = "bob"
+            username = "bob"
+            [[accounts]]
+            name = "alice"
+            username = "alice"
+            password = "listed"
+            "#,
+        );
+        let config = load(path.clone()).unwrap();
+        assert_eq!(account_names(&config), ["alice", "bob"]);
+        assert_eq!(config.accounts[0].password.as_deref(), Some("listed"));
+
+        let config = Config::load(&Cli {
+            config: Some(path),
+            username: Some("carol".to_string()),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(account_names(&config), ["carol", "bob", "alice"]);
+        assert_eq!(config.accounts[0].password.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn fails_on_missing_or_invalid_file() {
+        let missing = std::env::temp_dir().join("audiobookshelf_client_config_missing.toml");
+        let _ = std::fs::remove_file(&missing);
+        let error = load(missing).unwrap_err();
+        assert!(error.to_string().starts_with("Unable to read config"));
+
+        let invalid = config_file("invalid", "[player]\nvolume = \"loud\"\n");
+        let error = load(invalid).unwrap_err();
+        assert!(error.to_string().starts_with("Unable to parse config"));
+    }
+}
@@ -0,0 +1,277 @@
+use audiobookshelf_api::schema::Chapter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Action player can perform in response to control command.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Play,
+    Pause,
+    PlayPause,
+    JumpForward,
+    JumpBack,
+    NextChapter,
+    PreviousChapter,
+    VolumeUp,
+    VolumeDown,
+}
+
+/// What to do then same jump command is received twice within `window_ms`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DoubleSkipAction {
+    /// Jump by configured amount again
+    Repeat,
+    /// Jump by twice configured amount
+    Double,
+    /// Jump to chapter boundary
+    Chapter,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct DoubleSkipConfig {
+    pub window_ms: u64,
+    pub action: DoubleSkipAction,
+}
+
+/// Jump intervals and mapping from command names (media keys, MPRIS, MQTT...) to actions.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct ControlsConfig {
+    /// Seconds
+    pub jump_forward: f64,
+    /// Seconds
+    pub jump_back: f64,
+    pub volume_step: f32,
+    pub double_skip: DoubleSkipConfig,
+    pub commands: BTreeMap<String, Action>,
+}
+
+/// Resolved position change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Jump {
+    Relative(f64),
+    NextChapter,
+    PreviousChapter,
+}
+
+impl Jump {
+    /// Position in seconds after jumping from `position`, if there is where to jump.
+    pub fn target(self, position: f64, chapters: &[Chapter]) -> Option<f64> {
+        match self {
+            Jump::Relative(amount) => Some(position + amount),
+            Jump::NextChapter => chapters
+                .iter()
+                .find(|chapter| chapter.start.0 > position)
+                .map(|chapter| chapter.start.0),
+            // Small grace period allows to skip past start of current chapter
+            Jump::PreviousChapter => chapters
+                .iter()
+                .rev()
+                .find(|chapter| chapter.start.0 < position - 3.0)
+                .map(|chapter| chapter.start.0)
+                .or(Some(0.0)),
+        }
+    }
+}
+
+impl Default for DoubleSkipConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 500,
+            action: DoubleSkipAction::Repeat,
+        }
+    }
+}
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        let commands = [
+            ("play", Action::Play),
+            ("pause", Action::Pause),
+            ("play_pause", Action::PlayPause),
+            ("next", Action::JumpForward),
+            ("previous", Action::JumpBack),
+            ("seek_forward", Action::JumpForward),
+            ("seek_backward", Action::JumpBack),
+            ("next_chapter", Action::NextChapter),
+            ("previous_chapter", Action::PreviousChapter),
+            ("volume_up", Action::VolumeUp),
+            ("volume_down", Action::VolumeDown),
+        ]
+        .into_iter()
+        .map(|(command, action)| (command.to_string(), action))
+        .collect();
+
+        Self {
+            jump_forward: 30.0,
+            jump_back: 10.0,
+            volume_step: 0.1,
+            double_skip: DoubleSkipConfig::default(),
+            commands,
+        }
+    }
+}
+
+impl ControlsConfig {
+    pub fn action(&self, command: &str) -> Option<Action> {
+        self.commands.get(command).copied()
+    }
+
+    /// Resolve jump action into position change.
+    ///
+    /// `repeated` should be set if same action was issued within double skip window.
+    pub fn resolve_jump(&self, action: Action, repeated: bool) -> Option<Jump> {
+        let (amount, chapter) = match action {
+            Action::JumpForward => (self.jump_forward, Jump::NextChapter),
+            Action::JumpBack => (-self.jump_back, Jump::PreviousChapter),
+            Action::NextChapter => return Some(Jump::NextChapter),
+            Action::PreviousChapter => return Some(Jump::PreviousChapter),
+            _ => return None,
+        };
+        let jump = match (repeated, self.double_skip.action) {
+            (false, _) | (true, DoubleSkipAction::Repeat) => Jump::Relative(amount),
+            (true, DoubleSkipAction::Double) => Jump::Relative(amount * 2.0),
+            (true, DoubleSkipAction::Chapter) => chapter,
+        };
+        Some(jump)
+    }
+}
+
+/// Tracks last jump to detect double skips.
+#[derive(Default)]
+pub struct SkipTracker {
+    last: Option<(Action, Instant)>,
+}
+
+impl SkipTracker {
+    /// Register action, returning `true` if it repeats previous one within `window`.
+    pub fn register(&mut self, action: Action, window: Duration) -> bool {
+        self.register_at(action, window, Instant::now())
+    }
+
+    fn register_at(&mut self, action: Action, window: Duration, now: Instant) -> bool {
+        let repeated = matches!(
+            self.last,
+            Some((last, at)) if last == action && now.duration_since(at) <= window
+        );
+        // Repeated skip consumes the pair, so triple press starts a new one
+        self.last = if repeated { None } else { Some((action, now)) };
+        repeated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audiobookshelf_api::schema::Seconds;
+
+    const WINDOW: Duration = Duration::from_millis(500);
+
+    fn controls(action: DoubleSkipAction) -> ControlsConfig {
+        ControlsConfig {
+            double_skip: DoubleSkipConfig {
+                window_ms: 500,
+                action,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn chapters() -> Vec<Chapter> {
+        [(0.0, 100.0), (100.0, 250.0), (250.0, 400.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(id, (start, end))| Chapter {
+                id,
+                start: Seconds(start),
+                end: Seconds(end),
+                title: format!("Chapter {id}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_press_within_window() {
+        let start = Instant::now();
+        let mut skips = SkipTracker::default();
+        assert!(!skips.register_at(Action::JumpForward, WINDOW, start));
+        assert!(skips.register_at(Action::JumpForward, WINDOW, start + WINDOW));
+    }
+
+    #[test]
+    fn ignores_press_outside_window_or_of_other_action() {
+        let start = Instant::now();
+        let mut skips = SkipTracker::default();
+        assert!(!skips.register_at(Action::JumpForward, WINDOW, start));
+        let late = start + WINDOW + Duration::from_millis(1);
+        assert!(!skips.register_at(Action::JumpForward, WINDOW, late));
+        assert!(!skips.register_at(Action::JumpBack, WINDOW, late));
+    }
+
+    #[test]
+    fn third_press_starts_new_pair() {
+        let start = Instant::now();
+        let mut skips = SkipTracker::default();
+        let presses: Vec<_> = (0..4)
+            .map(|i| skips.register_at(Action::JumpBack, WINDOW, start + i * WINDOW / 4))
+            .collect();
+        assert_eq!(presses, [false, true, false, true]);
+    }
+
+    #[test]
+    fn resolves_repeated_jumps() {
+        let repeat = controls(DoubleSkipAction::Repeat);
+        assert_eq!(
+            repeat.resolve_jump(Action::JumpForward, false),
+            Some(Jump::Relative(30.0))
+        );
+        assert_eq!(
+            repeat.resolve_jump(Action::JumpForward, true),
+            Some(Jump::Relative(30.0))
+        );
+
+        let double = controls(DoubleSkipAction::Double);
+        assert_eq!(
+            double.resolve_jump(Action::JumpBack, false),
+            Some(Jump::Relative(-10.0))
+        );
+        assert_eq!(
+            double.resolve_jump(Action::JumpBack, true),
+            Some(Jump::Relative(-20.0))
+        );
+
+        let chapter = controls(DoubleSkipAction::Chapter);
+        assert_eq!(
+            chapter.resolve_jump(Action::JumpForward, true),
+            Some(Jump::NextChapter)
+        );
+        assert_eq!(
+            chapter.resolve_jump(Action::JumpBack, true),
+            Some(Jump::PreviousChapter)
+        );
+        assert_eq!(chapter.resolve_jump(Action::VolumeUp, true), None);
+    }
+
+    #[test]
+    fn jumps_between_chapters() {
+        let chapters = chapters();
+        assert_eq!(Jump::NextChapter.target(50.0, &chapters), Some(100.0));
+        assert_eq!(Jump::PreviousChapter.target(150.0, &chapters), Some(100.0));
+        // Close to chapter start goes to the one before
+        assert_eq!(Jump::PreviousChapter.target(102.0, &chapters), Some(0.0));
+    }
+
+    #[test]
+    fn chapter_jumps_stop_at_first_and_last_chapter() {
+        let chapters = chapters();
+        assert_eq!(Jump::PreviousChapter.target(50.0, &chapters), Some(0.0));
+        assert_eq!(Jump::PreviousChapter.target(1.0, &chapters), Some(0.0));
+        assert_eq!(Jump::NextChapter.target(300.0, &chapters), None);
+        assert_eq!(Jump::NextChapter.target(300.0, &[]), None);
+        assert_eq!(Jump::PreviousChapter.target(300.0, &[]), Some(0.0));
+    }
+}
@@ -1,29 +1,55 @@
-use anyhow::Result;
+mod announce;
+mod cli;
+mod commands;
+mod config;
+mod controls;
+mod relay;
+mod schedule;
+mod tui;
+
+use announce::Ducking;
+use anyhow::{Context, Result};
+use audiobookshelf_api::audio_stream::StreamStorageProvider;
 use audiobookshelf_api::params::{DeviceInfoParams, PlayLibraryItemParams};
-use audiobookshelf_api::schema::PlaybackSessionExtended;
-use audiobookshelf_api::stream_download::storage::temp::TempStorageProvider;
+use audiobookshelf_api::reqwest;
+use audiobookshelf_api::schema::{
+    Chapter, Codec, Id, LibraryItem, MediaProgressUpdate, MimeType, PlaybackMedia,
+    PlaybackSessionExtended, Playlist, PlaylistItem, PodcastEpisode,
+};
 use audiobookshelf_api::stream_download::StreamDownload;
 use audiobookshelf_api::{
     schema::{AudioTrack, FileMetadata},
-    ClientConfig, Url, UserClient,
+    UserClient,
 };
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{FromRef, Query, State},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
-use rodio::{source::EmptyCallback, Decoder, OutputStream, Sink};
+use clap::Parser;
+use cli::{Cli, Command};
+use config::Config;
+use controls::{Action, ControlsConfig, Jump, SkipTracker};
+use futures_util::{Stream, StreamExt};
+use relay::RelayConfig;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{source::EmptyCallback, Decoder, OutputStream, OutputStreamHandle, Sink};
+use schedule::{Alarm, Ramp, Scheduler};
 use serde::{Deserialize, Serialize};
-use std::env::var;
+use std::collections::VecDeque;
 use std::fs::File;
-use std::future::IntoFuture;
-use std::io::{BufReader, Read, Seek};
+use std::future::{Future, IntoFuture};
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 
+/// Formats enabled in rodio by default.
+const DECODERS: &[Codec] = &[Codec::Flac, Codec::Mp3, Codec::Vorbis];
+
 macro_rules! unwrap_or_return {
     ($option:expr, $result:expr) => {
         if let Some(value) = $option {
@@ -56,19 +82,58 @@ where
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    dotenv::dotenv()?;
-    let config = ClientConfig {
-        root_url: Url::parse(&var("AUDIOBOOKSHELF_URL")?)?,
-    };
-    let username = var("AUDIOBOOKSHELF_USERNAME")?;
-    let password = var("AUDIOBOOKSHELF_PASSWORD")?;
-    let listen_on = var("AUDIOBOOKSHELF_CLIENT_LISTEN")?;
-    let client = UserClient::auth(config, username, password).await?;
-
-    // Initialize audio player
-    let mut client = AudioClient::new(client)?;
-    client.use_local(true);
-    client.set_current_item().await?;
+    // `.env` is optional, settings may come from config file instead
+    let _ = dotenv::dotenv();
+    let cli = Cli::parse();
+    let player_config = Config::load(&cli)?;
+    let account = player_config
+        .accounts
+        .first()
+        .context("No accounts configured")?
+        .clone();
+    let client = account.login(&player_config.server).await?;
+
+    match cli.command.unwrap_or_default() {
+        Command::Serve => {
+            let mut client = AudioClient::new(client, account.name, player_config.clone())?;
+            client.set_current_item().await?;
+            serve(client, player_config).await
+        }
+        Command::Play { item } => {
+            let mut client = AudioClient::new(client, account.name, player_config)?;
+            client.set_item(&item).await?;
+            play_until_interrupted(client).await
+        }
+        Command::Resume => {
+            let mut client = AudioClient::new(client, account.name, player_config)?;
+            if !client.set_current_item().await? {
+                anyhow::bail!("Nothing to resume");
+            }
+            play_until_interrupted(client).await
+        }
+        Command::Tui => {
+            let mut player = AudioClient::new(client.clone(), account.name, player_config.clone())?;
+            player.set_current_item().await?;
+            // Resumed only once user asks for it
+            player.sink.pause();
+            let (send, recv) = mpsc::channel(64);
+            tokio::select! {
+                result = run_audio_client(&mut player, recv) => result?,
+                result = tui::run(client, send) => result?,
+            }
+            player.sink.pause();
+            player.close_session().await
+        }
+        Command::List { library } => commands::list(&client, library).await,
+        Command::Download { item, output } => commands::download(&client, &item, output).await,
+        Command::Progress => commands::progress(&client).await,
+        Command::Search { query, library } => commands::search(&client, &query, library).await,
+    }
+}
+
+/// Play with controls over HTTP.
+async fn serve(mut client: AudioClient, player_config: Config) -> Result<()> {
+    let listen_on = player_config.player.listen.clone();
     client.sink.play();
 
     // Connect player to server
@@ -82,7 +147,20 @@ async fn main() -> Result<()> {
         .route("/position/", get(get_position))
         .route("/volume/", post(set_volume))
         .route("/volume/", get(get_volume))
-        .with_state(send);
+        .route("/command/", post(command))
+        .route("/capabilities/", get(capabilities))
+        .route("/schedule/", post(add_alarm))
+        .route("/schedule/", get(get_alarms))
+        .route("/schedule/", delete(clear_alarms))
+        .route("/announce/", post(announce))
+        .route("/stream/", get(stream))
+        .route("/user/", get(get_user))
+        .route("/user/", post(switch_user))
+        .with_state(AppState {
+            sender: send,
+            controls: Arc::new(player_config.controls),
+            relay: Arc::new(player_config.relay),
+        });
 
     tokio::select! {
         result = run_audio_client(&mut client, recv) => {
@@ -96,6 +174,38 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Play in foreground until Ctrl-C, closing playback session afterwards.
+async fn play_until_interrupted(mut client: AudioClient) -> Result<()> {
+    client.sink.play();
+    // Sender is kept, so player runs until interrupted rather than until it has no events
+    let (_send, recv) = mpsc::channel(1);
+    tokio::select! {
+        result = run_audio_client(&mut client, recv) => result?,
+        result = tokio::signal::ctrl_c() => result?,
+    }
+    client.sink.pause();
+    client.close_session().await
+}
+
+#[derive(Clone)]
+struct AppState {
+    sender: mpsc::Sender<ClientEvent>,
+    controls: Arc<ControlsConfig>,
+    relay: Arc<RelayConfig>,
+}
+
+impl FromRef<AppState> for mpsc::Sender<ClientEvent> {
+    fn from_ref(state: &AppState) -> Self {
+        state.sender.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ControlsConfig> {
+    fn from_ref(state: &AppState) -> Self {
+        state.controls.clone()
+    }
+}
+
 #[derive(Deserialize)]
 struct SetPlayRequest {
     play: bool,
@@ -167,21 +277,232 @@ async fn get_volume(
     Ok(Json(Volume { volume }))
 }
 
+#[derive(Deserialize)]
+struct CommandRequest {
+    command: String,
+}
+
+/// Perform action mapped to command name in config.
+async fn command(State(state): State<AppState>, Json(data): Json<CommandRequest>) -> StatusCode {
+    let action = unwrap_or_return!(state.controls.action(&data.command), StatusCode::NOT_FOUND);
+    match state.sender.send(ClientEvent::Action(action)).await {
+        Ok(_) => StatusCode::OK,
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Report active jump intervals and command mapping.
+async fn capabilities(State(controls): State<Arc<ControlsConfig>>) -> Json<ControlsConfig> {
+    Json(controls.as_ref().clone())
+}
+
+async fn add_alarm(
+    State(sender): State<mpsc::Sender<ClientEvent>>,
+    Json(data): Json<Alarm>,
+) -> StatusCode {
+    match sender.send(ClientEvent::AddAlarm(data)).await {
+        Ok(_) => StatusCode::OK,
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+async fn get_alarms(
+    State(sender): State<mpsc::Sender<ClientEvent>>,
+) -> Result<Json<Vec<Alarm>>, ApiError> {
+    let (return_sender, receiver) = oneshot::channel();
+    sender.send(ClientEvent::GetAlarms(return_sender)).await?;
+    Ok(Json(receiver.await?))
+}
+
+async fn clear_alarms(State(sender): State<mpsc::Sender<ClientEvent>>) -> StatusCode {
+    match sender.send(ClientEvent::ClearAlarms).await {
+        Ok(_) => StatusCode::OK,
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[derive(Deserialize)]
+struct AnnounceRequest {
+    text: Option<String>,
+}
+
+/// Speak given text, or playback status if none provided.
+async fn announce(
+    State(sender): State<mpsc::Sender<ClientEvent>>,
+    data: Option<Json<AnnounceRequest>>,
+) -> Result<StatusCode, ApiError> {
+    let text = data.and_then(|Json(data)| data.text);
+    let (return_sender, receiver) = oneshot::channel();
+    sender
+        .send(ClientEvent::Announce(text, return_sender))
+        .await?;
+    receiver.await??;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+struct StreamQuery {
+    token: Option<String>,
+    /// Library item to relay instead of one currently playing
+    item: Option<String>,
+    /// Track index. Defaults to current track of playing item, or first track of selected one.
+    track: Option<usize>,
+}
+
+/// Relay audio track, so network players can play it.
+async fn stream(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> Result<Response, ApiError> {
+    if !state.relay.enabled {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+    if !state.relay.is_authorized(&headers, query.token.as_deref()) {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let (return_sender, receiver) = oneshot::channel();
+    state
+        .sender
+        .send(ClientEvent::OpenStream(
+            query.item,
+            query.track,
+            return_sender,
+        ))
+        .await?;
+    let (client, source) = receiver.await??;
+    let (response, mime_type, offset) = open_stream(&client, source).await?;
+    let (mime_type, body) = state.relay.relay(response, &mime_type, offset)?;
+    Ok(([(CONTENT_TYPE, mime_type)], body).into_response())
+}
+
+/// Track relayed over `/stream`, picked by player and requested by relay itself.
+enum StreamSource {
+    /// Track of playing item, relayed from `offset` seconds into it, so that network
+    /// player matches position of this one
+    Playing { track: Box<AudioTrack>, offset: f64 },
+    /// Track of selected item, first one by default
+    Item(Id<LibraryItem>, Option<usize>),
+}
+
+/// Request audio of relayed track.
+///
+/// Returns response with audio, its mime type and offset in seconds to relay it from.
+async fn open_stream(
+    client: &UserClient,
+    source: StreamSource,
+) -> Result<(reqwest::Response, MimeType, f64)> {
+    let (item, track) = match source {
+        StreamSource::Playing { track, offset } => {
+            let response = client.audiofile_response(&track.content_url).await?;
+            return Ok((response, track.mime_type, offset));
+        }
+        StreamSource::Item(item, track) => (item, track),
+    };
+    let mut params = AudioClient::playback_params();
+    // Direct play tracks are file urls, which stay valid once session is closed
+    params.force_direct_play = true;
+    let playback = client.library_item_play(&item, &params).await?;
+    let result = match playback.audio_tracks.get(track.unwrap_or(0)) {
+        Some(track) => client
+            .audiofile_response(&track.content_url)
+            .await
+            .map(|response| (response, track.mime_type.clone(), 0.0))
+            .map_err(Into::into),
+        None => Err(anyhow::anyhow!("No such track")),
+    };
+    // Session was only needed to learn track urls, and would be left open otherwise
+    client.close_session(&playback.playback_session.id).await?;
+    result
+}
+
+#[derive(Serialize)]
+struct Accounts {
+    active: String,
+    accounts: Vec<String>,
+}
+
+async fn get_user(
+    State(sender): State<mpsc::Sender<ClientEvent>>,
+) -> Result<Json<Accounts>, ApiError> {
+    let (return_sender, receiver) = oneshot::channel();
+    sender.send(ClientEvent::GetUser(return_sender)).await?;
+    Ok(Json(receiver.await?))
+}
+
+#[derive(Deserialize)]
+struct SwitchUserRequest {
+    name: String,
+}
+
+async fn switch_user(
+    State(sender): State<mpsc::Sender<ClientEvent>>,
+    Json(data): Json<SwitchUserRequest>,
+) -> Result<StatusCode, ApiError> {
+    let (return_sender, receiver) = oneshot::channel();
+    sender
+        .send(ClientEvent::SwitchUser(data.name, return_sender))
+        .await?;
+    if receiver.await?? {
+        Ok(StatusCode::OK)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
 struct AudioClient {
     client: UserClient,
+    /// Name of account `client` is logged in as
+    account: String,
     playing: Option<PlayingState>,
+    /// Rest of playlist started by alarm, played once current item ends
+    queue: VecDeque<PlaylistItem>,
+    /// Then set to `true`, player will assume that it executed on same machine as `audiobookshelf` server,
+    /// and will try to load audio files directly from file system, instead of proxying through server.
     use_local: bool,
+    config: Config,
+    skips: SkipTracker,
+    scheduler: Scheduler,
+    ramp: Option<Ramp>,
+    stop_at: Option<Instant>,
+    last_chapter: Option<usize>,
+    ducking: Ducking,
+    handle: OutputStreamHandle,
     sink: Arc<Sink>,
     /// Must be present even if not used.
     /// Dropping this value breaks `sink`
     _stream: OutputStream,
 }
 
+type ProgressUpdates = Pin<Box<dyn Stream<Item = MediaProgressUpdate> + Send>>;
+
 struct PlayingState {
     playback: PlaybackSessionExtended,
     current_track: usize,
 }
 
+/// Output to device named `device`, or to default device of system.
+fn output_stream(device: Option<&str>) -> Result<(OutputStream, OutputStreamHandle)> {
+    let Some(name) = device else {
+        return Ok(OutputStream::try_default()?);
+    };
+    let device = rodio::cpal::default_host()
+        .output_devices()?
+        .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+        .with_context(|| format!("No audio output device named {name}"))?;
+    Ok(OutputStream::try_from_device(&device)?)
+}
+
+impl PlayingState {
+    fn chapters(&self) -> &[Chapter] {
+        match &self.playback.playback_session.playback_media {
+            PlaybackMedia::Book { chapters, .. } => chapters,
+            PlaybackMedia::Podcast { chapters, .. } => chapters,
+            _ => &[],
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct PositionOffset {
     offset: f64,
@@ -193,8 +514,35 @@ enum ClientEvent {
     Pause,
     Seek(f64),
     Volume(f32),
+    Action(Action),
     GetVolume(oneshot::Sender<f32>),
     GetOffset(oneshot::Sender<Option<PositionOffset>>),
+    AddAlarm(Alarm),
+    GetAlarms(oneshot::Sender<Vec<Alarm>>),
+    ClearAlarms,
+    Announce(Option<String>, oneshot::Sender<Result<()>>),
+    OpenStream(
+        Option<String>,
+        Option<usize>,
+        oneshot::Sender<Result<(UserClient, StreamSource)>>,
+    ),
+    GetUser(oneshot::Sender<Accounts>),
+    SwitchUser(String, oneshot::Sender<Result<bool>>),
+    PlayItem(Id<LibraryItem>, oneshot::Sender<Result<bool>>),
+    Speed(f32),
+    GetNowPlaying(oneshot::Sender<Option<NowPlaying>>),
+}
+
+/// State of player, as shown by terminal UI.
+struct NowPlaying {
+    title: String,
+    author: String,
+    chapters: Vec<Chapter>,
+    chapter: Option<usize>,
+    position: PositionOffset,
+    paused: bool,
+    volume: f32,
+    speed: f32,
 }
 
 async fn run_audio_client(
@@ -202,6 +550,8 @@ async fn run_audio_client(
     mut events: mpsc::Receiver<ClientEvent>,
 ) -> Result<()> {
     let mut on_audio_end = client.wait_till_end();
+    let mut progress_updates = client.watch_progress().await;
+    let mut schedule_timer = tokio::time::interval(Duration::from_millis(500));
     loop {
         tokio::select! {
             event = events.recv() => {
@@ -215,19 +565,96 @@ async fn run_audio_client(
                     Some(ClientEvent::Volume(volume)) => {
                         client.sink.set_volume(volume)
                     },
+                    Some(ClientEvent::Action(action)) => {
+                        if client.perform(action).await? {
+                            on_audio_end = client.wait_till_end();
+                        }
+                    },
                     Some(ClientEvent::GetVolume(sender)) => {
                         let _ = sender.send(client.get_volume());
                     }
                     Some(ClientEvent::GetOffset(sender)) => {
                         let _ = sender.send(client.get_offset());
                     }
+                    Some(ClientEvent::AddAlarm(alarm)) => {
+                        client.scheduler.alarms.push(alarm);
+                    }
+                    Some(ClientEvent::GetAlarms(sender)) => {
+                        let _ = sender.send(client.scheduler.alarms.clone());
+                    }
+                    Some(ClientEvent::ClearAlarms) => {
+                        client.scheduler.alarms.clear();
+                    }
+                    Some(ClientEvent::Announce(text, sender)) => match client.announce(text) {
+                        Ok(announcement) => {
+                            tokio::spawn(async move {
+                                let _ = sender.send(announcement.await);
+                            });
+                        }
+                        Err(error) => {
+                            let _ = sender.send(Err(error));
+                        }
+                    },
+                    Some(ClientEvent::OpenStream(item, track, sender)) => {
+                        // Requests are made by relay, so player isn't blocked meanwhile
+                        let source = client.stream_source(item, track);
+                        let _ = sender.send(source.map(|source| (client.client.clone(), source)));
+                    }
+                    Some(ClientEvent::GetUser(sender)) => {
+                        let _ = sender.send(client.accounts());
+                    }
+                    Some(ClientEvent::SwitchUser(name, sender)) => {
+                        let result = client.switch_user(&name).await;
+                        if matches!(result, Ok(true)) {
+                            on_audio_end = client.wait_till_end();
+                            progress_updates = client.watch_progress().await;
+                        }
+                        let _ = sender.send(result);
+                    }
+                    Some(ClientEvent::PlayItem(item, sender)) => {
+                        let result = client.set_item(&item).await;
+                        if matches!(result, Ok(true)) {
+                            client.sink.play();
+                            on_audio_end = client.wait_till_end();
+                        }
+                        let _ = sender.send(result);
+                    }
+                    Some(ClientEvent::Speed(speed)) => {
+                        client.sink.set_speed(speed);
+                    }
+                    Some(ClientEvent::GetNowPlaying(sender)) => {
+                        let _ = sender.send(client.now_playing());
+                    }
                     None => { return Ok(()); }
                 }
             },
+            _ = schedule_timer.tick() => {
+                if client.run_schedule().await? {
+                    on_audio_end = client.wait_till_end();
+                }
+                client.announce_chapter_change();
+            },
+            update = next_update(&mut progress_updates) => {
+                match update {
+                    Some(update) => match client.follow(update).await {
+                        Ok(true) => on_audio_end = client.wait_till_end(),
+                        Ok(false) => {}
+                        // Item may be deleted, or not playable on this device
+                        Err(error) => eprintln!("Following progress failed: {error:#}"),
+                    },
+                    // Connection to server events is lost
+                    None => progress_updates = None,
+                }
+            },
             is_finished = on_audio_end.recv() => {
                 if is_finished.is_some() {
                     client.sink.clear();
-                    client.add_next_track().await?;
+                    if !client.add_next_track().await? {
+                        client.play_next_queued().await?;
+                    }
+                    on_audio_end = client.wait_till_end();
+                } else {
+                    // Sink was cleared by item that failed to start, wait for next one
                     on_audio_end = client.wait_till_end();
                 }
             }
@@ -235,25 +662,42 @@ async fn run_audio_client(
     }
 }
 
+/// Wait for next update, or forever if not following.
+async fn next_update(updates: &mut Option<ProgressUpdates>) -> Option<MediaProgressUpdate> {
+    match updates {
+        Some(updates) => updates.next().await,
+        None => std::future::pending().await,
+    }
+}
+
 impl AudioClient {
-    fn new(client: UserClient) -> Result<Self> {
-        let (_stream, handle) = rodio::OutputStream::try_default()?;
+    fn new(client: UserClient, account: String, config: Config) -> Result<Self> {
+        let (_stream, handle) = output_stream(config.player.device.as_deref())?;
         let sink = Arc::new(rodio::Sink::try_new(&handle)?);
+        sink.set_volume(config.player.volume);
+        if config.relay.mute_local {
+            sink.set_volume(0.0);
+        }
+        let ducking = Ducking::new(sink.clone(), config.announce.duck);
         Ok(Self {
             client,
+            account,
             sink,
             playing: None,
-            use_local: false,
+            queue: VecDeque::new(),
+            use_local: config.player.local,
+            scheduler: Scheduler::new(config.schedule.clone()),
+            config,
+            skips: SkipTracker::default(),
+            ramp: None,
+            stop_at: None,
+            last_chapter: None,
+            ducking,
+            handle,
             _stream,
         })
     }
 
-    /// Then set to `true`, player will assume that it executed on same machine as `audiobookshelf` server,
-    /// and will try to load audio files directly from file system, instead of proxying through server.
-    fn use_local(&mut self, use_local: bool) {
-        self.use_local = use_local;
-    }
-
     /// Pause execution until audio file fully played.
     ///
     /// Will immediatly file if sink is cleaned
@@ -273,25 +717,20 @@ impl AudioClient {
 
     fn get_offset(&self) -> Option<PositionOffset> {
         self.playing.as_ref().map(|p| PositionOffset {
-            offset: p.playback.audio_tracks[p.current_track].start_offset
+            offset: p.playback.audio_tracks[p.current_track].start_offset.0
                 + self.sink.get_pos().as_secs_f64(),
-            duration: p.playback.playback_session.duration,
+            duration: p.playback.playback_session.duration.0,
         })
     }
 
     fn playback_params() -> PlayLibraryItemParams {
-        PlayLibraryItemParams {
-            device_info: DeviceInfoParams {
-                client_name: Some("hukumkas_client".into()),
-                ..Default::default()
-            },
-            supported_mime_types: vec![
-                "audio/flac".into(),
-                "audio/mpeg".into(),
-                "audio/ogg".into(),
-            ],
-            ..Default::default()
-        }
+        PlayLibraryItemParams::builder()
+            .device(
+                DeviceInfoParams::detect()
+                    .with_client("hukumkas_client", env!("CARGO_PKG_VERSION")),
+            )
+            .mime_types(MimeType::supported(DECODERS))
+            .build()
     }
 
     /// Seek to position.
@@ -302,6 +741,7 @@ impl AudioClient {
         } else {
             return Ok(false);
         };
+        let position = position.clamp(0.0, playing.playback.playback_session.duration.0);
         let (current_track, offset) =
             Self::get_active_track_index(&playing.playback, position).unwrap();
         if current_track != playing.current_track {
@@ -314,6 +754,9 @@ impl AudioClient {
             if !is_paused {
                 self.sink.play();
             }
+            if let Some(playing) = &mut self.playing {
+                playing.current_track = current_track;
+            }
         }
         self.sink
             .try_seek(Duration::from_secs_f64(offset))
@@ -322,9 +765,295 @@ impl AudioClient {
         Ok(true)
     }
 
+    /// Perform control action.
+    ///
+    /// Returns `true` if sink was reset and end of track must be awaited again.
+    async fn perform(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Play => self.sink.play(),
+            Action::Pause => self.sink.pause(),
+            Action::PlayPause if self.sink.is_paused() => self.sink.play(),
+            Action::PlayPause => self.sink.pause(),
+            Action::VolumeUp => self
+                .sink
+                .set_volume(self.sink.volume() + self.config.controls.volume_step),
+            Action::VolumeDown => self
+                .sink
+                .set_volume((self.sink.volume() - self.config.controls.volume_step).max(0.0)),
+            _ => {
+                let window = Duration::from_millis(self.config.controls.double_skip.window_ms);
+                let repeated = self.skips.register(action, window);
+                let jump = unwrap_or_return!(
+                    self.config.controls.resolve_jump(action, repeated),
+                    Ok(false)
+                );
+                let position = unwrap_or_return!(self.jump_target(jump), Ok(false));
+                return self.seek(position).await;
+            }
+        }
+        Ok(false)
+    }
+
+    /// Fire due alarms, and advance active volume ramp and sleep timer.
+    ///
+    /// Returns `true` if sink was reset and end of track must be awaited again.
+    async fn run_schedule(&mut self) -> Result<bool> {
+        let mut reset = false;
+        for alarm in self.scheduler.poll() {
+            match self.start_alarm(&alarm).await {
+                Ok(alarm_reset) => reset |= alarm_reset,
+                // Alarm would fail the same way next time
+                Err(error) => {
+                    eprintln!("Alarm at {} failed and is removed: {error:#}", alarm.time);
+                    self.scheduler.alarms.retain(|other| *other != alarm);
+                }
+            }
+        }
+        if let Some(ramp) = &self.ramp {
+            let (volume, complete) = ramp.volume();
+            self.sink.set_volume(volume);
+            if complete {
+                self.ramp = None;
+            }
+        }
+        if self
+            .stop_at
+            .is_some_and(|stop_at| stop_at <= Instant::now())
+        {
+            self.sink.pause();
+            self.stop_at = None;
+        }
+        Ok(reset)
+    }
+
+    async fn start_alarm(&mut self, alarm: &Alarm) -> Result<bool> {
+        let reset = match (&alarm.playlist, &alarm.item) {
+            (Some(playlist), _) => self.set_playlist(playlist).await?,
+            (None, Some(item)) => self.set_item(item).await?,
+            (None, None) => self.set_current_item().await?,
+        };
+        if self.config.relay.mute_local {
+            // Audio is heard through relay only, local output stays silent
+        } else if alarm.ramp_secs > 0 {
+            self.sink.set_volume(0.0);
+            self.ramp = Some(Ramp::new(
+                Duration::from_secs(alarm.ramp_secs),
+                alarm.volume,
+            ));
+        } else {
+            self.sink.set_volume(alarm.volume);
+        }
+        self.stop_at = alarm
+            .stop_after_mins
+            .map(|minutes| Instant::now() + Duration::from_secs(minutes * 60));
+        self.sink.play();
+        Ok(reset)
+    }
+
+    /// Progress updates made on other devices, if follow mode is enabled.
+    ///
+    /// Player keeps working without following if server events can't be received.
+    async fn watch_progress(&self) -> Option<ProgressUpdates> {
+        if !self.config.follow.enabled {
+            return None;
+        }
+        match self.client.watch_progress().await {
+            Ok(updates) => Some(Box::pin(updates)),
+            Err(error) => {
+                eprintln!("Not following other devices, server events are unavailable: {error}");
+                None
+            }
+        }
+    }
+
+    /// Keep paused position in sync with progress made on other devices.
+    ///
+    /// Switches to other item if it was played on other device.
+    /// Returns `true` if sink was reset and end of track must be awaited again.
+    async fn follow(&mut self, update: MediaProgressUpdate) -> Result<bool> {
+        let progress = update.data;
+        if !self.sink.is_paused() || progress.is_finished || progress.hide_from_continue_listening {
+            return Ok(false);
+        }
+        match &self.playing {
+            Some(playing)
+                if playing.playback.playback_session.library_item_id
+                    == progress.library_item_id
+                    && playing.playback.playback_session.episode_id == progress.episode_id =>
+            {
+                let offset = unwrap_or_return!(self.get_offset(), Ok(false)).offset;
+                if (offset - progress.current_time.0).abs() < 1.0 {
+                    return Ok(false);
+                }
+                self.seek(progress.current_time.0).await
+            }
+            _ => {
+                self.queue.clear();
+                self.set_media(&progress.library_item_id, progress.episode_id.as_ref())
+                    .await
+            }
+        }
+    }
+
+    /// Position in seconds from beginning of audiobook after performing `jump`.
+    fn jump_target(&self, jump: Jump) -> Option<f64> {
+        let position = self.get_offset()?.offset;
+        jump.target(position, self.playing.as_ref()?.chapters())
+    }
+
+    fn now_playing(&self) -> Option<NowPlaying> {
+        let playing = self.playing.as_ref()?;
+        let session = &playing.playback.playback_session;
+        Some(NowPlaying {
+            title: session.display_title.clone(),
+            author: session.display_author.clone(),
+            chapters: playing.chapters().to_vec(),
+            chapter: self.current_chapter(),
+            position: self.get_offset()?,
+            paused: self.sink.is_paused(),
+            volume: self.sink.volume(),
+            speed: self.sink.speed(),
+        })
+    }
+
+    /// Close playback session of current item, if any.
+    async fn close_session(&mut self) -> Result<()> {
+        if let Some(playing) = self.playing.take() {
+            self.client
+                .close_session(&playing.playback.playback_session.id)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn current_chapter(&self) -> Option<usize> {
+        let offset = self.get_offset()?.offset;
+        self.playing
+            .as_ref()?
+            .chapters()
+            .iter()
+            .position(|chapter| chapter.start.0 <= offset && offset < chapter.end.0)
+    }
+
+    /// Speak `text`, or playback status if not set, ducking audiobook volume meanwhile.
+    ///
+    /// Returned future synthesizes speech and starts playing it, and is meant to be spawned,
+    /// since TTS may take a while.
+    fn announce(
+        &self,
+        text: Option<String>,
+    ) -> Result<impl Future<Output = Result<()>> + Send + 'static> {
+        let text = match text {
+            Some(text) => text,
+            None => {
+                let position = self.get_offset().context("Nothing is playing")?;
+                announce::status_text(self.current_chapter(), position.duration - position.offset)
+            }
+        };
+        let config = self.config.announce.clone();
+        let handle = self.handle.clone();
+        let ducking = self.ducking.clone();
+        Ok(async move {
+            let audio = config.synthesize(&text).await?;
+            let announcement = Sink::try_new(&handle)?;
+            announcement.append(Decoder::new(Cursor::new(audio))?);
+            ducking.start();
+            tokio::task::spawn_blocking(move || {
+                announcement.sleep_until_end();
+                ducking.end();
+            });
+            Ok(())
+        })
+    }
+
+    /// Announce status then playback enters next chapter, if enabled.
+    fn announce_chapter_change(&mut self) {
+        let chapter = self.current_chapter();
+        let previous = std::mem::replace(&mut self.last_chapter, chapter);
+        let changed = matches!((previous, chapter), (Some(a), Some(b)) if a != b);
+        if !changed || !self.config.announce.on_chapter || self.sink.is_paused() {
+            return;
+        }
+        // Failing TTS should not interrupt playback
+        match self.announce(None) {
+            Ok(announcement) => {
+                tokio::spawn(async move {
+                    if let Err(error) = announcement.await {
+                        eprintln!("Announcement failed: {error:#}");
+                    }
+                });
+            }
+            Err(error) => eprintln!("Announcement failed: {error:#}"),
+        }
+    }
+
+    fn accounts(&self) -> Accounts {
+        Accounts {
+            active: self.account.clone(),
+            accounts: self
+                .config
+                .accounts
+                .iter()
+                .map(|account| account.name.clone())
+                .collect(),
+        }
+    }
+
+    /// Log in as another configured account and load its continue listening state.
+    ///
+    /// Returns `false` if there is no such account.
+    async fn switch_user(&mut self, name: &str) -> Result<bool> {
+        let account = unwrap_or_return!(
+            self.config
+                .accounts
+                .iter()
+                .find(|account| account.name == name),
+            Ok(false)
+        );
+        let client = account.login(&self.config.server).await?;
+        let account = account.name.clone();
+
+        self.close_session().await?;
+        self.sink.clear();
+        self.client = client;
+        self.account = account;
+        self.last_chapter = None;
+        // Playlist and timers were started for previous user
+        self.queue.clear();
+        self.ramp = None;
+        self.stop_at = None;
+        self.set_current_item().await?;
+        Ok(true)
+    }
+
+    /// Track of playing item, or of selected `item`, to relay.
+    fn stream_source(&self, item: Option<String>, track: Option<usize>) -> Result<StreamSource> {
+        if let Some(item) = item {
+            return Ok(StreamSource::Item(Id::new(item), track));
+        }
+        let playing = self.playing.as_ref().context("Nothing is playing")?;
+        let index = track.unwrap_or(playing.current_track);
+        let track = playing
+            .playback
+            .audio_tracks
+            .get(index)
+            .context("No such track")?
+            .clone();
+        // Other tracks of playing item are relayed from their start
+        let offset = if index == playing.current_track {
+            self.sink.get_pos().as_secs_f64()
+        } else {
+            0.0
+        };
+        Ok(StreamSource::Playing {
+            track: Box::new(track),
+            offset,
+        })
+    }
+
     async fn add_next_track(&mut self) -> Result<bool> {
         let playing = unwrap_or_return!(&mut self.playing, Ok(false));
-        if playing.current_track >= playing.playback.audio_tracks.len() {
+        if playing.current_track + 1 >= playing.playback.audio_tracks.len() {
             return Ok(false);
         }
         playing.current_track += 1;
@@ -342,16 +1071,71 @@ impl AudioClient {
     async fn set_current_item(&mut self) -> Result<bool> {
         let current_library_item =
             unwrap_or_return!(self.client.me().await?.currently_listening(), Ok(false));
+        self.set_item(&current_library_item).await
+    }
+
+    /// Init sink with given item
+    async fn set_item(&mut self, item: &Id<LibraryItem>) -> Result<bool> {
+        self.queue.clear();
+        self.set_media(item, None).await
+    }
 
-        let playback = self
-            .client
-            .library_item_play(&current_library_item, &Self::playback_params())
+    /// Init sink with first unfinished item of playlist, queueing the rest.
+    async fn set_playlist(&mut self, playlist: &Id<Playlist>) -> Result<bool> {
+        let playlist = self.client.playlist(playlist).await?;
+        let user = self.client.me().await?;
+        let mut queue: VecDeque<_> = playlist
+            .items
+            .into_iter()
+            .filter(|item| {
+                !user
+                    .progress_for(&item.library_item_id, item.episode_id.as_ref())
+                    .is_some_and(|progress| progress.is_finished)
+            })
+            .map(|item| PlaylistItem {
+                library_item_id: item.library_item_id,
+                episode_id: item.episode_id,
+            })
+            .collect();
+        let first = unwrap_or_return!(queue.pop_front(), Ok(false));
+        let reset = self
+            .set_media(&first.library_item_id, first.episode_id.as_ref())
             .await?;
+        self.queue = queue;
+        Ok(reset)
+    }
+
+    /// Init sink with next queued playlist item, if any.
+    async fn play_next_queued(&mut self) -> Result<bool> {
+        let next = unwrap_or_return!(self.queue.pop_front(), Ok(false));
+        self.set_media(&next.library_item_id, next.episode_id.as_ref())
+            .await
+    }
+
+    /// Init sink with given item, or with its podcast episode.
+    ///
+    /// Session of previous item is closed first, so it isn't left open on server, and
+    /// previous item stops even if new one fails to start.
+    async fn set_media(
+        &mut self,
+        item: &Id<LibraryItem>,
+        episode: Option<&Id<PodcastEpisode>>,
+    ) -> Result<bool> {
+        self.close_session().await?;
+        self.sink.clear();
+        let params = Self::playback_params();
+        let playback = match episode {
+            Some(episode) => {
+                self.client
+                    .library_item_play_episode(item, episode, &params)
+                    .await?
+            }
+            None => self.client.library_item_play(item, &params).await?,
+        };
 
         let (current_track, offset) =
-            Self::get_active_track_index(&playback, playback.playback_session.current_time)
+            Self::get_active_track_index(&playback, playback.playback_session.current_time.0)
                 .unwrap();
-        self.sink.clear();
         self.sink.append(Decoder::new(
             self.get_audio_source(&playback.audio_tracks[current_track])
                 .await?,
@@ -385,8 +1169,8 @@ impl AudioClient {
         current_time: f64,
     ) -> Option<(usize, f64)> {
         for (index, track) in playback.audio_tracks.iter().enumerate() {
-            if track.start_offset + track.duration >= current_time {
-                return Some((index, current_time - track.start_offset));
+            if (track.start_offset + track.duration).0 >= current_time {
+                return Some((index, current_time - track.start_offset.0));
             }
         }
         None
@@ -403,4 +1187,4 @@ fn open_local_stream(metadata: &Option<FileMetadata>) -> Option<Box<dyn ReadSeek
 trait ReadSeekMarker: Read + Seek + Send + Sync {}
 
 impl<T: Read + Seek + Send + Sync> ReadSeekMarker for BufReader<T> {}
-impl ReadSeekMarker for StreamDownload<TempStorageProvider> {}
+impl ReadSeekMarker for StreamDownload<StreamStorageProvider> {}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use audiobookshelf_api::reqwest;
+use audiobookshelf_api::schema::MimeType;
+use axum::body::Body;
+use axum::http::{header::AUTHORIZATION, HeaderMap};
+use serde::Deserialize;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio_util::io::ReaderStream;
+
+/// Re-serving audio over `GET /stream/` for network players.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RelayConfig {
+    pub enabled: bool,
+    /// Required either as `Authorization: Bearer` header or `token` query parameter
+    pub token: String,
+    /// Keep local output silent, so audio is only heard through relay
+    pub mute_local: bool,
+    pub transcode: Option<TranscodeConfig>,
+}
+
+/// Transcoding with `ffmpeg`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TranscodeConfig {
+    /// Container format, passed as `-f`
+    pub format: String,
+    /// Audio codec, passed as `-c:a`
+    pub codec: String,
+    pub mime_type: String,
+}
+
+impl RelayConfig {
+    pub fn is_authorized(&self, headers: &HeaderMap, token: Option<&str>) -> bool {
+        if self.token.is_empty() {
+            return false;
+        }
+        let bearer = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        bearer
+            .or(token)
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), self.token.as_bytes()))
+    }
+
+    /// Turn upstream audio file response into relayed body and its mime type, starting
+    /// `offset` seconds into audio.
+    ///
+    /// Without transcoding, audio is remuxed by `ffmpeg` to start at `offset`, unless its
+    /// format is not known, then it is relayed from the start.
+    pub fn relay(
+        &self,
+        response: reqwest::Response,
+        mime_type: &MimeType,
+        offset: f64,
+    ) -> Result<(String, Body)> {
+        let transcode = match &self.transcode {
+            Some(transcode) => Some(transcode.clone()),
+            None if offset > 0.0 => TranscodeConfig::copy(mime_type),
+            None => None,
+        };
+        match transcode {
+            Some(transcode) => Ok((
+                transcode.mime_type.clone(),
+                transcode.transcode(response, offset)?,
+            )),
+            None => Ok((
+                mime_type.to_string(),
+                Body::from_stream(response.bytes_stream()),
+            )),
+        }
+    }
+}
+
+/// Compare without stopping at first difference, so response time doesn't reveal how much
+/// of token was guessed.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0, |difference, (left, right)| difference | (left ^ right))
+            == 0
+}
+
+impl TranscodeConfig {
+    /// Copy of audio stream into format streamable from any position.
+    fn copy(mime_type: &MimeType) -> Option<Self> {
+        let (format, mime_type) = match mime_type {
+            MimeType::Mpeg => ("mp3", "audio/mpeg"),
+            // Only AAC, usual for audiobooks, can be copied out of mp4
+            MimeType::Mp4 | MimeType::Aac => ("adts", "audio/aac"),
+            MimeType::Ogg => ("ogg", "audio/ogg"),
+            MimeType::Flac => ("flac", "audio/flac"),
+            MimeType::Webm => ("webm", "audio/webm"),
+            MimeType::Wav => ("wav", "audio/wav"),
+            MimeType::Other(_) => return None,
+        };
+        Some(Self {
+            format: format.to_string(),
+            codec: "copy".to_string(),
+            mime_type: mime_type.to_string(),
+        })
+    }
+
+    fn transcode(&self, mut response: reqwest::Response, offset: f64) -> Result<Body> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-loglevel", "error"])
+            .args(["-ss", &format!("{offset:.3}")])
+            .args(["-i", "pipe:0", "-vn"])
+            .args(["-c:a", &self.codec, "-f", &self.format, "pipe:1"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        tokio::spawn(async move {
+            // Listener disconnecting closes the pipe, which ends the copy
+            while let Ok(Some(chunk)) = response.chunk().await {
+                if stdin.write_all(&chunk).await.is_err() {
+                    break;
+                }
+            }
+            drop(stdin);
+            let _ = child.wait().await;
+        });
+
+        Ok(Body::from_stream(ReaderStream::new(stdout)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(token: &str) -> RelayConfig {
+        RelayConfig {
+            enabled: true,
+            token: token.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn compares_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret1"));
+        assert!(!constant_time_eq(b"secret", b"sec"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn accepts_header_or_query_token() {
+        let relay = config("secret");
+        assert!(relay.is_authorized(&bearer("secret"), None));
+        assert!(relay.is_authorized(&HeaderMap::new(), Some("secret")));
+        assert!(!relay.is_authorized(&bearer("wrong"), None));
+        assert!(!relay.is_authorized(&HeaderMap::new(), Some("secrets")));
+        assert!(!relay.is_authorized(&HeaderMap::new(), None));
+
+        let mut basic = HeaderMap::new();
+        basic.insert(AUTHORIZATION, "Basic secret".parse().unwrap());
+        assert!(!relay.is_authorized(&basic, None));
+    }
+
+    #[test]
+    fn empty_token_denies() {
+        let relay = config("");
+        assert!(!relay.is_authorized(&bearer(""), None));
+        assert!(!relay.is_authorized(&HeaderMap::new(), Some("")));
+        assert!(!relay.is_authorized(&HeaderMap::new(), None));
+    }
+}
@@ -0,0 +1,205 @@
+use audiobookshelf_api::schema::{Id, LibraryItem, Playlist};
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, Instant};
+
+fn deserialize_time<'d, D: Deserializer<'d>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let time = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(&time, "%H:%M"))
+        .map_err(serde::de::Error::custom)
+}
+
+fn serialize_time<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.format("%H:%M:%S").to_string())
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+/// Alarm starting playback at given local time of day.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Alarm {
+    /// `HH:MM` or `HH:MM:SS`
+    #[serde(deserialize_with = "deserialize_time")]
+    #[serde(serialize_with = "serialize_time")]
+    pub time: NaiveTime,
+    /// Days of week alarm is active on. Every day if empty.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Library item to play. Item currently listened to is used if neither item nor
+    /// playlist is set.
+    #[serde(default)]
+    pub item: Option<Id<LibraryItem>>,
+    /// Playlist to play from its first unfinished item. Takes precedence over `item`.
+    #[serde(default)]
+    pub playlist: Option<Id<Playlist>>,
+    /// Volume reached at the end of ramp up
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Seconds
+    #[serde(default)]
+    pub ramp_secs: u64,
+    #[serde(default)]
+    pub stop_after_mins: Option<u64>,
+}
+
+impl Alarm {
+    /// Check if alarm fires within `(from, to]`.
+    pub fn fires_between(&self, from: NaiveDateTime, to: NaiveDateTime) -> bool {
+        from.date()
+            .iter_days()
+            .take_while(|date| *date <= to.date())
+            .filter(|date| self.days.is_empty() || self.days.contains(&date.weekday()))
+            .map(|date| date.and_time(self.time))
+            .any(|at| at > from && at <= to)
+    }
+}
+
+pub struct Scheduler {
+    pub alarms: Vec<Alarm>,
+    last_check: NaiveDateTime,
+}
+
+impl Scheduler {
+    pub fn new(alarms: Vec<Alarm>) -> Self {
+        Self {
+            alarms,
+            last_check: Local::now().naive_local(),
+        }
+    }
+
+    /// Alarms fired since last call.
+    pub fn poll(&mut self) -> Vec<Alarm> {
+        self.poll_at(Local::now().naive_local())
+    }
+
+    fn poll_at(&mut self, now: NaiveDateTime) -> Vec<Alarm> {
+        let fired = self
+            .alarms
+            .iter()
+            .filter(|alarm| alarm.fires_between(self.last_check, now))
+            .cloned()
+            .collect();
+        self.last_check = now;
+        fired
+    }
+}
+
+/// Linear volume ramp up.
+pub struct Ramp {
+    start: Instant,
+    duration: Duration,
+    target: f32,
+}
+
+impl Ramp {
+    pub fn new(duration: Duration, target: f32) -> Self {
+        Self {
+            start: Instant::now(),
+            duration,
+            target,
+        }
+    }
+
+    /// Volume at current moment, and whether ramp is complete.
+    pub fn volume(&self) -> (f32, bool) {
+        self.volume_at(Instant::now())
+    }
+
+    fn volume_at(&self, now: Instant) -> (f32, bool) {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= self.duration {
+            (self.target, true)
+        } else {
+            let part = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+            (self.target * part, false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        // October 2026 starts on Thursday, 12th is Monday
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_time(NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap())
+    }
+
+    fn alarm(time: &str, days: Vec<Weekday>) -> Alarm {
+        Alarm {
+            time: NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap(),
+            days,
+            item: None,
+            playlist: None,
+            volume: 1.0,
+            ramp_secs: 0,
+            stop_after_mins: None,
+        }
+    }
+
+    #[test]
+    fn fires_within_window_crossing_midnight() {
+        let from = at(12, "23:59:50");
+        let to = at(13, "00:00:20");
+        assert!(alarm("00:00:10", vec![]).fires_between(from, to));
+        assert!(alarm("23:59:55", vec![]).fires_between(from, to));
+        assert!(!alarm("00:00:30", vec![]).fires_between(from, to));
+        assert!(!alarm("12:00:00", vec![]).fires_between(from, to));
+    }
+
+    #[test]
+    fn fires_on_listed_days_only() {
+        assert_eq!(at(12, "00:00:00").weekday(), Weekday::Mon);
+        let from = at(12, "06:59:50");
+        let to = at(12, "07:00:10");
+        assert!(alarm("07:00:00", vec![Weekday::Mon, Weekday::Fri]).fires_between(from, to));
+        assert!(!alarm("07:00:00", vec![Weekday::Tue]).fires_between(from, to));
+
+        // Day is the one alarm fires on, not the one window starts on
+        let from = at(12, "23:59:50");
+        let to = at(13, "00:00:20");
+        assert!(alarm("00:00:10", vec![Weekday::Tue]).fires_between(from, to));
+        assert!(!alarm("00:00:10", vec![Weekday::Mon]).fires_between(from, to));
+    }
+
+    #[test]
+    fn window_excludes_start_and_includes_end() {
+        let alarm = alarm("07:00:00", vec![]);
+        assert!(alarm.fires_between(at(12, "06:59:59"), at(12, "07:00:00")));
+        assert!(!alarm.fires_between(at(12, "07:00:00"), at(12, "07:00:01")));
+    }
+
+    #[test]
+    fn poll_fires_once() {
+        let mut scheduler = Scheduler::new(vec![alarm("07:00:00", vec![])]);
+        scheduler.last_check = at(12, "06:59:59");
+        assert_eq!(scheduler.poll_at(at(12, "07:00:00")).len(), 1);
+        assert!(scheduler.poll_at(at(12, "07:00:00")).is_empty());
+        assert!(scheduler.poll_at(at(12, "07:00:01")).is_empty());
+        assert_eq!(scheduler.poll_at(at(13, "07:00:01")).len(), 1);
+    }
+
+    #[test]
+    fn ramp_rises_linearly_to_target() {
+        let ramp = Ramp::new(Duration::from_secs(10), 0.8);
+        assert_eq!(ramp.volume_at(ramp.start), (0.0, false));
+        assert_eq!(
+            ramp.volume_at(ramp.start + Duration::from_secs(5)),
+            (0.4, false)
+        );
+        assert_eq!(
+            ramp.volume_at(ramp.start + Duration::from_secs(10)),
+            (0.8, true)
+        );
+        assert_eq!(
+            ramp.volume_at(ramp.start + Duration::from_secs(60)),
+            (0.8, true)
+        );
+    }
+}
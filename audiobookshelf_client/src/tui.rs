@@ -0,0 +1,480 @@
+use crate::commands::{describe, describe_minified};
+use crate::controls::Action;
+use crate::{ClientEvent, NowPlaying};
+use anyhow::Result;
+use audiobookshelf_api::params::{CoverFormat, CoverParams, LibraryItemParams};
+use audiobookshelf_api::schema::{Id, Library, LibraryItem};
+use audiobookshelf_api::UserClient;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures_util::StreamExt;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, List, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+const SPEED_STEP: f32 = 0.1;
+const HELP: &str = "q quit · tab focus · enter select · / search · space pause · ←/→ seek · n/p chapter · +/- volume · [/] speed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Libraries,
+    Items,
+    Chapters,
+}
+
+struct Entry {
+    id: Id<LibraryItem>,
+    title: String,
+    author: String,
+}
+
+/// Library browser and now playing pane, controlling player over `player` channel.
+struct App {
+    client: UserClient,
+    player: mpsc::Sender<ClientEvent>,
+    focus: Focus,
+    libraries: Vec<Library>,
+    library_state: ListState,
+    items: Vec<Entry>,
+    item_state: ListState,
+    chapter_state: ListState,
+    /// Query being typed, while in search mode
+    search: Option<String>,
+    /// Cover of each item, `None` if item has no cover or it can't be decoded
+    covers: HashMap<Id<LibraryItem>, Option<DynamicImage>>,
+    now_playing: Option<NowPlaying>,
+    status: String,
+    quit: bool,
+}
+
+/// Run terminal UI until user quits.
+pub async fn run(client: UserClient, player: mpsc::Sender<ClientEvent>) -> Result<()> {
+    let mut app = App::new(client, player).await?;
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+impl App {
+    async fn new(client: UserClient, player: mpsc::Sender<ClientEvent>) -> Result<Self> {
+        let libraries = client.libraries().await?;
+        let mut app = Self {
+            client,
+            player,
+            focus: Focus::Libraries,
+            libraries,
+            library_state: ListState::default(),
+            items: vec![],
+            item_state: ListState::default(),
+            chapter_state: ListState::default(),
+            search: None,
+            covers: HashMap::new(),
+            now_playing: None,
+            status: HELP.to_string(),
+            quit: false,
+        };
+        if !app.libraries.is_empty() {
+            app.library_state.select(Some(0));
+            app.load_items().await;
+        }
+        Ok(app)
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let mut input = EventStream::new();
+        let mut refresh = tokio::time::interval(Duration::from_millis(250));
+        let (cover_sender, mut covers) = mpsc::channel(16);
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            tokio::select! {
+                event = input.next() => match event {
+                    Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                        self.on_key(key).await;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(error)) => return Err(error.into()),
+                    None => return Ok(()),
+                },
+                _ = refresh.tick() => {
+                    self.now_playing = self.request(ClientEvent::GetNowPlaying).await.flatten();
+                    self.fetch_cover(&cover_sender);
+                },
+                Some((id, cover)) = covers.recv() => {
+                    self.covers.insert(id, cover);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Send event to player, waiting for its answer.
+    async fn request<T>(&self, event: impl FnOnce(oneshot::Sender<T>) -> ClientEvent) -> Option<T> {
+        let (sender, receiver) = oneshot::channel();
+        self.player.send(event(sender)).await.ok()?;
+        receiver.await.ok()
+    }
+
+    async fn send(&self, event: ClientEvent) {
+        let _ = self.player.send(event).await;
+    }
+
+    async fn on_key(&mut self, key: KeyEvent) {
+        if let Some(query) = &mut self.search {
+            match key.code {
+                KeyCode::Char(c) => query.push(c),
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Esc => self.search = None,
+                KeyCode::Enter => {
+                    let query = self.search.take().unwrap_or_default();
+                    self.search_items(&query).await;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Libraries => Focus::Items,
+                    Focus::Items => Focus::Chapters,
+                    Focus::Chapters => Focus::Libraries,
+                }
+            }
+            KeyCode::Char('/') => self.search = Some(String::new()),
+            KeyCode::Up | KeyCode::Char('k') => self.focused_list().select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.focused_list().select_next(),
+            KeyCode::Enter => self.select().await,
+            KeyCode::Char(' ') => self.send(ClientEvent::Action(Action::PlayPause)).await,
+            KeyCode::Left => self.send(ClientEvent::Action(Action::JumpBack)).await,
+            KeyCode::Right => self.send(ClientEvent::Action(Action::JumpForward)).await,
+            KeyCode::Char('n') => self.send(ClientEvent::Action(Action::NextChapter)).await,
+            KeyCode::Char('p') => {
+                self.send(ClientEvent::Action(Action::PreviousChapter))
+                    .await
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.send(ClientEvent::Action(Action::VolumeUp)).await
+            }
+            KeyCode::Char('-') => self.send(ClientEvent::Action(Action::VolumeDown)).await,
+            KeyCode::Char(']') => self.change_speed(SPEED_STEP).await,
+            KeyCode::Char('[') => self.change_speed(-SPEED_STEP).await,
+            _ => {}
+        }
+    }
+
+    fn focused_list(&mut self) -> &mut ListState {
+        match self.focus {
+            Focus::Libraries => &mut self.library_state,
+            Focus::Items => &mut self.item_state,
+            Focus::Chapters => &mut self.chapter_state,
+        }
+    }
+
+    /// Open library, play item or seek to chapter, depending on focus.
+    async fn select(&mut self) {
+        match self.focus {
+            Focus::Libraries => {
+                self.load_items().await;
+                self.focus = Focus::Items;
+            }
+            Focus::Items => {
+                let Some(entry) = self.item_state.selected().and_then(|i| self.items.get(i)) else {
+                    return;
+                };
+                let id = entry.id.clone();
+                self.status = format!("Loading {}", entry.title);
+                match self
+                    .request(|sender| ClientEvent::PlayItem(id, sender))
+                    .await
+                {
+                    Some(Ok(_)) => self.status = HELP.to_string(),
+                    Some(Err(error)) => self.status = format!("Failed to play: {error}"),
+                    None => self.status = "Player stopped".to_string(),
+                }
+            }
+            Focus::Chapters => {
+                let start = self.chapter_state.selected().and_then(|i| {
+                    let now_playing = self.now_playing.as_ref()?;
+                    Some(now_playing.chapters.get(i)?.start.0)
+                });
+                if let Some(start) = start {
+                    self.send(ClientEvent::Seek(start)).await;
+                }
+            }
+        }
+    }
+
+    async fn change_speed(&mut self, change: f32) {
+        let speed = self.now_playing.as_ref().map_or(1.0, |n| n.speed);
+        let speed = ((speed + change) * 10.0).round() / 10.0;
+        self.send(ClientEvent::Speed(speed.clamp(0.5, 3.0))).await;
+    }
+
+    fn selected_library(&self) -> Option<&Library> {
+        self.library_state
+            .selected()
+            .and_then(|i| self.libraries.get(i))
+    }
+
+    async fn load_items(&mut self) {
+        let Some(library) = self.selected_library() else {
+            return;
+        };
+        let result = self
+            .client
+            .library_items(&library.id, LibraryItemParams::default())
+            .await;
+        match result {
+            Ok(items) => self.set_items(
+                items
+                    .iter()
+                    .map(|item| Entry::new(&item.id, describe_minified(item)))
+                    .collect(),
+            ),
+            Err(error) => self.status = format!("Failed to load items: {error}"),
+        }
+    }
+
+    async fn search_items(&mut self, query: &str) {
+        let Some(library) = self.selected_library() else {
+            return;
+        };
+        match self.client.search_library(&library.id, query, 50).await {
+            Ok(result) => {
+                let found = result.book.iter().chain(&result.podcast);
+                self.set_items(
+                    found
+                        .map(|found| {
+                            Entry::new(&found.library_item.id, describe(&found.library_item))
+                        })
+                        .collect(),
+                );
+                self.focus = Focus::Items;
+                self.status = format!("Results for \"{query}\"");
+            }
+            Err(error) => self.status = format!("Search failed: {error}"),
+        }
+    }
+
+    fn set_items(&mut self, items: Vec<Entry>) {
+        self.item_state.select((!items.is_empty()).then_some(0));
+        self.items = items;
+    }
+
+    /// Start fetching cover of selected item, unless it is fetched already.
+    fn fetch_cover(&mut self, sender: &mpsc::Sender<(Id<LibraryItem>, Option<DynamicImage>)>) {
+        let Some(entry) = self.item_state.selected().and_then(|i| self.items.get(i)) else {
+            return;
+        };
+        if self.covers.contains_key(&entry.id) {
+            return;
+        }
+        // Placeholder prevents fetching the same cover again meanwhile
+        self.covers.insert(entry.id.clone(), None);
+        let client = self.client.clone();
+        let sender = sender.clone();
+        let id = entry.id.clone();
+        tokio::spawn(async move {
+            let params = CoverParams::new().width(400).format(CoverFormat::Jpeg);
+            let cover = client
+                .item_cover(&id, params)
+                .await
+                .ok()
+                .and_then(|image| image::load_from_memory(&image.data).ok());
+            let _ = sender.send((id, cover)).await;
+        });
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [browser, player, status] = Layout::vertical([
+            Constraint::Min(8),
+            Constraint::Length(12),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [libraries, items, cover] = Layout::horizontal([
+            Constraint::Percentage(20),
+            Constraint::Percentage(50),
+            Constraint::Percentage(30),
+        ])
+        .areas(browser);
+
+        let library_list = List::new(self.libraries.iter().map(|library| library.name.clone()))
+            .block(self.block("Libraries", Focus::Libraries))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(library_list, libraries, &mut self.library_state);
+
+        let items_title = match &self.search {
+            Some(query) => format!("Search: {query}_"),
+            None => "Items".to_string(),
+        };
+        let item_list = List::new(self.items.iter().map(|entry| {
+            Line::from(vec![
+                Span::raw(entry.title.clone()),
+                Span::styled(
+                    format!("  {}", entry.author),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        }))
+        .block(self.block(&items_title, Focus::Items))
+        .highlight_style(highlight());
+        frame.render_stateful_widget(item_list, items, &mut self.item_state);
+
+        let cover_block = Block::bordered().title("Cover");
+        let cover_area = cover_block.inner(cover);
+        frame.render_widget(cover_block, cover);
+        let selected_cover = self
+            .item_state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .and_then(|entry| self.covers.get(&entry.id)?.as_ref());
+        if let Some(image) = selected_cover {
+            frame.render_widget(Paragraph::new(cover_lines(image, cover_area)), cover_area);
+        }
+
+        self.draw_player(frame, player);
+        frame.render_widget(
+            Paragraph::new(self.status.as_str()).style(Style::default().fg(Color::DarkGray)),
+            status,
+        );
+    }
+
+    fn draw_player(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(now_playing) = &self.now_playing else {
+            frame.render_widget(
+                Paragraph::new("Nothing is playing").block(Block::bordered().title("Now playing")),
+                area,
+            );
+            return;
+        };
+        let title = format!(
+            "Now playing: {} — {}",
+            now_playing.title, now_playing.author
+        );
+        let block = self.block(&title, Focus::Chapters);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [chapters, progress] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+
+        if self.chapter_state.selected().is_none() || self.focus != Focus::Chapters {
+            self.chapter_state.select(now_playing.chapter);
+        }
+        let chapter_list =
+            List::new(now_playing.chapters.iter().enumerate().map(|(i, chapter)| {
+                let style = if Some(i) == now_playing.chapter {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Line::styled(
+                    format!("{}  {}", format_time(chapter.start.0), chapter.title),
+                    style,
+                )
+            }))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(chapter_list, chapters, &mut self.chapter_state);
+
+        let position = &now_playing.position;
+        let ratio = if position.duration > 0.0 {
+            (position.offset / position.duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let label = format!(
+            "{} {} / {}  vol {:.0}%  {:.1}x",
+            if now_playing.paused { "⏸" } else { "▶" },
+            format_time(position.offset),
+            format_time(position.duration),
+            now_playing.volume * 100.0,
+            now_playing.speed,
+        );
+        frame.render_widget(
+            Gauge::default()
+                .ratio(ratio)
+                .label(label)
+                .gauge_style(Style::default().fg(Color::Cyan)),
+            progress,
+        );
+    }
+
+    fn block<'a>(&self, title: &'a str, focus: Focus) -> Block<'a> {
+        let block = Block::bordered().title(title);
+        if self.focus == focus {
+            block.border_style(Style::default().fg(Color::Yellow))
+        } else {
+            block
+        }
+    }
+}
+
+impl Entry {
+    fn new(id: &Id<LibraryItem>, (title, author): (String, String)) -> Self {
+        Self {
+            id: id.clone(),
+            title,
+            author,
+        }
+    }
+}
+
+fn highlight() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+/// Image drawn with half blocks, two pixels per cell, fitted into `area`.
+fn cover_lines(image: &DynamicImage, area: Rect) -> Vec<Line<'static>> {
+    if area.width == 0 || area.height == 0 {
+        return vec![];
+    }
+    let image = image
+        .resize(
+            u32::from(area.width),
+            u32::from(area.height) * 2,
+            FilterType::Triangle,
+        )
+        .to_rgb8();
+    let color = |x, y| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        Color::Rgb(r, g, b)
+    };
+    (0..image.height() / 2)
+        .map(|row| {
+            let spans: Vec<_> = (0..image.width())
+                .map(|x| {
+                    Span::styled(
+                        "▀",
+                        Style::default()
+                            .fg(color(x, row * 2))
+                            .bg(color(x, row * 2 + 1)),
+                    )
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Seconds as `h:mm:ss`.
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
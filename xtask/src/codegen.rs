@@ -0,0 +1,319 @@
+//! Rust source of schema types, written in style of `audiobookshelf_api::schema`.
+//!
+//! Output is meant to be merged into `schema.rs`, which provides imports, `Extra` and
+//! helper modules such as `timestamp` referenced by overrides.
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::openapi::{Schema, Spec};
+use crate::overrides::Overrides;
+
+const DERIVE: &str = "#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "yield",
+];
+
+pub fn generate(spec: &Spec, overrides: &Overrides) -> Result<String> {
+    let generator = Generator { spec, overrides };
+    let mut output = String::from(
+        "// Generated by `cargo xtask schema`. Corrections belong to overrides file.\n",
+    );
+    for (name, schema) in &spec.components.schemas {
+        if overrides
+            .type_override(name)
+            .is_some_and(|type_override| type_override.skip)
+        {
+            continue;
+        }
+        output.push('\n');
+        generator
+            .item(&mut output, name, schema)
+            .map_err(|error| anyhow!("{name}: {error}"))?;
+    }
+    Ok(output)
+}
+
+/// Properties in order of spec, with names of required ones.
+type Object = (Vec<(String, Schema)>, Vec<String>);
+
+struct Generator<'a> {
+    spec: &'a Spec,
+    overrides: &'a Overrides,
+}
+
+impl Generator<'_> {
+    fn item(&self, output: &mut String, name: &str, schema: &Schema) -> Result<()> {
+        let type_name = self.type_name(name);
+        doc(output, "", schema.description.as_deref());
+        let is_object = schema.type_.as_deref() == Some("object")
+            || !schema.properties.is_empty()
+            || !schema.all_of.is_empty();
+        if is_object {
+            self.structure(output, name, &type_name, schema)
+        } else if schema.type_.as_deref() == Some("string") && !schema.enum_values.is_empty() {
+            enumeration(output, &type_name, schema)
+        } else {
+            let rust_type = self.rust_type(schema)?;
+            writeln!(output, "pub type {type_name} = {rust_type};")?;
+            Ok(())
+        }
+    }
+
+    fn structure(
+        &self,
+        output: &mut String,
+        name: &str,
+        type_name: &str,
+        schema: &Schema,
+    ) -> Result<()> {
+        let (properties, required) = self.object(schema)?;
+        writeln!(output, "{DERIVE}")?;
+        writeln!(output, "#[serde(rename_all = \"camelCase\")]")?;
+        writeln!(output, "#[non_exhaustive]")?;
+        writeln!(output, "pub struct {type_name} {{")?;
+        for (key, property) in &properties {
+            let field_override = self.overrides.field_override(name, key);
+            if field_override.is_some_and(|field_override| field_override.skip) {
+                continue;
+            }
+            doc(output, "    ", property.description.as_deref());
+
+            let field_name = match field_override.and_then(|o| o.name.clone()) {
+                Some(field_name) => field_name,
+                None => field_name(key),
+            };
+            let is_keyword = field_name.ends_with('_');
+            if is_keyword || serde_camel_case(&field_name) != *key {
+                writeln!(output, "    #[serde(rename = \"{key}\")]")?;
+            }
+
+            let rust_type = match field_override {
+                Some(field_override) if field_override.type_.is_some() => {
+                    for attribute in &field_override.attributes {
+                        writeln!(output, "    {attribute}")?;
+                    }
+                    field_override.type_.clone().unwrap()
+                }
+                _ => {
+                    if let Some(field_override) = field_override {
+                        for attribute in &field_override.attributes {
+                            writeln!(output, "    {attribute}")?;
+                        }
+                    }
+                    let rust_type = self.rust_type(property)?;
+                    let is_required = required.contains(key);
+                    if !is_required {
+                        writeln!(output, "    #[serde(default)]")?;
+                    }
+                    if property.nullable || !is_required {
+                        format!("Option<{rust_type}>")
+                    } else {
+                        rust_type
+                    }
+                }
+            };
+            writeln!(output, "    pub {field_name}: {rust_type},")?;
+        }
+        writeln!(output, "    #[cfg(feature = \"extra-fields\")]")?;
+        writeln!(output, "    #[serde(flatten)]")?;
+        writeln!(output, "    pub extra: Extra,")?;
+        writeln!(output, "}}")?;
+        Ok(())
+    }
+
+    /// Properties and required fields of object, merged with parts of `allOf`.
+    fn object(&self, schema: &Schema) -> Result<Object> {
+        let mut properties: Vec<(String, Schema)> = vec![];
+        let mut required = vec![];
+        let parts = schema.all_of.iter().map(|part| self.resolve(part));
+        for part in parts {
+            let (part_properties, part_required) = self.object(part?)?;
+            merge(&mut properties, part_properties);
+            required.extend(part_required);
+        }
+        merge(&mut properties, schema.properties.clone());
+        required.extend(schema.required.iter().cloned());
+        Ok((properties, required))
+    }
+
+    fn resolve<'s>(&'s self, schema: &'s Schema) -> Result<&'s Schema> {
+        match schema.reference_name() {
+            Some(name) => self
+                .spec
+                .schema(name)
+                .ok_or_else(|| anyhow!("unknown schema {name}")),
+            None => Ok(schema),
+        }
+    }
+
+    fn rust_type(&self, schema: &Schema) -> Result<String> {
+        if let Some(name) = schema.reference_name() {
+            if self.spec.schema(name).is_none() {
+                bail!("unknown schema {name}");
+            }
+            return Ok(self.type_name(name));
+        }
+        if let [single] = schema.all_of.as_slice() {
+            return self.rust_type(single);
+        }
+        let rust_type = match schema.type_.as_deref() {
+            Some("string") => "String".to_string(),
+            Some("integer") => self
+                .overrides
+                .integer
+                .as_deref()
+                .unwrap_or("i64")
+                .to_string(),
+            Some("number") => "f64".to_string(),
+            Some("boolean") => "bool".to_string(),
+            Some("array") => match &schema.items {
+                Some(items) => format!("Vec<{}>", self.rust_type(items)?),
+                None => "Vec<serde_json::Value>".to_string(),
+            },
+            _ => "serde_json::Value".to_string(),
+        };
+        Ok(rust_type)
+    }
+
+    fn type_name(&self, name: &str) -> String {
+        match self
+            .overrides
+            .type_override(name)
+            .and_then(|type_override| type_override.name.clone())
+        {
+            Some(type_name) => type_name,
+            None => upper_camel_case(name),
+        }
+    }
+}
+
+fn enumeration(output: &mut String, type_name: &str, schema: &Schema) -> Result<()> {
+    writeln!(output, "{DERIVE}")?;
+    writeln!(output, "#[serde(rename_all = \"camelCase\")]")?;
+    writeln!(output, "#[non_exhaustive]")?;
+    writeln!(output, "pub enum {type_name} {{")?;
+    for value in &schema.enum_values {
+        let Some(value) = value.as_str() else {
+            bail!("enum value {value} is not string");
+        };
+        let variant = upper_camel_case(value);
+        if lower_first(&variant) != value {
+            writeln!(output, "    #[serde(rename = \"{value}\")]")?;
+        }
+        writeln!(output, "    {variant},")?;
+    }
+    writeln!(output, "    #[serde(other)]")?;
+    writeln!(output, "    Unknown,")?;
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
+/// Add `properties`, replacing existing ones with the same name.
+fn merge(properties: &mut Vec<(String, Schema)>, new: Vec<(String, Schema)>) {
+    for (key, schema) in new {
+        match properties.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(existing) => existing.1 = schema,
+            None => properties.push((key, schema)),
+        }
+    }
+}
+
+fn doc(output: &mut String, indent: &str, description: Option<&str>) {
+    for line in description.unwrap_or("").lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            output.push_str(&format!("{indent}///\n"));
+        } else {
+            output.push_str(&format!("{indent}/// {line}\n"));
+        }
+    }
+}
+
+/// Split identifier into words on separators and case changes, e.g. `authorNameLF`
+/// into `author`, `Name` and `LF`.
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let starts_word = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_ascii_digit()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+        if starts_word && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+pub fn upper_camel_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Field name, with `_` appended to keywords.
+pub fn field_name(key: &str) -> String {
+    let name = words(key)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+/// Name `serde(rename_all = "camelCase")` gives to field.
+fn serde_camel_case(field: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize = false;
+    for c in field.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            result.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,4 @@
+//! Development tasks of the workspace, run with `cargo xtask <task>`.
+pub mod codegen;
+pub mod openapi;
+pub mod overrides;
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use xtask::openapi::Spec;
+use xtask::overrides::Overrides;
+
+const USAGE: &str = "\
+Usage: cargo xtask schema --spec <openapi.json> [--overrides <file.toml>] [--out <file.rs>]
+
+Generate schema types from audiobookshelf OpenAPI spec. Overrides default to
+xtask/schema_overrides.toml, output is printed unless --out is given.";
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("schema") => schema(args),
+        _ => bail!("{USAGE}"),
+    }
+}
+
+fn schema(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let mut spec = None;
+    let mut overrides = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schema_overrides.toml");
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .with_context(|| format!("{arg} needs value\n\n{USAGE}"))?;
+        match arg.as_str() {
+            "--spec" => spec = Some(PathBuf::from(value)),
+            "--overrides" => overrides = PathBuf::from(value),
+            "--out" => out = Some(PathBuf::from(value)),
+            _ => bail!("Unknown argument {arg}\n\n{USAGE}"),
+        }
+    }
+    let Some(spec) = spec else {
+        bail!("{USAGE}");
+    };
+
+    let text = std::fs::read_to_string(&spec)
+        .with_context(|| format!("Unable to read spec {}", spec.display()))?;
+    let spec: Spec = serde_json::from_str(&text).context("Invalid OpenAPI spec")?;
+    let text = std::fs::read_to_string(&overrides)
+        .with_context(|| format!("Unable to read overrides {}", overrides.display()))?;
+    let overrides = Overrides::parse(&text).context("Invalid overrides")?;
+
+    let output = xtask::codegen::generate(&spec, &overrides)?;
+    match out {
+        Some(out) => std::fs::write(&out, output)
+            .with_context(|| format!("Unable to write {}", out.display()))?,
+        None => print!("{output}"),
+    }
+    Ok(())
+}
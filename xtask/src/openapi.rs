@@ -0,0 +1,92 @@
+//! Subset of OpenAPI 3 spec, describing schema components.
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{DeserializeOwned, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Spec {
+    #[serde(default)]
+    pub components: Components,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Components {
+    /// In order of spec
+    #[serde(default, deserialize_with = "ordered")]
+    pub schemas: Vec<(String, Schema)>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    #[serde(rename = "$ref")]
+    pub reference: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub format: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub nullable: bool,
+    /// In order of spec
+    #[serde(default, deserialize_with = "ordered")]
+    pub properties: Vec<(String, Schema)>,
+    #[serde(default)]
+    pub required: Vec<String>,
+    pub items: Option<Box<Schema>>,
+    #[serde(default, rename = "enum")]
+    pub enum_values: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub all_of: Vec<Schema>,
+    #[serde(default)]
+    pub one_of: Vec<Schema>,
+    #[serde(default)]
+    pub any_of: Vec<Schema>,
+}
+
+impl Spec {
+    pub fn schema(&self, name: &str) -> Option<&Schema> {
+        self.components
+            .schemas
+            .iter()
+            .find(|(schema_name, _)| schema_name == name)
+            .map(|(_, schema)| schema)
+    }
+}
+
+impl Schema {
+    /// Name of component referenced with `#/components/schemas/<name>`.
+    pub fn reference_name(&self) -> Option<&str> {
+        self.reference
+            .as_deref()
+            .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+    }
+}
+
+/// Deserialize JSON object into list of entries, keeping their order.
+fn ordered<'de, D, T>(deserializer: D) -> Result<Vec<(String, T)>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    struct OrderedVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: DeserializeOwned> Visitor<'de> for OrderedVisitor<T> {
+        type Value = Vec<(String, T)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("object")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = vec![];
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(OrderedVisitor(PhantomData))
+}
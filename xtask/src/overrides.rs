@@ -0,0 +1,78 @@
+//! Hand-written corrections of generated schema, read from TOML file.
+//!
+//! ```toml
+//! # Type of integers without more specific override
+//! integer = "u64"
+//!
+//! # Applies to field of that name in every type
+//! [fields.addedAt]
+//! type = "DateTime<Utc>"
+//! attributes = ['#[serde(with = "timestamp")]']
+//!
+//! [types.libraryItemMinified]
+//! name = "LibraryItemMinified"
+//!
+//! [types.libraryItemMinified.fields.media]
+//! type = "LibraryMediaMinified"
+//! attributes = ["#[serde(flatten)]"]
+//!
+//! # Written by hand in schema.rs
+//! [types.playbackSession]
+//! skip = true
+//! ```
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    pub integer: Option<String>,
+    #[serde(default)]
+    pub fields: HashMap<String, FieldOverride>,
+    #[serde(default)]
+    pub types: HashMap<String, TypeOverride>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TypeOverride {
+    /// Rust name, instead of spec name converted to `UpperCamelCase`
+    pub name: Option<String>,
+    #[serde(default)]
+    pub skip: bool,
+    #[serde(default)]
+    pub fields: HashMap<String, FieldOverride>,
+}
+
+/// Overridden field is used exactly as given, without wrapping into `Option`.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FieldOverride {
+    /// Rust name, instead of spec name converted to `snake_case`
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    #[serde(default)]
+    pub skip: bool,
+}
+
+impl Overrides {
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn type_override(&self, schema: &str) -> Option<&TypeOverride> {
+        self.types.get(schema)
+    }
+
+    /// Override of field in given type, falling back to override of all fields with that name.
+    pub fn field_override(&self, schema: &str, field: &str) -> Option<&FieldOverride> {
+        self.types
+            .get(schema)
+            .and_then(|type_override| type_override.fields.get(field))
+            .or_else(|| self.fields.get(field))
+    }
+}
@@ -0,0 +1,118 @@
+use xtask::codegen::generate;
+use xtask::openapi::Spec;
+use xtask::overrides::Overrides;
+
+const SPEC: &str = r##"{
+  "components": {
+    "schemas": {
+      "mediaType": {
+        "type": "string",
+        "enum": ["book", "podcast", "audio-file"]
+      },
+      "libraryBase": {
+        "type": "object",
+        "properties": {
+          "id": {"type": "string"},
+          "addedAt": {"type": "integer"}
+        },
+        "required": ["id", "addedAt"]
+      },
+      "library": {
+        "description": "Library of books or podcasts.",
+        "allOf": [
+          {"$ref": "#/components/schemas/libraryBase"},
+          {
+            "properties": {
+              "mediaType": {"$ref": "#/components/schemas/mediaType"},
+              "type": {"type": "string"},
+              "authorNameLF": {"type": "string", "nullable": true},
+              "tags": {"type": "array", "items": {"type": "string"}},
+              "size": {"type": "integer"},
+              "internal": {"type": "boolean"}
+            },
+            "required": ["mediaType", "type", "authorNameLF", "tags"]
+          }
+        ]
+      },
+      "hidden": {"type": "object"}
+    }
+  }
+}"##;
+
+const OVERRIDES: &str = r##"
+integer = "u64"
+
+[fields.addedAt]
+type = "DateTime<Utc>"
+attributes = ['#[serde(with = "timestamp")]']
+
+[types.library.fields.internal]
+skip = true
+
+[types.hidden]
+skip = true
+"##;
+
+fn output() -> String {
+    let spec: Spec = serde_json::from_str(SPEC).unwrap();
+    let overrides = Overrides::parse(OVERRIDES).unwrap();
+    generate(&spec, &overrides).unwrap()
+}
+
+#[test]
+fn generates_enum() {
+    let output = output();
+    let expected = "\
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = \"camelCase\")]
+#[non_exhaustive]
+pub enum MediaType {
+    Book,
+    Podcast,
+    #[serde(rename = \"audio-file\")]
+    AudioFile,
+    #[serde(other)]
+    Unknown,
+}
+";
+    assert!(output.contains(expected), "{output}");
+}
+
+#[test]
+fn generates_struct_with_overrides() {
+    let output = output();
+    let expected = "\
+/// Library of books or podcasts.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = \"camelCase\")]
+#[non_exhaustive]
+pub struct Library {
+    pub id: String,
+    #[serde(with = \"timestamp\")]
+    pub added_at: DateTime<Utc>,
+    pub media_type: MediaType,
+    #[serde(rename = \"type\")]
+    pub type_: String,
+    #[serde(rename = \"authorNameLF\")]
+    pub author_name_lf: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[cfg(feature = \"extra-fields\")]
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+";
+    assert!(output.contains(expected), "{output}");
+    assert!(!output.contains("Hidden"), "{output}");
+}
+
+#[test]
+fn reports_unknown_reference() {
+    let spec: Spec = serde_json::from_str(
+        r##"{"components": {"schemas": {"a": {"type": "array", "items": {"$ref": "#/components/schemas/b"}}}}}"##,
+    )
+    .unwrap();
+    let error = generate(&spec, &Overrides::default()).unwrap_err();
+    assert_eq!(error.to_string(), "a: unknown schema b");
+}